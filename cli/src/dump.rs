@@ -6,7 +6,9 @@ use std::{
 use anyhow::Result;
 use clap::Args;
 use engine::storage::PersistedEntity;
-use kernel::prelude::{CoreProps, Entity, EntityGid, EntityKey, JsonValue, LookupBy};
+use kernel::prelude::{
+    redact_entity_json, CoreProps, Entity, EntityGid, EntityKey, JsonValue, LookupBy,
+};
 
 use crate::DomainBuilder;
 
@@ -22,6 +24,9 @@ pub struct Command {
     name: Option<String>,
     #[arg(short, long)]
     gid: Option<u64>,
+    /// Redact entities as they would be seen by this actor's key.
+    #[arg(long)]
+    actor: Option<String>,
 }
 
 impl Command {
@@ -106,6 +111,22 @@ pub async fn execute_command(cmd: &Command) -> Result<()> {
                 .collect(),
         },
     };
+    let entities: Vec<PersistedEntity> = match &cmd.actor {
+        Some(actor) => {
+            let actor = EntityKey::new(actor);
+            entities
+                .into_iter()
+                .map(|mut p| {
+                    let mut value = p.to_json_value()?;
+                    redact_entity_json(&actor, &mut value);
+                    p.serialized = value.to_string();
+                    Ok(p)
+                })
+                .collect::<Result<Vec<_>>>()?
+        }
+        None => entities,
+    };
+
     if cmd.lines {
         for entity in entities {
             io::stdout().write_all(entity.serialized.as_bytes())?;
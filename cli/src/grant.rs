@@ -0,0 +1,54 @@
+use anyhow::{anyhow, Result};
+use clap::Args;
+use tracing::*;
+
+use crate::DomainBuilder;
+use engine::prelude::{DevNullNotifier, HasRoles, HasUsernames, SessionOpener};
+use kernel::prelude::{LookupBy, Role};
+
+#[derive(Debug, Args, Clone)]
+pub struct Command {
+    #[arg(short, long, value_name = "FILE")]
+    path: Option<String>,
+    #[arg(short, long)]
+    username: String,
+    #[arg(short, long, default_value_t = Role::Admin)]
+    role: Role,
+    #[arg(long)]
+    revoke: bool,
+}
+
+impl Command {
+    fn builder(&self) -> DomainBuilder {
+        DomainBuilder::new(self.path.clone())
+    }
+}
+
+/// Grants roles directly, without needing to be an admin, which is how the
+/// first admin gets their role.
+#[tokio::main]
+pub async fn execute_command(cmd: &Command) -> Result<()> {
+    let domain = cmd.builder().build().await?;
+    let session = domain.open_session()?;
+    let session = session.set_session()?;
+
+    let world = session.world()?.expect("No world");
+    let Some(key) = world.find_name_key(&cmd.username)? else {
+        return Err(anyhow!("no user named {}", cmd.username));
+    };
+    let user = session
+        .entity(&LookupBy::Key(&key))?
+        .expect("No user entity");
+
+    if cmd.revoke {
+        info!(username = %cmd.username, role = %cmd.role, "revoking");
+        user.revoke_role(cmd.role)?;
+    } else {
+        info!(username = %cmd.username, role = %cmd.role, "granting");
+        user.grant_role(cmd.role)?;
+    }
+
+    session.close(&DevNullNotifier::default())?;
+
+    Ok(())
+}
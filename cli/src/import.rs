@@ -1,10 +1,10 @@
 use std::{collections::HashMap, rc::Rc};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use clap::Args;
 
-use engine::storage::{Storage, StorageFactory};
-use kernel::prelude::{Entity, EntityKey, LookupBy, OpenScopeMut};
+use engine::storage::{PersistedEntity, Storage, StorageFactory};
+use kernel::prelude::{CoreProps, Entity, EntityKey, LookupBy, OpenScopeMut};
 use plugins_rune::{Behaviors, RUNE_EXTENSION};
 use tracing::*;
 
@@ -31,14 +31,30 @@ pub async fn execute_command(cmd: &Command) -> Result<()> {
     let factory = builder.storage_factory()?;
     let storage = factory.create_storage()?;
 
+    import_directory(storage, &cmd.from)
+}
+
+/// Imports the entities and scripts in a directory created by exporting.
+pub fn import_directory(storage: Rc<dyn Storage>, from: &str) -> Result<()> {
     let mut importer = Importer::new(storage);
 
     importer.begin()?;
 
-    for entry in walkdir::WalkDir::new(&cmd.from).sort_by_key(|a| a.file_type().is_dir()) {
+    // Worlds served from a directory keep their own state in hidden
+    // directories, and futures alongside entities.
+    let entries = walkdir::WalkDir::new(from)
+        .sort_by_key(|a| a.file_type().is_dir())
+        .into_iter()
+        .filter_entry(|e| e.depth() == 0 || !e.file_name().to_string_lossy().starts_with('.'));
+
+    for entry in entries {
         let entry = entry?;
         let path = entry.path();
 
+        if entry.depth() == 1 && entry.file_name() == directory::FUTURES_FILE {
+            continue;
+        }
+
         if !entry.file_type().is_dir() {
             match path.extension() {
                 Some(e) => {
@@ -99,15 +115,17 @@ impl Importer {
                 loaded
             }
             None => {
-                todo!("assign gid to new entities");
-                /*
-                    PersistedEntity {
+                let entity = Entity::from_value(data.clone())?;
+                let Some(gid) = entity.gid() else {
+                    return Err(anyhow!("new entity {} has no gid", key));
+                };
+
+                PersistedEntity {
                     key: key.to_string(),
-                    gid: todo!("assign gid to new entities"),
+                    gid: gid.into(),
                     version: 1,
                     serialized: data.to_string(),
                 }
-                */
             }
         };
 
@@ -8,26 +8,33 @@ use std::{error::Error, sync::Arc};
 use tracing::*;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-use engine::{prelude::Domain, sequences::Sequence, storage::StorageFactory};
+use engine::{
+    domain::DEFAULT_CONFLICT_RETRIES,
+    prelude::Domain,
+    sequences::Sequence,
+    storage::{MailboxLimits, StorageFactory, DEFAULT_VERSION_RETENTION},
+};
 use kernel::prelude::{EntityKey, Identity, RegisteredPlugins};
 use plugins_core::{
     building::BuildingPluginFactory, carrying::CarryingPluginFactory, chat::ChatPluginFactory,
-    emote::EmotePluginFactory, fashion::FashionPluginFactory, helping::HelpingPluginFactory,
-    location::LocationPluginFactory, looking::LookingPluginFactory, memory::MemoryPluginFactory,
-    moving::MovingPluginFactory, sched::SchedulingPluginFactory, security::SecurityPluginFactory,
-    DefaultFinder,
+    choosing::ChoosingPluginFactory, consuming::ConsumingPluginFactory, emote::EmotePluginFactory,
+    fashion::FashionPluginFactory, helping::HelpingPluginFactory, location::LocationPluginFactory,
+    locking::LockingPluginFactory, looking::LookingPluginFactory, memory::MemoryPluginFactory,
+    moderation::ModerationPluginFactory, moving::MovingPluginFactory,
+    sched::SchedulingPluginFactory, security::SecurityPluginFactory, DefaultFinder,
 };
 use plugins_dynlib::DynamicPluginFactory;
 use plugins_rpc::RpcPluginFactory;
-use plugins_rune::RunePluginFactory;
-use sqlite::Factory;
+use plugins_rune::{RunePluginFactory, RUNE_EXTENSION};
 
 mod dump;
 mod eval;
 mod export;
+mod grant;
 mod hacking;
 mod import;
 mod migrate;
+mod replay;
 mod serve;
 mod shell;
 mod terminal;
@@ -51,6 +58,8 @@ enum Commands {
     Migrate(migrate::Command),
     Export(export::Command),
     Import(import::Command),
+    Replay(replay::Command),
+    Grant(grant::Command),
     Schema,
     Hacking,
 }
@@ -126,18 +135,30 @@ impl Sequence<Identity> for Ed25519Identities {
 
 struct DomainBuilder {
     path: Option<String>,
+    directory: Option<String>,
     dynlib: bool,
     rune: bool,
     rpc: bool,
+    conflict_retries: usize,
+    version_retention: usize,
+    entity_cache: bool,
+    revalidate_entity_cache: bool,
+    mailbox_limits: MailboxLimits,
 }
 
 impl Default for DomainBuilder {
     fn default() -> Self {
         Self {
             path: None,
+            directory: None,
             dynlib: true,
             rune: true,
             rpc: false,
+            conflict_retries: DEFAULT_CONFLICT_RETRIES,
+            version_retention: DEFAULT_VERSION_RETENTION,
+            entity_cache: false,
+            revalidate_entity_cache: false,
+            mailbox_limits: Default::default(),
         }
     }
 }
@@ -150,8 +171,63 @@ impl DomainBuilder {
         }
     }
 
-    pub fn storage_factory(&self) -> Result<sqlite::Factory> {
-        Factory::new(self.path.as_ref().unwrap_or(&"world.sqlite3".to_owned()))
+    pub fn conflict_retries(self, conflict_retries: usize) -> DomainBuilder {
+        Self {
+            conflict_retries,
+            ..self
+        }
+    }
+
+    pub fn version_retention(self, version_retention: usize) -> DomainBuilder {
+        Self {
+            version_retention,
+            ..self
+        }
+    }
+
+    pub fn entity_cache(self, entity_cache: bool) -> DomainBuilder {
+        Self {
+            entity_cache,
+            ..self
+        }
+    }
+
+    pub fn revalidate_entity_cache(self, revalidate_entity_cache: bool) -> DomainBuilder {
+        Self {
+            revalidate_entity_cache,
+            ..self
+        }
+    }
+
+    pub fn mailbox_limits(self, mailbox_limits: MailboxLimits) -> DomainBuilder {
+        Self {
+            mailbox_limits,
+            ..self
+        }
+    }
+
+    pub fn directory(self, directory: Option<String>) -> DomainBuilder {
+        Self { directory, ..self }
+    }
+
+    pub fn storage_factory(&self) -> Result<Arc<dyn StorageFactory>> {
+        if let Some(directory) = &self.directory {
+            return Ok(Arc::new(
+                directory::Factory::new(directory)?
+                    .with_script_file(
+                        &format!("entry.{}", RUNE_EXTENSION),
+                        &format!("/scopes/behaviors/langs/{}/entry", RUNE_EXTENSION),
+                    )?
+                    .with_version_retention(self.version_retention)
+                    .with_mailbox_limits(self.mailbox_limits.clone()),
+            ));
+        }
+
+        Ok(Arc::new(
+            sqlite::Factory::new(self.path.as_ref().unwrap_or(&"world.sqlite3".to_owned()))?
+                .with_version_retention(self.version_retention)
+                .with_mailbox_limits(self.mailbox_limits.clone()),
+        ))
     }
 
     pub async fn build(&self) -> Result<Domain> {
@@ -170,23 +246,35 @@ impl DomainBuilder {
         registered_plugins.register(EmotePluginFactory::default());
         registered_plugins.register(MovingPluginFactory::default());
         registered_plugins.register(CarryingPluginFactory::default());
+        registered_plugins.register(LockingPluginFactory::default());
+        registered_plugins.register(ConsumingPluginFactory::default());
         registered_plugins.register(FashionPluginFactory::default());
         registered_plugins.register(MemoryPluginFactory::default());
         registered_plugins.register(SecurityPluginFactory::default());
+        registered_plugins.register(ModerationPluginFactory::default());
         registered_plugins.register(HelpingPluginFactory::default());
         registered_plugins.register(BuildingPluginFactory::default());
         registered_plugins.register(SchedulingPluginFactory::default());
         registered_plugins.register(LocationPluginFactory::default());
+        registered_plugins.register(ChoosingPluginFactory::default());
         let finder = Arc::new(DefaultFinder::default());
-        let storage_factory = Arc::new(self.storage_factory()?);
+        let storage_factory = self.storage_factory()?;
         storage_factory.migrate()?;
-        Ok(Domain::new(
+        let domain = Domain::new(
             storage_factory,
             Arc::new(registered_plugins),
             finder,
             Arc::new(NanoIds {}),
             Arc::new(Ed25519Identities {}),
-        ))
+        )
+        .with_conflict_retries(self.conflict_retries);
+        if self.revalidate_entity_cache {
+            Ok(domain.with_revalidated_entity_cache())
+        } else if self.entity_cache {
+            Ok(domain.with_entity_cache())
+        } else {
+            Ok(domain)
+        }
     }
 }
 
@@ -235,6 +323,8 @@ fn main() -> Result<(), Box<dyn Error>> {
         Some(Commands::Migrate(cmd)) => Ok(migrate::execute_command(cmd)?),
         Some(Commands::Export(cmd)) => Ok(export::execute_command(cmd)?),
         Some(Commands::Import(cmd)) => Ok(import::execute_command(cmd)?),
+        Some(Commands::Replay(cmd)) => Ok(replay::execute_command(cmd)?),
+        Some(Commands::Grant(cmd)) => Ok(grant::execute_command(cmd)?),
         Some(Commands::Hacking) => Ok(hacking::execute_command()?),
         Some(Commands::Schema) => Ok(schema::execute_command()?),
         None => Ok(()),
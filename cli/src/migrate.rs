@@ -11,7 +11,10 @@ use plugins_rune::Behaviors;
 use tracing::info;
 
 use crate::DomainBuilder;
-use engine::{prelude::DevNullNotifier, prelude::SessionOpener, storage::StorageFactory};
+use engine::{
+    prelude::{Credentials, DevNullNotifier, SessionOpener},
+    storage::StorageFactory,
+};
 use kernel::prelude::{
     DomainError, EntityKey, EntityPtr, EntityPtrResolver, LoadAndStoreScope, LookupBy, OpenScope,
     OpenScopeRefMut, Properties, Scope,
@@ -29,6 +32,8 @@ pub struct Command {
     erase: bool,
     #[arg(long)]
     rename: Option<String>,
+    #[arg(long)]
+    upgrade: bool,
 }
 
 impl Command {
@@ -60,7 +65,7 @@ pub async fn execute_command(cmd: &Command) -> Result<()> {
     let factory = builder.storage_factory()?;
     let storage = factory.create_storage()?;
 
-    if cmd.scopes || cmd.scope.is_some() {
+    if cmd.scopes || cmd.scope.is_some() || cmd.upgrade {
         info!("loading keys...");
 
         let entities = storage.query_all()?;
@@ -86,6 +91,13 @@ pub async fn execute_command(cmd: &Command) -> Result<()> {
                     }
                 }
 
+                if cmd.upgrade {
+                    let upgraded = domain.scope_upgrades().upgrade(&mut entity.borrow_mut())?;
+                    if upgraded > 0 {
+                        info!(%upgraded, "upgraded {:?}", key);
+                    }
+                }
+
                 if cmd.scopes {
                     load_and_save_scope::<Properties>(&entity)?;
                     load_and_save_scope::<Location>(&entity)?;
@@ -97,6 +109,7 @@ pub async fn execute_command(cmd: &Command) -> Result<()> {
                     load_and_save_scope::<Wearing>(&entity)?;
                     load_and_save_scope::<Mind>(&entity)?;
                     load_and_save_scope::<Behaviors>(&entity)?;
+                    load_and_save_scope::<Credentials>(&entity)?;
                 }
             }
         }
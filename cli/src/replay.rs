@@ -0,0 +1,60 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use clap::Args;
+use std::fs::OpenOptions;
+use tracing::*;
+
+use engine::{prelude::DevNullNotifier, storage::StorageFactory};
+
+use crate::{import::import_directory, DomainBuilder};
+
+#[derive(Debug, Args, Clone)]
+pub struct Command {
+    #[arg(short, long, value_name = "FILE")]
+    path: Option<String>,
+    #[arg(long)]
+    from: String,
+    #[arg(long)]
+    to: String,
+    /// When the world being replayed into was copied, entries from before
+    /// then are already part of it.
+    #[arg(long)]
+    since: DateTime<Utc>,
+}
+
+impl Command {
+    fn builder(&self) -> DomainBuilder {
+        DomainBuilder::new(self.path.clone())
+    }
+}
+
+#[tokio::main]
+pub async fn execute_command(cmd: &Command) -> Result<()> {
+    let journal = cmd.builder().storage_factory()?.create_storage()?;
+    let entries = journal
+        .query_journal()?
+        .into_iter()
+        .filter(|e| e.time > cmd.since)
+        .collect::<Vec<_>>();
+
+    // Replaying into an existing world would only make a mess of it.
+    OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&cmd.to)?;
+
+    let builder = DomainBuilder::new(Some(cmd.to.clone()));
+    let factory = builder.storage_factory()?;
+    factory.migrate()?;
+
+    import_directory(factory.create_storage()?, &cmd.from)?;
+
+    let domain = builder.build().await?;
+    let replayed = domain.replay(&entries, &DevNullNotifier::default())?;
+
+    info!(%replayed, entries = %entries.len(), "replayed");
+
+    domain.stop()?;
+
+    Ok(())
+}
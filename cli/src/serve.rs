@@ -7,6 +7,12 @@ use tokio::signal;
 use tokio::time::sleep;
 use tracing::*;
 
+use engine::{
+    domain::DEFAULT_CONFLICT_RETRIES,
+    storage::DEFAULT_VERSION_RETENTION,
+    storage::{MailboxLimits, DEFAULT_MAILBOX_CAPACITY, DEFAULT_MAILBOX_TTL_HOURS},
+};
+
 use crate::DomainBuilder;
 
 mod handlers;
@@ -23,11 +29,34 @@ use ws::*;
 pub struct Command {
     #[arg(short, long, value_name = "FILE")]
     path: Option<String>,
+    #[arg(long, value_name = "DIR", conflicts_with = "path")]
+    directory: Option<String>,
+    #[arg(long, default_value_t = DEFAULT_CONFLICT_RETRIES)]
+    conflict_retries: usize,
+    #[arg(long, default_value_t = DEFAULT_VERSION_RETENTION)]
+    version_retention: usize,
+    #[arg(long)]
+    entity_cache: bool,
+    #[arg(long, requires = "entity_cache")]
+    revalidate_entity_cache: bool,
+    #[arg(long, default_value_t = DEFAULT_MAILBOX_CAPACITY)]
+    mailbox_capacity: usize,
+    #[arg(long, default_value_t = DEFAULT_MAILBOX_TTL_HOURS)]
+    mailbox_ttl_hours: i64,
 }
 
 impl Command {
     fn builder(&self) -> DomainBuilder {
         DomainBuilder::new(self.path.clone())
+            .directory(self.directory.clone())
+            .conflict_retries(self.conflict_retries)
+            .version_retention(self.version_retention)
+            .entity_cache(self.entity_cache)
+            .revalidate_entity_cache(self.revalidate_entity_cache)
+            .mailbox_limits(MailboxLimits {
+                capacity: self.mailbox_capacity,
+                ttl: chrono::Duration::hours(self.mailbox_ttl_hours),
+            })
     }
 }
 
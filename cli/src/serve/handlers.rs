@@ -118,6 +118,8 @@ pub struct RegisterUser {
     pub email: String,
     pub name: String,
     pub password: String,
+    #[serde(default)]
+    pub invite: Option<String>,
 }
 
 #[derive(Deserialize)]
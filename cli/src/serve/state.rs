@@ -1,6 +1,8 @@
 use anyhow::Context;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::Arc;
 use tokio::sync::broadcast;
 use tokio::sync::Mutex;
 use tracing::*;
@@ -10,6 +12,7 @@ use kernel::prelude::*;
 
 use plugins_core::carrying::model::Containing;
 use plugins_core::fashion::model::Wearing;
+use plugins_core::security::model::Invitations;
 use plugins_core::tools;
 use plugins_rune::Behaviors;
 
@@ -21,51 +24,98 @@ pub struct AppState {
     pub tick_deadline: Mutex<Option<DateTime<Utc>>>,
     pub tx: broadcast::Sender<ServerMessage>,
     pub env: Config,
+    /// Connected players and how many connections each of them has open.
+    online: Arc<std::sync::Mutex<HashMap<EntityKey, usize>>>,
 }
 
 pub struct Config {
     pub jwt_secret: String,
+    /// When set, registering requires an invitation code.
+    pub require_invite: bool,
 }
 
 impl Config {
     pub fn from_env() -> Option<Self> {
         let jwt_secret = std::env::var("JWT_SECRET").ok()?;
-        Some(Self { jwt_secret })
+        let require_invite = std::env::var("REQUIRE_INVITE")
+            .map(|value| {
+                matches!(
+                    value.trim().to_ascii_lowercase().as_str(),
+                    "1" | "true" | "yes" | "on"
+                )
+            })
+            .unwrap_or(false);
+        Some(Self {
+            jwt_secret,
+            require_invite,
+        })
     }
 }
 
 pub struct SenderNotifier {
     tx: broadcast::Sender<ServerMessage>,
+    online: Arc<std::sync::Mutex<HashMap<EntityKey, usize>>>,
 }
 
-impl Notifier for SenderNotifier {
-    fn notify(&self, audience: &EntityKey, observed: &TaggedJson) -> Result<()> {
+impl SenderNotifier {
+    fn send(&self, audience: &EntityKey, observed: &TaggedJson, sequence: Option<u64>) {
         trace!("notify {:?} -> {:?}", audience, observed);
 
         let serialized = observed.clone().into_tagged();
-        let outgoing = ServerMessage::Notify(audience.to_string(), serialized);
+        let outgoing = ServerMessage::Notify(audience.to_string(), serialized, sequence);
         match self.tx.send(outgoing) {
             Err(e) => warn!("Send failed: {:?}", e),
             Ok(_) => {}
         }
+    }
+}
+
+impl Notifier for SenderNotifier {
+    fn notify(&self, audience: &EntityKey, observed: &TaggedJson) -> Result<()> {
+        self.send(audience, observed, None);
 
         Ok(())
     }
+
+    fn notify_mailed(
+        &self,
+        audience: &EntityKey,
+        observed: &TaggedJson,
+        sequence: u64,
+    ) -> Result<()> {
+        self.send(audience, observed, Some(sequence));
+
+        Ok(())
+    }
+
+    fn is_present(&self, audience: &EntityKey) -> bool {
+        self.online
+            .lock()
+            .expect("Lock error")
+            .contains_key(audience)
+    }
 }
 
 impl AppState {
     pub fn new(domain: Domain) -> Self {
-        let env = Config::from_env().expect("no config");
+        Self::new_with_config(domain, Config::from_env().expect("no config"))
+    }
+
+    pub fn new_with_config(domain: Domain, env: Config) -> Self {
         let (tx, _rx) = broadcast::channel(100);
         AppState {
             domain: domain.clone(),
             tick_deadline: Default::default(),
             tx,
             env,
+            online: Default::default(),
         }
     }
 
     pub fn try_start_session(&self, key: &EntityKey) -> Result<ClientSession> {
+        let mut online = self.online.lock().expect("Lock error");
+        *online.entry(key.clone()).or_default() += 1;
+
         Ok(ClientSession { key: key.clone() })
     }
 
@@ -85,6 +135,7 @@ impl AppState {
     pub fn notifier(&self) -> SenderNotifier {
         SenderNotifier {
             tx: self.tx.clone(),
+            online: self.online.clone(),
         }
     }
 
@@ -122,6 +173,30 @@ impl AppState {
             return Err(anyhow::anyhow!("already registered"));
         }
 
+        match user
+            .invite
+            .as_deref()
+            .map(str::trim)
+            .filter(|c| !c.is_empty())
+        {
+            Some(code) => {
+                let mut invitations = world.scope_mut::<Invitations>()?;
+                let Some(invitation) = invitations.redeem(code, Utc::now()) else {
+                    warn!("invalid invitation");
+                    return Err(anyhow::anyhow!("invalid invitation"));
+                };
+                invitations.save()?;
+
+                info!(inviter = %invitation.inviter, "invited");
+            }
+            None => {
+                if self.env.require_invite {
+                    warn!("invitation required");
+                    return Err(anyhow::anyhow!("invitation required"));
+                }
+            }
+        }
+
         info!("registering");
 
         use argon2::{
@@ -165,9 +240,124 @@ impl AppState {
         Ok(key)
     }
 
-    pub fn remove_session(&self, _session: &ClientSession) {}
+    pub fn remove_session(&self, session: &ClientSession) {
+        let mut online = self.online.lock().expect("Lock error");
+        if let Some(connections) = online.get_mut(&session.key) {
+            *connections -= 1;
+            if *connections == 0 {
+                online.remove(&session.key);
+            }
+        }
+    }
 }
 
 pub struct ClientSession {
     pub key: EntityKey,
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use plugins_core::security::model::Invitation;
+    use plugins_core::BuildSurroundings;
+
+    use super::*;
+
+    fn invited_state(require_invite: bool) -> Result<(BuildSurroundings, AppState)> {
+        let mut build = BuildSurroundings::new()?;
+        let (_session, surroundings) = build.build()?;
+        let (world, actor, area) = surroundings.unpack()?;
+        world.set_welcome_area(&area.key())?;
+
+        let now = Utc::now();
+        let mut invitations = world.scope_mut::<Invitations>()?;
+        for (code, expires) in [
+            ("VALID", now + Duration::days(1)),
+            ("EXPIRED", now - Duration::days(1)),
+        ] {
+            invitations.mint(Invitation {
+                code: code.to_owned(),
+                inviter: actor.key(),
+                created: now - Duration::days(2),
+                expires,
+            });
+        }
+        invitations.save()?;
+
+        build.close()?;
+
+        let domain = build.domain().unwrap().clone();
+        let state = AppState::new_with_config(
+            domain,
+            Config {
+                jwt_secret: "secret".to_owned(),
+                require_invite,
+            },
+        );
+
+        Ok((build, state))
+    }
+
+    fn registering(email: &str, invite: Option<&str>) -> RegisterUser {
+        RegisterUser {
+            email: email.to_owned(),
+            name: email.to_owned(),
+            password: "password".to_owned(),
+            invite: invite.map(|i| i.to_owned()),
+        }
+    }
+
+    #[test]
+    fn it_registers_with_a_valid_invitation() -> Result<()> {
+        let (_build, state) = invited_state(true)?;
+
+        state.register_user(&registering("jacob", Some("valid")))?;
+
+        assert!(state.find_user_key("jacob")?.is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_refuses_to_register_without_a_required_invitation() -> Result<()> {
+        let (_build, state) = invited_state(true)?;
+
+        assert!(state.register_user(&registering("jacob", None)).is_err());
+        assert!(state
+            .register_user(&registering("jacob", Some(" ")))
+            .is_err());
+        assert!(state.find_user_key("jacob")?.is_none());
+
+        let (_build, state) = invited_state(false)?;
+
+        state.register_user(&registering("jacob", None))?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_refuses_to_register_with_an_expired_invitation() -> Result<()> {
+        let (_build, state) = invited_state(true)?;
+
+        assert!(state
+            .register_user(&registering("jacob", Some("EXPIRED")))
+            .is_err());
+        assert!(state.find_user_key("jacob")?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_refuses_to_register_with_a_reused_invitation() -> Result<()> {
+        let (_build, state) = invited_state(true)?;
+
+        state.register_user(&registering("jacob", Some("VALID")))?;
+
+        assert!(state
+            .register_user(&registering("carla", Some("VALID")))
+            .is_err());
+        assert!(state.find_user_key("carla")?.is_none());
+
+        Ok(())
+    }
+}
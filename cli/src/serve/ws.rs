@@ -1,22 +1,20 @@
 use anyhow::Result;
 use axum::{extract::Extension, response::IntoResponse};
 use axum_typed_websockets::{Message, WebSocket, WebSocketUpgrade};
+use chrono::Utc;
 use futures::{
     sink::SinkExt,
     stream::{SplitStream, StreamExt},
 };
 use serde::{Deserialize, Serialize};
-use std::{borrow::Borrow, rc::Rc, sync::Arc, time::Duration};
+use std::{borrow::Borrow, sync::Arc, time::Duration};
 use tokio::task::JoinHandle;
 use tokio::{sync::broadcast, time::timeout};
 use tracing::{info, trace, warn};
 
-use engine::prelude::{EvaluateAs, Notifier, Session, SessionOpener};
-use kernel::prelude::{
-    Effect, EntityKey, EntityPtrResolver, JsonValue, LookupBy, Perform, PerformAction, Performer,
-    TaggedJson,
-};
-use kernel::{common::SimpleReply, session::ActiveSession};
+use engine::prelude::{Domain, EvaluateAs, Notifier};
+use kernel::common::SimpleReply;
+use kernel::prelude::{Effect, EntityKey, JsonValue, TaggedJson};
 
 use crate::serve::{handlers::TokenClaims, ClientSession};
 
@@ -28,6 +26,7 @@ pub enum ClientMessage {
     Token { token: String },
     Evaluate(String),
     Perform(TaggedJson),
+    Acknowledge(u64),
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -37,7 +36,7 @@ pub enum ServerMessage {
     Error(String),
     Welcome { self_key: String },
     Reply(JsonValue),
-    Notify(String, JsonValue),
+    Notify(String, JsonValue, Option<u64>),
     Ping,
 }
 
@@ -84,6 +83,10 @@ async fn handle_socket(stream: WebSocket<ServerMessage, ClientMessage>, state: A
         return;
     };
 
+    // Subscribe before catching up on missed notifications so nothing raised
+    // in between is lost.
+    let mut rx = state.tx.subscribe();
+
     info!("welcome");
     let _ = sender
         .send(Message::Item(ServerMessage::Welcome {
@@ -91,18 +94,28 @@ async fn handle_socket(stream: WebSocket<ServerMessage, ClientMessage>, state: A
         }))
         .await;
 
+    let mut replayed = 0;
+    for (sequence, value) in missed_notifications(&state, &session.key).await {
+        replayed = sequence;
+        let notify = ServerMessage::Notify(session.key.to_string(), value, Some(sequence));
+        if sender.send(Message::Item(notify)).await.is_err() {
+            warn!("replaying:tx:error");
+            return;
+        }
+    }
+
     let our_key = session.key.to_string();
     let (session_tx, _session_rx) = broadcast::channel::<ServerMessage>(10);
 
     // Forward global events to the client if they're the intended audience.
-    let mut rx = state.tx.subscribe();
     let broadcasting_tx = session_tx.clone();
     let mut broadcasting_task = tokio::spawn({
         let our_key = our_key.clone();
         async move {
             while let Ok(server_message) = rx.recv().await {
                 match &server_message {
-                    ServerMessage::Notify(key, _) => {
+                    ServerMessage::Notify(_, _, Some(sequence)) if *sequence <= replayed => {}
+                    ServerMessage::Notify(key, _, _) => {
                         if our_key == *key && broadcasting_tx.send(server_message).is_err() {
                             warn!("broadcasting:tx:error");
                             break;
@@ -130,15 +143,27 @@ async fn handle_socket(stream: WebSocket<ServerMessage, ClientMessage>, state: A
     let mut recv_task = tokio::spawn({
         let state = state.clone();
         async move {
+            // Acknowledgements are saved together once the client goes quiet,
+            // rather than one write for each notification.
+            let mut acknowledging: Option<u64> = None;
+
             loop {
-                let maybe_message = timeout(Duration::from_millis(10000), receiver.next()).await;
+                let waiting = match acknowledging {
+                    Some(_) => Duration::from_millis(1000),
+                    None => Duration::from_millis(10000),
+                };
+                let maybe_message = timeout(waiting, receiver.next()).await;
 
                 let message = match maybe_message {
                     Ok(message) => message,
                     Err(_) => {
-                        session_tx
-                            .send(ServerMessage::Ping)
-                            .expect("Error sending reply");
+                        if let Some(sequence) = acknowledging.take() {
+                            acknowledge(&state, &our_key, sequence).await;
+                        } else {
+                            session_tx
+                                .send(ServerMessage::Ping)
+                                .expect("Error sending reply");
+                        }
                         continue;
                     }
                 };
@@ -157,26 +182,12 @@ async fn handle_socket(stream: WebSocket<ServerMessage, ClientMessage>, state: A
                                     let our_key = our_key.clone();
 
                                     move || {
-                                        let session =
-                                            domain.open_session().expect("Error opening session");
-                                        let action: Rc<_> = session
-                                            .try_deserialize_action(&value)
-                                            .expect("try parse action failed")
-                                            .unwrap()
-                                            .into();
-                                        let actor = session
-                                            .entity(&LookupBy::Key(&EntityKey::new(&our_key)))
-                                            .expect("Actor lookup failed")
-                                            .expect("Actor not found");
-                                        let perform = Perform::Actor {
-                                            actor,
-                                            action: PerformAction::Instance(action),
-                                        };
-                                        trace!("perform {:?}", &perform.enum_name());
-                                        let session = session.set_session()?;
-                                        let effect =
-                                            session.perform(perform).expect("Perform failed");
-                                        session.close(&notifier).expect("Error closing session");
+                                        trace!("perform {:?}", value.tag());
+                                        let effect = domain.perform_as(
+                                            &EntityKey::new(&our_key),
+                                            &value,
+                                            &notifier,
+                                        )?;
                                         Ok(serde_json::to_value(effect)?)
                                     }
                                 });
@@ -185,8 +196,8 @@ async fn handle_socket(stream: WebSocket<ServerMessage, ClientMessage>, state: A
                                 Ok(Ok(reply)) => session_tx
                                     .send(ServerMessage::Reply(reply))
                                     .expect("Error sending reply"),
-                                Ok(Err(e)) => todo!("{:?}", e),
-                                Err(e) => todo!("{:?}", e),
+                                Ok(Err(e)) => warn!("{:?}", e),
+                                Err(e) => warn!("{:?}", e),
                             };
                         }
                         ClientMessage::Evaluate(text) => {
@@ -200,10 +211,8 @@ async fn handle_socket(stream: WebSocket<ServerMessage, ClientMessage>, state: A
                                     let our_key = our_key.clone();
 
                                     move || {
-                                        let session =
-                                            domain.open_session().expect("Error opening session");
                                         let effect = evaluate_commands(
-                                            session,
+                                            &domain,
                                             &notifier,
                                             EvaluateAs::Key(&EntityKey::new(&our_key)),
                                             &text,
@@ -222,12 +231,19 @@ async fn handle_socket(stream: WebSocket<ServerMessage, ClientMessage>, state: A
                                 Err(e) => warn!("{:?}", e),
                             };
                         }
+                        ClientMessage::Acknowledge(sequence) => {
+                            acknowledging = acknowledging.max(Some(sequence));
+                        }
                         _ => todo!(),
                     }
                 } else {
                     break;
                 }
             }
+
+            if let Some(sequence) = acknowledging {
+                acknowledge(&state, &our_key, sequence).await;
+            }
         }
     });
 
@@ -251,8 +267,53 @@ async fn handle_socket(stream: WebSocket<ServerMessage, ClientMessage>, state: A
     state.remove_session(&session);
 }
 
+/// Forgets notifications the player has seen, up to and including the sequence.
+async fn acknowledge(state: &AppState, key: &str, sequence: u64) {
+    let handle: JoinHandle<Result<()>> = tokio::task::spawn_blocking({
+        let domain = state.domain.clone();
+        let key = EntityKey::new(key);
+
+        move || domain.acknowledge(&key, sequence)
+    });
+
+    match handle.await {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => warn!("{:?}", e),
+        Err(e) => warn!("{:?}", e),
+    };
+}
+
+/// Notifications raised for the player that they haven't acknowledged, which
+/// are usually those raised while they weren't connected.
+async fn missed_notifications(state: &AppState, key: &EntityKey) -> Vec<(u64, JsonValue)> {
+    let handle: JoinHandle<Result<Vec<(u64, JsonValue)>>> = tokio::task::spawn_blocking({
+        let domain = state.domain.clone();
+        let key = key.clone();
+
+        move || {
+            domain
+                .query_mailbox(&key, Utc::now())?
+                .into_iter()
+                .map(|mail| Ok((mail.sequence, serde_json::from_str(&mail.serialized)?)))
+                .collect()
+        }
+    });
+
+    match handle.await {
+        Ok(Ok(missed)) => missed,
+        Ok(Err(e)) => {
+            warn!("{:?}", e);
+            Vec::new()
+        }
+        Err(e) => {
+            warn!("{:?}", e);
+            Vec::new()
+        }
+    }
+}
+
 fn evaluate_commands<T>(
-    session: Rc<Session>,
+    domain: &Domain,
     notifier: &T,
     eval_as: EvaluateAs,
     text: &str,
@@ -260,16 +321,8 @@ fn evaluate_commands<T>(
 where
     T: Notifier,
 {
-    let effect: Effect = if let Some(effect) = session
-        .evaluate_and_perform_as(eval_as, text)
-        .expect("Evaluation error")
-    {
-        effect
-    } else {
-        SimpleReply::What.try_into()?
-    };
-
-    session.close(notifier).expect("Error closing session");
-
-    Ok(effect)
+    match domain.evaluate_and_perform_as(eval_as, text, notifier)? {
+        Some(effect) => Ok(effect),
+        None => Ok(SimpleReply::What.try_into()?),
+    }
 }
@@ -139,7 +139,10 @@ impl Renderer {
 
         match self.tree.find_match(value) {
             Some((node, key, value)) => {
-                let name_from_path = node.path().unwrap().to_str().unwrap().to_owned();
+                let Some(path) = node.path() else {
+                    return Err(anyhow::anyhow!("No template for {}", key));
+                };
+                let name_from_path = path.to_string_lossy().into_owned();
                 let mut context = Context::new();
                 context.insert(key, &value);
                 all.push_str(&render(context, &name_from_path)?);
@@ -56,6 +56,35 @@ impl DottedPath {
             self
         }
     }
+
+    pub fn lookup<'v>(&self, value: &'v JsonValue) -> Option<&'v JsonValue> {
+        self.0.iter().try_fold(value, |value, key| value.get(key))
+    }
+
+    /// Removes the value at this path, removing the root leaves an empty
+    /// object in its place.
+    pub fn remove(&self, value: &mut JsonValue) -> Option<JsonValue> {
+        let Some((last, parents)) = self.0.split_last() else {
+            return Some(std::mem::replace(
+                value,
+                JsonValue::Object(Default::default()),
+            ));
+        };
+        let mut cursor = value;
+        for key in parents {
+            cursor = cursor.get_mut(key)?;
+        }
+        cursor.as_object_mut()?.remove(last)
+    }
+
+    pub fn replace(&self, value: &mut JsonValue, replacing: JsonValue) -> Option<JsonValue> {
+        let (last, parents) = self.0.split_last()?;
+        let mut cursor = value;
+        for key in parents {
+            cursor = cursor.get_mut(key)?;
+        }
+        cursor.as_object_mut()?.insert(last.clone(), replacing)
+    }
 }
 
 impl From<Vec<String>> for DottedPath {
@@ -90,7 +119,7 @@ impl FromStr for DottedPath {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct DottedPaths(Vec<DottedPath>);
 
 impl DottedPaths {
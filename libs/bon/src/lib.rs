@@ -11,7 +11,7 @@ pub mod prelude {
         AnyChanges, CompareChanges, CompareError, Modified, Original, TreeDiff,
     };
     pub use crate::dotted::{DottedPath, DottedPaths, JsonValue};
-    pub use crate::perms::{find_acls, AclRule, Acls};
+    pub use crate::perms::{find_acls, redact, AclRule, Acls, Perm, Subject};
     pub use crate::perms::{Attempted, Denied, HasSecurityContext, Policy, SecurityContext};
     pub use crate::scour::{scour, Scoured};
     pub use crate::tagged::{DeserializeTagged, HasTag, TaggedJson, TaggedJsonError, ToTaggedJson};
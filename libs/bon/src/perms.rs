@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    dotted::{DottedPath, JsonValue},
+    dotted::{DottedPath, DottedPaths, JsonValue},
     scour::Scoured,
 };
 
@@ -97,29 +97,38 @@ impl<P> SecurityContext<P> {
 
 impl<P> SecurityContext<P>
 where
-    P: PartialEq,
+    P: PartialEq + ToString,
 {
     pub(crate) fn allows(&self, subject: &Subject) -> bool {
         match subject {
             Subject::Everybody => true,
             Subject::Owner => self.actor == self.owner,
             Subject::Creator => self.actor == self.creator,
-            Subject::Principal(_p) => todo!(),
+            Subject::Principal(p) => self.actor.to_string() == *p,
         }
     }
 }
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum Attempted {
+    Read(DottedPath),
     Write(DottedPath),
 }
 
 impl Attempted {
     fn perm(&self) -> Perm {
         match self {
+            Attempted::Read(_) => Perm::Read,
             Attempted::Write(_) => Perm::Write,
         }
     }
+
+    fn path(&self) -> &DottedPath {
+        match self {
+            Attempted::Read(path) => path,
+            Attempted::Write(path) => path,
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -140,45 +149,69 @@ impl<'a, P> Policy<'a, P> {
 
 impl<'a, P> Policy<'a, P>
 where
-    P: PartialEq,
+    P: PartialEq + ToString,
 {
     pub fn allows(&self, attempted: Attempted) -> Option<Denied> {
         if self.acls.is_empty() {
             return None;
         }
 
-        match &attempted {
-            Attempted::Write(path) => {
-                let applying = self
-                    .acls
-                    .iter()
-                    .filter(|v| v.path.drop_last().is_parent_of(path));
-
-                let mut rulings: Vec<Option<Option<Denied>>> = Vec::new();
-
-                for scoured in applying {
-                    for rule in scoured
-                        .value
-                        .rules
-                        .iter()
-                        .filter(|rule| rule.perm == attempted.perm())
-                    {
-                        for sub in rule.sub.iter() {
-                            if self.context.allows(sub) {
-                                rulings.push(Some(None))
-                            } else {
-                                rulings.push(Some(Some(Denied::Disallowed)))
-                            }
-                        }
+        let path = attempted.path();
+        let applying = self
+            .acls
+            .iter()
+            .filter(|v| v.path.drop_last().is_parent_of(path));
+
+        let mut rulings: Vec<Option<Option<Denied>>> = Vec::new();
+
+        for scoured in applying {
+            for rule in scoured
+                .value
+                .rules
+                .iter()
+                .filter(|rule| rule.perm == attempted.perm())
+            {
+                for sub in rule.sub.iter() {
+                    if self.context.allows(sub) {
+                        rulings.push(Some(None))
+                    } else {
+                        rulings.push(Some(Some(Denied::Disallowed)))
                     }
                 }
-
-                rulings.into_iter().flatten().flatten().next()
             }
         }
+
+        rulings.into_iter().flatten().flatten().next()
     }
 }
 
+/// Removes everything from `value` the actor in `context` is unable to read,
+/// returning the paths that were removed. Owners are able to read everything.
+pub fn redact<P>(value: &mut JsonValue, context: SecurityContext<P>) -> DottedPaths
+where
+    P: PartialEq + ToString,
+{
+    if context.actor == context.owner {
+        return DottedPaths::default();
+    }
+
+    let Some(acls) = find_acls(value) else {
+        return DottedPaths::default();
+    };
+
+    let policy = Policy::new(&acls, context);
+    let unreadable: Vec<DottedPath> = acls
+        .iter()
+        .map(|scoured| scoured.path.drop_last())
+        .filter(|path| policy.allows(Attempted::Read(path.clone())).is_some())
+        .collect();
+
+    unreadable
+        .into_iter()
+        .filter(|path| path.remove(value).is_some())
+        .collect()
+}
+
 pub trait HasSecurityContext<P> {
     fn security_context(&self) -> SecurityContext<P>;
 }
@@ -44,6 +44,22 @@ mod application {
         let v = allowing.allows(Attempted::Write("scopes.props.desc.value".into()));
         assert_eq!(v, None);
     }
+
+    #[test]
+    pub fn it_should_restrict_writes_to_principal() {
+        let acls = vec![Scoured::new(
+            "scopes.props.name.acls".into(),
+            Acls::from_iter([(Perm::Write, Subject::Principal("jacob".to_owned()))].into_iter()),
+        )];
+
+        let failing = Policy::new(&acls, SecurityContext::new("actor", "owner", "creator"));
+        let v = failing.allows(Attempted::Write("scopes.props.name.value".into()));
+        assert_eq!(v, Some(Denied::Disallowed));
+
+        let allowing = Policy::new(&acls, SecurityContext::new("jacob", "owner", "creator"));
+        let v = allowing.allows(Attempted::Write("scopes.props.name.value".into()));
+        assert_eq!(v, None);
+    }
 }
 
 mod parsing {
@@ -125,4 +141,110 @@ mod parsing {
             ])
         );
     }
+
+    #[test]
+    pub fn it_should_return_acls_nested_inside_tagged_acls() {
+        let acls = Acls::default();
+        let i = json!({
+            "acls": acls,
+            "nested": {
+                "acls": acls,
+            }
+        });
+        assert_eq!(
+            find_acls(&i),
+            Some(vec![
+                Scoured {
+                    path: "acls".into(),
+                    value: Acls::default()
+                },
+                Scoured {
+                    path: "nested.acls".into(),
+                    value: Acls::default()
+                }
+            ])
+        );
+    }
+}
+
+mod redaction {
+    use super::super::{redact, Acls, Perm, SecurityContext, Subject};
+    use serde_json::json;
+
+    fn private() -> Acls {
+        Acls::from_iter([(Perm::Read, Subject::Owner)].into_iter())
+    }
+
+    #[test]
+    pub fn it_should_leave_readable_paths() {
+        let mut value = json!({
+            "scopes": {
+                "props": { "acls": Acls::default(), "name": "Jacob" }
+            }
+        });
+        let original = value.clone();
+        let redacted = redact(
+            &mut value,
+            SecurityContext::new("actor", "owner", "creator"),
+        );
+        assert!(redacted.is_empty());
+        assert_eq!(value, original);
+    }
+
+    #[test]
+    pub fn it_should_remove_unreadable_paths() {
+        let mut value = json!({
+            "scopes": {
+                "props": { "name": "Jacob" },
+                "credentials": { "acls": private(), "passwords": { "web": "hash" } }
+            }
+        });
+        let redacted = redact(
+            &mut value,
+            SecurityContext::new("actor", "owner", "creator"),
+        );
+        let redacted: Vec<String> = redacted.into();
+        assert_eq!(redacted, vec!["scopes.credentials".to_owned()]);
+        assert_eq!(
+            value,
+            json!({
+                "scopes": {
+                    "props": { "name": "Jacob" },
+                }
+            })
+        );
+    }
+
+    #[test]
+    pub fn it_should_remove_everything_when_the_root_is_unreadable() {
+        let mut value = json!({
+            "acls": private(),
+            "scopes": {
+                "props": { "name": "Jacob" }
+            }
+        });
+        let redacted = redact(
+            &mut value,
+            SecurityContext::new("actor", "owner", "creator"),
+        );
+        let redacted: Vec<String> = redacted.into();
+        assert_eq!(redacted, vec!["".to_owned()]);
+        assert_eq!(value, json!({}));
+    }
+
+    #[test]
+    pub fn it_should_allow_owners_to_read_everything() {
+        let mut value = json!({
+            "scopes": {
+                "credentials": { "acls": private(), "passwords": { "web": "hash" } }
+            }
+        });
+        let original = value.clone();
+        let redacted = redact(
+            &mut value,
+            SecurityContext::new("owner", "owner", "creator"),
+        );
+        assert!(redacted.is_empty());
+        assert_eq!(value, original);
+    }
 }
@@ -35,19 +35,24 @@ where
 {
     match value {
         JsonValue::Object(o) => {
-            let value = serde_json::from_value::<T>(value.clone());
-            match value {
-                Ok(value) => Some(vec![Scoured {
+            // Matches may be nested inside of other matches, for example
+            // properties with their own acls inside an entity with acls.
+            let found = serde_json::from_value::<T>(value.clone())
+                .ok()
+                .map(|value| Scoured {
                     path: DottedPath::default(),
                     value,
-                }]),
-                Err(_) => Some(
-                    o.iter()
-                        .flat_map(|(k, v)| scour(v).map(|o| o.into_iter().map(|p| p.prefix(k))))
-                        .flatten()
-                        .collect(),
-                ),
-            }
+                });
+            Some(
+                found
+                    .into_iter()
+                    .chain(
+                        o.iter()
+                            .flat_map(|(k, v)| scour(v).map(|o| o.into_iter().map(|p| p.prefix(k))))
+                            .flatten(),
+                    )
+                    .collect(),
+            )
         }
         JsonValue::Array(array) => Some(array.iter().flat_map(scour).flatten().collect()),
         JsonValue::String(_) => None,
@@ -0,0 +1,1449 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    fs,
+    io::{Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+};
+use tracing::*;
+
+use engine::{
+    storage::{ConflictError, EntityStorage, EntityVersionStorage, DEFAULT_VERSION_RETENTION},
+    storage::{DeadFuture, FutureStorage, PendingFutures, Storage, StorageFactory},
+    storage::{JournalEntry, JournalStorage},
+    storage::{MailboxLimits, MailboxStorage, PersistedMail},
+    storage::{PersistedEntity, PersistedFuture},
+};
+use kernel::prelude::{CoreProps, Entity, EntityKey, JsonValue, LookupBy};
+
+const ENTITY_EXTENSION: &str = "json";
+pub const FUTURES_FILE: &str = "futures.json";
+const STATE_DIRECTORY: &str = ".burrow";
+const INDEX_FILE: &str = "index.json";
+const DEAD_FUTURES_FILE: &str = "dead-futures.json";
+const JOURNAL_FILE: &str = "journal.jsonl";
+const MAILBOX_FILE: &str = "mailbox.json";
+const HISTORY_DIRECTORY: &str = "history";
+const STAGING_PREFIX: &str = "staging-";
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// A value in the serialized entities that's kept in a file of its own, next
+/// to the entity, so scripts can be edited like any other source file.
+#[derive(Clone, Debug)]
+struct ScriptFile {
+    file_name: String,
+    parent: String,
+    field: String,
+}
+
+impl ScriptFile {
+    fn extract(&self, value: &mut JsonValue) -> Option<String> {
+        let parent = value.pointer_mut(&self.parent)?.as_object_mut()?;
+        match parent.remove(&self.field)? {
+            JsonValue::String(script) => Some(script),
+            other => {
+                parent.insert(self.field.clone(), other);
+                None
+            }
+        }
+    }
+
+    fn merge(&self, value: &mut JsonValue, script: String) {
+        if let Some(parent) = value
+            .pointer_mut(&self.parent)
+            .and_then(|p| p.as_object_mut())
+        {
+            parent.insert(self.field.clone(), JsonValue::String(script));
+        }
+    }
+}
+
+/// Where each entity file is at and which version of it was last committed.
+/// Entity files don't include their version, so editing them by hand doesn't
+/// mean also keeping a version up to date.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct Index {
+    entities: BTreeMap<String, Indexed>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Indexed {
+    gid: u64,
+    version: u64,
+}
+
+impl Index {
+    fn version(&self, key: &str) -> Option<u64> {
+        self.entities.get(key).map(|i| i.version)
+    }
+
+    fn key_of(&self, gid: u64) -> Option<&str> {
+        self.entities
+            .iter()
+            .find(|(_, i)| i.gid == gid)
+            .map(|(key, _)| key.as_str())
+    }
+
+    fn check(&self, entity: &PersistedEntity) -> Result<()> {
+        let expected = (entity.version > 1).then_some(entity.version - 1);
+        if self.version(&entity.key) != expected {
+            return Err(ConflictError {
+                key: entity.key.clone(),
+                version: entity.version,
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct StoredFuture {
+    key: String,
+    entity: String,
+    time: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cron: Option<String>,
+    #[serde(default)]
+    attempts: u32,
+    action: JsonValue,
+}
+
+impl TryFrom<&PersistedFuture> for StoredFuture {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &PersistedFuture) -> Result<Self> {
+        Ok(Self {
+            key: value.key.clone(),
+            entity: value.entity.key_to_string().to_owned(),
+            time: value.time,
+            cron: value.cron.clone(),
+            attempts: value.attempts,
+            action: serde_json::from_str(&value.serialized)?,
+        })
+    }
+}
+
+impl From<StoredFuture> for PersistedFuture {
+    fn from(value: StoredFuture) -> Self {
+        Self {
+            key: value.key,
+            entity: EntityKey::new(&value.entity),
+            cron: value.cron,
+            time: value.time,
+            serialized: value.action.to_string(),
+            attempts: value.attempts,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct StoredDeadFuture {
+    future: StoredFuture,
+    error: String,
+    failed: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct Mailbox {
+    sequence: u64,
+    mail: Vec<StoredMail>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct StoredMail {
+    sequence: u64,
+    audience: String,
+    time: DateTime<Utc>,
+    serialized: String,
+}
+
+impl From<StoredMail> for PersistedMail {
+    fn from(value: StoredMail) -> Self {
+        Self {
+            sequence: value.sequence,
+            audience: EntityKey::new(&value.audience),
+            time: value.time,
+            serialized: value.serialized,
+        }
+    }
+}
+
+/// Everything a commit changes, written to its staging directory once the
+/// changes have been checked and staged. Writing the manifest is what commits
+/// them, should moving them into place be interrupted they're moved the next
+/// time the directory is migrated.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Manifest {
+    saves: Vec<StagedSave>,
+    deletes: Vec<String>,
+    versions: Vec<(String, u64)>,
+    journal: Option<StagedJournal>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StagedSave {
+    key: String,
+    scripts: Vec<String>,
+}
+
+/// Lines appended to the journal, which is first truncated to the length it
+/// had when the commit was staged so appending them can be repeated.
+#[derive(Debug, Serialize, Deserialize)]
+struct StagedJournal {
+    length: u64,
+    lines: Vec<String>,
+}
+
+/// The files making up a world kept in a directory, entities are in files
+/// named after their keys and futures are in a single file. Everything else,
+/// which isn't meant to be edited by hand, is kept in a hidden directory.
+#[derive(Clone, Debug)]
+struct Tree {
+    root: PathBuf,
+    scripts: Vec<ScriptFile>,
+}
+
+impl Tree {
+    fn entity_path(&self, key: &str) -> PathBuf {
+        self.root.join(format!("{}.{}", key, ENTITY_EXTENSION))
+    }
+
+    fn scripts_path(&self, key: &str) -> PathBuf {
+        self.root.join(key)
+    }
+
+    fn state_path(&self, name: &str) -> PathBuf {
+        self.root.join(STATE_DIRECTORY).join(name)
+    }
+
+    fn history_path(&self, key: &str) -> PathBuf {
+        self.state_path(HISTORY_DIRECTORY).join(key)
+    }
+
+    fn read_index(&self) -> Result<Index> {
+        Ok(read_json(&self.state_path(INDEX_FILE))?.unwrap_or_default())
+    }
+
+    fn write_index(&self, index: &Index) -> Result<()> {
+        write_json(&self.state_path(INDEX_FILE), index)
+    }
+
+    /// Adds entity files that were added by hand to the index and forgets
+    /// those that were removed.
+    fn reindex(&self) -> Result<Index> {
+        let mut index = self.read_index()?;
+        let mut found = Vec::new();
+
+        for entry in fs::read_dir(&self.root)? {
+            let path = entry?.path();
+            if !path.is_file()
+                || path.extension().and_then(|e| e.to_str()) != Some(ENTITY_EXTENSION)
+            {
+                continue;
+            }
+            if path.file_name().and_then(|n| n.to_str()) == Some(FUTURES_FILE) {
+                continue;
+            }
+            let Some(key) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+
+            if !index.entities.contains_key(key) {
+                let Some(value) = self.read_entity(key)? else {
+                    return Err(anyhow!("entity {} disappeared while indexing", key));
+                };
+                let entity = Entity::from_value(value)?;
+                let Some(gid) = entity.gid() else {
+                    return Err(anyhow!("entity {} has no gid", key));
+                };
+
+                info!(%key, "indexing");
+
+                index.entities.insert(
+                    key.to_owned(),
+                    Indexed {
+                        gid: gid.into(),
+                        version: 1,
+                    },
+                );
+            }
+
+            found.push(key.to_owned());
+        }
+
+        index.entities.retain(|key, _| found.contains(key));
+
+        Ok(index)
+    }
+
+    fn read_entity(&self, key: &str) -> Result<Option<JsonValue>> {
+        let Some(mut value) = read_json::<JsonValue>(&self.entity_path(key))? else {
+            return Ok(None);
+        };
+
+        for script in self.scripts.iter() {
+            let path = self.scripts_path(key).join(&script.file_name);
+            if path.is_file() {
+                script.merge(&mut value, fs::read_to_string(path)?);
+            }
+        }
+
+        Ok(Some(value))
+    }
+
+    fn load(&self, index: &Index, key: &str) -> Result<Option<PersistedEntity>> {
+        let Some(value) = self.read_entity(key)? else {
+            return Ok(None);
+        };
+
+        let (gid, version) = match index.entities.get(key) {
+            Some(indexed) => (indexed.gid, indexed.version),
+            None => {
+                let entity = Entity::from_value(value.clone())?;
+                let Some(gid) = entity.gid() else {
+                    return Err(anyhow!("entity {} has no gid", key));
+                };
+                (gid.into(), 1)
+            }
+        };
+
+        Ok(Some(PersistedEntity {
+            key: key.to_owned(),
+            gid,
+            version,
+            serialized: value.to_string(),
+        }))
+    }
+
+    /// The staging directory, which is laid out just like the tree.
+    fn staged(&self, staging: &Path) -> Tree {
+        Tree {
+            root: staging.to_owned(),
+            scripts: self.scripts.clone(),
+        }
+    }
+
+    /// Writes the entity and its scripts into the staging directory,
+    /// returning the names of the script files that were written.
+    fn stage(&self, staging: &Path, entity: &PersistedEntity) -> Result<Vec<String>> {
+        let mut value: JsonValue = serde_json::from_str(&entity.serialized)?;
+        let staged = self.staged(staging);
+        let mut scripts = Vec::new();
+
+        for script in self.scripts.iter() {
+            if let Some(source) = script.extract(&mut value) {
+                let dir = staged.scripts_path(&entity.key);
+                fs::create_dir_all(&dir)?;
+                fs::write(dir.join(&script.file_name), source)?;
+                scripts.push(script.file_name.clone());
+            }
+        }
+
+        fs::write(
+            staged.entity_path(&entity.key),
+            serde_json::to_string_pretty(&value)? + "\n",
+        )?;
+
+        Ok(scripts)
+    }
+
+    /// Moves a staged entity and its scripts into place, removing scripts
+    /// that the entity no longer has. Files already moved are skipped.
+    fn replace(&self, staging: &Path, key: &str, scripts: &[String]) -> Result<()> {
+        let staged = self.staged(staging);
+
+        for script in self.scripts.iter() {
+            let from = staged.scripts_path(key).join(&script.file_name);
+            let to = self.scripts_path(key).join(&script.file_name);
+            if scripts.contains(&script.file_name) {
+                if from.is_file() {
+                    fs::create_dir_all(self.scripts_path(key))?;
+                    fs::rename(from, to)?;
+                }
+            } else if to.is_file() {
+                fs::remove_file(to)?;
+            }
+        }
+
+        remove_empty_dir(&self.scripts_path(key))?;
+
+        let from = staged.entity_path(key);
+        if from.is_file() {
+            fs::rename(from, self.entity_path(key))?;
+        }
+
+        Ok(())
+    }
+
+    fn remove(&self, key: &str) -> Result<()> {
+        for script in self.scripts.iter() {
+            let path = self.scripts_path(key).join(&script.file_name);
+            if path.is_file() {
+                fs::remove_file(path)?;
+            }
+        }
+
+        remove_empty_dir(&self.scripts_path(key))?;
+
+        let path = self.entity_path(key);
+        if path.is_file() {
+            fs::remove_file(path)?;
+        }
+
+        Ok(())
+    }
+
+    fn stage_version(&self, staging: &Path, replaced: &PersistedEntity) -> Result<()> {
+        let dir = self.staged(staging).history_path(&replaced.key);
+        fs::create_dir_all(&dir)?;
+        fs::write(
+            dir.join(format!("{}.{}", replaced.version, ENTITY_EXTENSION)),
+            &replaced.serialized,
+        )?;
+
+        Ok(())
+    }
+
+    /// Moves a staged version into the history, forgetting the oldest versions
+    /// beyond those being retained.
+    fn keep_version(
+        &self,
+        staging: &Path,
+        key: &str,
+        version: u64,
+        retention: usize,
+    ) -> Result<()> {
+        let name = format!("{}.{}", version, ENTITY_EXTENSION);
+        let from = self.staged(staging).history_path(key).join(&name);
+        if from.is_file() {
+            let dir = self.history_path(key);
+            fs::create_dir_all(&dir)?;
+            fs::rename(from, dir.join(name))?;
+        }
+
+        let mut kept = self.kept_versions(key)?;
+        for (_, path) in kept.drain(..).skip(retention) {
+            fs::remove_file(path)?;
+        }
+
+        Ok(())
+    }
+
+    /// Versions kept in the history directory, newest first.
+    fn kept_versions(&self, key: &str) -> Result<Vec<(u64, PathBuf)>> {
+        let dir = self.history_path(key);
+        if !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let mut kept = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if let Some(version) = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .and_then(|s| s.parse::<u64>().ok())
+            {
+                kept.push((version, path));
+            }
+        }
+
+        kept.sort_by_key(|(version, _)| std::cmp::Reverse(*version));
+
+        Ok(kept)
+    }
+
+    fn futures_path(&self) -> PathBuf {
+        self.root.join(FUTURES_FILE)
+    }
+
+    fn read_futures(&self) -> Result<Vec<StoredFuture>> {
+        Ok(read_json(&self.futures_path())?.unwrap_or_default())
+    }
+
+    fn read_dead_futures(&self) -> Result<Vec<StoredDeadFuture>> {
+        Ok(read_json(&self.state_path(DEAD_FUTURES_FILE))?.unwrap_or_default())
+    }
+
+    fn read_mailbox(&self) -> Result<Mailbox> {
+        Ok(read_json(&self.state_path(MAILBOX_FILE))?.unwrap_or_default())
+    }
+
+    /// Moves committed changes from the staging directory into place,
+    /// returning false if the changes were never committed. Every step can
+    /// be repeated, so this can be done again should it be interrupted.
+    fn roll_forward(&self, staging: &Path, retention: usize) -> Result<bool> {
+        let staged = self.staged(staging);
+        let Some(manifest) = read_json::<Manifest>(&staged.state_path(MANIFEST_FILE))? else {
+            return Ok(false);
+        };
+
+        for (key, version) in manifest.versions.iter() {
+            self.keep_version(staging, key, *version, retention)?;
+        }
+
+        for save in manifest.saves.iter() {
+            self.replace(staging, &save.key, &save.scripts)?;
+        }
+
+        for key in manifest.deletes.iter() {
+            self.remove(key)?;
+        }
+
+        if let Some(journal) = &manifest.journal {
+            let mut file = fs::OpenOptions::new()
+                .create(true)
+                .truncate(false)
+                .write(true)
+                .open(self.state_path(JOURNAL_FILE))?;
+            if file.metadata()?.len() > journal.length {
+                file.set_len(journal.length)?;
+            }
+            file.seek(SeekFrom::End(0))?;
+            for line in journal.lines.iter() {
+                writeln!(file, "{}", line)?;
+            }
+        }
+
+        for name in [INDEX_FILE, MAILBOX_FILE] {
+            let from = staged.state_path(name);
+            if from.is_file() {
+                fs::rename(from, self.state_path(name))?;
+            }
+        }
+
+        let from = staged.state_path(FUTURES_FILE);
+        if from.is_file() {
+            fs::rename(from, self.futures_path())?;
+        }
+
+        Ok(true)
+    }
+
+    fn staging_directories(&self) -> Result<Vec<PathBuf>> {
+        let mut found = Vec::new();
+        for entry in fs::read_dir(self.root.join(STATE_DIRECTORY))? {
+            let entry = entry?;
+            if entry
+                .file_name()
+                .to_str()
+                .map(|n| n.starts_with(STAGING_PREFIX))
+                .unwrap_or_default()
+            {
+                found.push(entry.path());
+            }
+        }
+
+        Ok(found)
+    }
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<Option<T>> {
+    match fs::read_to_string(path) {
+        Ok(data) => Ok(Some(
+            serde_json::from_str(&data).with_context(|| format!("reading {:?}", path))?,
+        )),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn write_futures(path: &Path, mut futures: Vec<StoredFuture>) -> Result<()> {
+    futures.sort_by(|a, b| a.time.cmp(&b.time).then_with(|| a.key.cmp(&b.key)));
+
+    write_json(path, &futures)
+}
+
+/// Writes to a temporary file that's then renamed over the original, so the
+/// original is never left partially written.
+fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    let temporary = path.with_extension(format!("tmp-{}", nanoid::nanoid!()));
+    fs::write(&temporary, serde_json::to_string_pretty(value)? + "\n")?;
+    fs::rename(temporary, path)?;
+
+    Ok(())
+}
+
+fn remove_empty_dir(path: &Path) -> Result<()> {
+    if path.is_dir() && fs::read_dir(path)?.next().is_none() {
+        fs::remove_dir(path)?;
+    }
+
+    Ok(())
+}
+
+enum Pending {
+    Save(PersistedEntity, Vec<String>),
+    Delete(PersistedEntity),
+    Append(JournalEntry),
+    Post(StoredMail),
+    Queue(StoredFuture),
+    Cancel(String),
+    Reschedule(String, DateTime<Utc>),
+}
+
+/// Reads and writes entities in a directory. Changes are staged in a hidden
+/// directory and moved into place when the transaction is committed, after
+/// checking their versions just like other storage does. Dead letters are
+/// written as soon as they're changed.
+pub struct DirectoryStorage {
+    tree: Tree,
+    lock: Arc<Mutex<()>>,
+    sequence: Arc<AtomicU64>,
+    retention: usize,
+    limits: MailboxLimits,
+    index: RefCell<Option<Index>>,
+    staging: RefCell<Option<PathBuf>>,
+    pending: RefCell<Vec<Pending>>,
+}
+
+impl DirectoryStorage {
+    fn lock(&self) -> MutexGuard<'_, ()> {
+        self.lock.lock().expect("Lock error")
+    }
+
+    fn index(&self) -> Result<Index> {
+        let mut index = self.index.borrow_mut();
+        if index.is_none() {
+            *index = Some(self.tree.read_index()?);
+        }
+
+        Ok(index.clone().expect("Index missing"))
+    }
+
+    fn staging(&self) -> Result<PathBuf> {
+        let mut staging = self.staging.borrow_mut();
+        if let Some(path) = staging.as_ref() {
+            return Ok(path.clone());
+        }
+
+        let path = self
+            .tree
+            .state_path(&format!("{}{}", STAGING_PREFIX, nanoid::nanoid!()));
+        fs::create_dir_all(&path)?;
+        *staging = Some(path.clone());
+
+        Ok(path)
+    }
+
+    fn discard(&self) -> Result<()> {
+        self.pending.borrow_mut().clear();
+        self.index.borrow_mut().take();
+
+        if let Some(path) = self.staging.borrow_mut().take() {
+            if path.is_dir() {
+                fs::remove_dir_all(path)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks and stages everything pending, writing the manifest that
+    /// commits it last. Returns the staging directory when anything was.
+    fn prepare(&self, pending: &[Pending]) -> Result<Option<PathBuf>> {
+        let mut index = self.tree.read_index()?;
+
+        for pending in pending.iter() {
+            match pending {
+                Pending::Save(e, _) => index.check(e)?,
+                Pending::Delete(e) if index.version(&e.key).is_some_and(|v| v + 1 != e.version) => {
+                    return Err(ConflictError {
+                        key: e.key.clone(),
+                        version: e.version,
+                    }
+                    .into());
+                }
+                _ => {}
+            }
+        }
+
+        if pending.is_empty() {
+            return Ok(None);
+        }
+
+        let staging = self.staging()?;
+        let staged = self.tree.staged(&staging);
+        let mut manifest = Manifest::default();
+        let mut journal = Vec::new();
+        let mut mailbox = None;
+        let mut futures = None;
+
+        for pending in pending.iter() {
+            match pending {
+                Pending::Save(e, scripts) => {
+                    if self.retention > 0 {
+                        if let Some(replaced) = self.tree.load(&index, &e.key)? {
+                            self.tree.stage_version(&staging, &replaced)?;
+                            manifest
+                                .versions
+                                .push((replaced.key.clone(), replaced.version));
+                        }
+                    }
+                    manifest.saves.push(StagedSave {
+                        key: e.key.clone(),
+                        scripts: scripts.clone(),
+                    });
+                    index.entities.insert(
+                        e.key.clone(),
+                        Indexed {
+                            gid: e.gid,
+                            version: e.version,
+                        },
+                    );
+                }
+                Pending::Delete(e) => {
+                    if index.entities.remove(&e.key).is_some() {
+                        manifest.deletes.push(e.key.clone());
+                    }
+                }
+                Pending::Append(entry) => journal.push(serde_json::to_string(entry)?),
+                Pending::Post(mail) => {
+                    let mailbox = match &mut mailbox {
+                        Some(mailbox) => mailbox,
+                        None => mailbox.insert(self.tree.read_mailbox()?),
+                    };
+                    let expired = mail.time - self.limits.ttl;
+                    let kept = mailbox
+                        .mail
+                        .iter()
+                        .filter(|m| m.audience == mail.audience)
+                        .count();
+                    let mut excess = (kept + 1).saturating_sub(self.limits.capacity);
+                    mailbox.mail.retain(|m| {
+                        if m.audience != mail.audience {
+                            return true;
+                        }
+                        if excess > 0 {
+                            excess -= 1;
+                            return false;
+                        }
+                        m.time >= expired
+                    });
+                    if self.limits.capacity > 0 {
+                        mailbox.mail.push(mail.clone());
+                    }
+                    mailbox.sequence = mailbox.sequence.max(mail.sequence);
+                }
+                Pending::Queue(future) => {
+                    let futures = match &mut futures {
+                        Some(futures) => futures,
+                        None => futures.insert(self.tree.read_futures()?),
+                    };
+                    futures.retain(|f| f.key != future.key);
+                    futures.push(future.clone());
+                }
+                Pending::Cancel(key) => {
+                    let futures = match &mut futures {
+                        Some(futures) => futures,
+                        None => futures.insert(self.tree.read_futures()?),
+                    };
+                    futures.retain(|f| &f.key != key);
+                }
+                Pending::Reschedule(key, time) => {
+                    let futures = match &mut futures {
+                        Some(futures) => futures,
+                        None => futures.insert(self.tree.read_futures()?),
+                    };
+                    for future in futures.iter_mut().filter(|f| &f.key == key) {
+                        future.time = *time;
+                    }
+                }
+            }
+        }
+
+        fs::create_dir_all(staged.root.join(STATE_DIRECTORY))?;
+
+        write_json(&staged.state_path(INDEX_FILE), &index)?;
+
+        if let Some(mailbox) = mailbox {
+            write_json(&staged.state_path(MAILBOX_FILE), &mailbox)?;
+        }
+
+        if let Some(futures) = futures {
+            write_futures(&staged.state_path(FUTURES_FILE), futures)?;
+        }
+
+        if !journal.is_empty() {
+            let length = match fs::metadata(self.tree.state_path(JOURNAL_FILE)) {
+                Ok(metadata) => metadata.len(),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => 0,
+                Err(e) => return Err(e.into()),
+            };
+            manifest.journal = Some(StagedJournal {
+                length,
+                lines: journal,
+            });
+        }
+
+        write_json(&staged.state_path(MANIFEST_FILE), &manifest)?;
+
+        Ok(Some(staging))
+    }
+}
+
+impl Drop for DirectoryStorage {
+    fn drop(&mut self) {
+        if let Err(e) = self.discard() {
+            warn!("error discarding staged changes: {:?}", e);
+        }
+    }
+}
+
+impl Storage for DirectoryStorage {}
+
+impl EntityStorage for DirectoryStorage {
+    fn load(&self, lookup: &LookupBy) -> Result<Option<PersistedEntity>> {
+        let index = self.index()?;
+        let key = match lookup {
+            LookupBy::Key(key) => key.key_to_string().to_owned(),
+            LookupBy::Gid(gid) => match index.key_of((*gid).into()) {
+                Some(key) => key.to_owned(),
+                None => return Ok(None),
+            },
+        };
+
+        self.tree.load(&index, &key)
+    }
+
+    fn stored_versions(&self) -> Result<Vec<(EntityKey, u64)>> {
+        Ok(self
+            .index()?
+            .entities
+            .iter()
+            .map(|(key, i)| (EntityKey::new(key), i.version))
+            .collect())
+    }
+
+    fn save(&self, entity: &PersistedEntity) -> Result<()> {
+        self.index()?.check(entity)?;
+
+        let scripts = self.tree.stage(&self.staging()?, entity)?;
+
+        let mut pending = self.pending.borrow_mut();
+        pending.retain(|p| !matches!(p, Pending::Save(e, _) if e.key == entity.key));
+        pending.push(Pending::Save(entity.clone(), scripts));
+
+        Ok(())
+    }
+
+    fn delete(&self, entity: &PersistedEntity) -> Result<()> {
+        self.pending
+            .borrow_mut()
+            .push(Pending::Delete(entity.clone()));
+
+        Ok(())
+    }
+
+    fn begin(&self) -> Result<()> {
+        self.discard()
+    }
+
+    fn rollback(&self, _benign: bool) -> Result<()> {
+        self.discard()
+    }
+
+    fn commit(&self) -> Result<()> {
+        let pending = self.pending.take();
+
+        let applied = {
+            let _lock = self.lock();
+            match self.prepare(&pending) {
+                Ok(Some(staging)) => {
+                    let moved = self.tree.roll_forward(&staging, self.retention);
+                    if let Err(e) = &moved {
+                        // These changes are committed, so they're left staged
+                        // for the next migration to finish moving into place.
+                        warn!(path = ?staging, "error moving committed changes: {:?}", e);
+                        self.staging.borrow_mut().take();
+                    }
+                    moved.map(|_| ())
+                }
+                Ok(None) => Ok(()),
+                Err(e) => Err(e),
+            }
+        };
+
+        self.discard()?;
+
+        applied
+    }
+
+    fn query_all(&self) -> Result<Vec<PersistedEntity>> {
+        let index = self.index()?;
+        let mut entities = Vec::new();
+        for key in index.entities.keys() {
+            if let Some(entity) = self.tree.load(&index, key)? {
+                entities.push(entity);
+            }
+        }
+
+        Ok(entities)
+    }
+}
+
+impl EntityVersionStorage for DirectoryStorage {
+    fn query_versions(&self, key: &EntityKey) -> Result<Vec<PersistedEntity>> {
+        let index = self.index()?;
+        let Some(gid) = index.entities.get(key.key_to_string()).map(|i| i.gid) else {
+            return Ok(Vec::new());
+        };
+
+        let mut versions = Vec::new();
+        for (version, path) in self.tree.kept_versions(key.key_to_string())? {
+            versions.push(PersistedEntity {
+                key: key.key_to_string().to_owned(),
+                gid,
+                version,
+                serialized: fs::read_to_string(path)?,
+            });
+        }
+
+        Ok(versions)
+    }
+}
+
+impl FutureStorage for DirectoryStorage {
+    fn queue(&self, future: PersistedFuture) -> Result<()> {
+        self.pending
+            .borrow_mut()
+            .push(Pending::Queue((&future).try_into()?));
+
+        Ok(())
+    }
+
+    fn cancel(&self, key: &str) -> Result<()> {
+        self.pending
+            .borrow_mut()
+            .push(Pending::Cancel(key.to_owned()));
+
+        Ok(())
+    }
+
+    fn query_futures_before(&self, now: DateTime<Utc>) -> Result<PendingFutures> {
+        let (pending, waiting): (Vec<_>, Vec<_>) = self
+            .tree
+            .read_futures()?
+            .into_iter()
+            .partition(|f| now >= f.time);
+
+        if pending.is_empty() {
+            return Ok(PendingFutures::Waiting(
+                waiting.iter().map(|f| f.time).min(),
+            ));
+        }
+
+        Ok(PendingFutures::Futures(
+            pending.into_iter().map(|f| f.into()).collect(),
+        ))
+    }
+
+    fn list_futures(&self, entity: &EntityKey) -> Result<Vec<PersistedFuture>> {
+        let mut listed = self
+            .tree
+            .read_futures()?
+            .into_iter()
+            .filter(|f| f.entity == entity.key_to_string())
+            .map(PersistedFuture::from)
+            .collect::<Vec<_>>();
+
+        listed.sort_by(|a, b| a.time.cmp(&b.time).then_with(|| a.key.cmp(&b.key)));
+
+        Ok(listed)
+    }
+
+    fn get_future(&self, key: &str) -> Result<Option<PersistedFuture>> {
+        Ok(self
+            .tree
+            .read_futures()?
+            .into_iter()
+            .find(|f| f.key == key)
+            .map(|f| f.into()))
+    }
+
+    fn reschedule(&self, key: &str, time: DateTime<Utc>) -> Result<()> {
+        self.pending
+            .borrow_mut()
+            .push(Pending::Reschedule(key.to_owned(), time));
+
+        Ok(())
+    }
+
+    fn dead_letter(
+        &self,
+        future: &PersistedFuture,
+        error: &str,
+        failed: DateTime<Utc>,
+    ) -> Result<()> {
+        let _lock = self.lock();
+        let mut dead = self.tree.read_dead_futures()?;
+        dead.retain(|d| d.future.key != future.key);
+        dead.push(StoredDeadFuture {
+            future: future.try_into()?,
+            error: error.to_owned(),
+            failed,
+        });
+
+        write_json(&self.tree.state_path(DEAD_FUTURES_FILE), &dead)
+    }
+
+    fn query_dead_letters(&self) -> Result<Vec<DeadFuture>> {
+        Ok(self
+            .tree
+            .read_dead_futures()?
+            .into_iter()
+            .map(|d| DeadFuture {
+                future: d.future.into(),
+                error: d.error,
+                failed: d.failed,
+            })
+            .collect())
+    }
+}
+
+impl JournalStorage for DirectoryStorage {
+    fn append(&self, entry: &JournalEntry) -> Result<()> {
+        self.pending
+            .borrow_mut()
+            .push(Pending::Append(entry.clone()));
+
+        Ok(())
+    }
+
+    fn query_journal(&self) -> Result<Vec<JournalEntry>> {
+        let path = self.tree.state_path(JOURNAL_FILE);
+        if !path.is_file() {
+            return Ok(Vec::new());
+        }
+
+        fs::read_to_string(path)?
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| Ok(serde_json::from_str(l)?))
+            .collect()
+    }
+}
+
+impl MailboxStorage for DirectoryStorage {
+    fn post(&self, audience: &EntityKey, time: DateTime<Utc>, serialized: &str) -> Result<u64> {
+        let sequence = self.sequence.fetch_add(1, Ordering::Relaxed) + 1;
+        self.pending.borrow_mut().push(Pending::Post(StoredMail {
+            sequence,
+            audience: audience.key_to_string().to_owned(),
+            time,
+            serialized: serialized.to_owned(),
+        }));
+
+        Ok(sequence)
+    }
+
+    fn query_mailbox(
+        &self,
+        audience: &EntityKey,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<PersistedMail>> {
+        Ok(self
+            .tree
+            .read_mailbox()?
+            .mail
+            .into_iter()
+            .filter(|m| m.audience == audience.key_to_string() && m.time >= now - self.limits.ttl)
+            .map(|m| m.into())
+            .collect())
+    }
+
+    fn acknowledge(&self, audience: &EntityKey, sequence: u64) -> Result<()> {
+        let _lock = self.lock();
+        let mut mailbox = self.tree.read_mailbox()?;
+        mailbox
+            .mail
+            .retain(|m| m.audience != audience.key_to_string() || m.sequence > sequence);
+
+        write_json(&self.tree.state_path(MAILBOX_FILE), &mailbox)
+    }
+}
+
+pub struct Factory {
+    tree: Tree,
+    retention: usize,
+    limits: MailboxLimits,
+    lock: Arc<Mutex<()>>,
+    sequence: Arc<AtomicU64>,
+}
+
+impl Factory {
+    pub fn new(path: &str) -> Result<Self> {
+        let tree = Tree {
+            root: PathBuf::from(path),
+            scripts: Vec::new(),
+        };
+
+        let sequence = tree.read_mailbox()?.sequence;
+
+        Ok(Factory {
+            tree,
+            retention: DEFAULT_VERSION_RETENTION,
+            limits: Default::default(),
+            lock: Default::default(),
+            sequence: Arc::new(AtomicU64::new(sequence)),
+        })
+    }
+
+    pub fn with_version_retention(self, retention: usize) -> Self {
+        Self { retention, ..self }
+    }
+
+    pub fn with_mailbox_limits(self, limits: MailboxLimits) -> Self {
+        Self { limits, ..self }
+    }
+
+    /// Keeps the string found at the JSON pointer in each entity in a file
+    /// with the given name, in a directory named after the entity.
+    pub fn with_script_file(self, file_name: &str, pointer: &str) -> Result<Self> {
+        let Some((parent, field)) = pointer.rsplit_once('/') else {
+            return Err(anyhow!("invalid script pointer {}", pointer));
+        };
+
+        let mut tree = self.tree;
+        tree.scripts.push(ScriptFile {
+            file_name: file_name.to_owned(),
+            parent: parent.to_owned(),
+            field: field.to_owned(),
+        });
+
+        Ok(Self { tree, ..self })
+    }
+}
+
+impl Factory {
+    fn storage(&self) -> DirectoryStorage {
+        DirectoryStorage {
+            tree: self.tree.clone(),
+            lock: Arc::clone(&self.lock),
+            sequence: Arc::clone(&self.sequence),
+            retention: self.retention,
+            limits: self.limits.clone(),
+            index: Default::default(),
+            staging: Default::default(),
+            pending: Default::default(),
+        }
+    }
+}
+
+impl StorageFactory for Factory {
+    fn migrate(&self) -> Result<()> {
+        let _lock = self.lock.lock().expect("Lock error");
+
+        fs::create_dir_all(self.tree.root.join(STATE_DIRECTORY))?;
+
+        for staging in self.tree.staging_directories()? {
+            if self.tree.roll_forward(&staging, self.retention)? {
+                info!(path = ?staging, "finished committed changes");
+            } else {
+                warn!(path = ?staging, "removing abandoned changes");
+            }
+            fs::remove_dir_all(staging)?;
+        }
+
+        let index = self.tree.reindex()?;
+
+        self.tree.write_index(&index)
+    }
+
+    fn create_storage(&self) -> Result<Rc<dyn Storage>> {
+        Ok(Rc::new(self.storage()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use chrono::Days;
+    use kernel::prelude::{build_entity, EntityGid, Identity, MutCoreProps};
+
+    const SCRIPT_POINTER: &str = "/scopes/behaviors/langs/rn/entry";
+
+    struct TemporaryDirectory(PathBuf);
+
+    impl TemporaryDirectory {
+        fn new() -> Result<Self> {
+            let path = std::env::temp_dir().join(format!("burrow-{}", nanoid::nanoid!()));
+            fs::create_dir_all(&path)?;
+            Ok(Self(path))
+        }
+
+        fn factory(&self) -> Result<Factory> {
+            let factory = Factory::new(self.0.to_str().unwrap())?
+                .with_script_file("entry.rn", SCRIPT_POINTER)?;
+
+            factory.migrate()?;
+
+            Ok(factory)
+        }
+    }
+
+    impl Drop for TemporaryDirectory {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn entity(key: &str, gid: u64, version: u64) -> PersistedEntity {
+        PersistedEntity {
+            key: key.to_string(),
+            gid,
+            version,
+            serialized: serde_json::json!({ "key": key, "version": version }).to_string(),
+        }
+    }
+
+    fn commit(s: &Rc<dyn Storage>, e: &PersistedEntity) -> Result<()> {
+        s.begin()?;
+        s.save(e)?;
+        s.commit()
+    }
+
+    #[test]
+    fn it_queries_for_entity_by_missing_key() -> Result<()> {
+        let dir = TemporaryDirectory::new()?;
+        let s = dir.factory()?.create_storage()?;
+
+        assert!(s.load(&LookupBy::Key(&EntityKey::new("world")))?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_inserts_and_queries_for_entity_by_key_and_gid() -> Result<()> {
+        let dir = TemporaryDirectory::new()?;
+        let factory = dir.factory()?;
+
+        commit(&factory.create_storage()?, &entity("world", 1, 1))?;
+
+        assert!(dir.0.join("world.json").is_file());
+
+        let s = factory.create_storage()?;
+        let loaded = s.load(&LookupBy::Key(&EntityKey::new("world")))?.unwrap();
+        assert_eq!(loaded.version, 1);
+        assert_eq!(loaded.gid, 1);
+
+        let gid = EntityGid::new(1);
+        let loaded = s.load(&LookupBy::Gid(&gid))?.unwrap();
+        assert_eq!(loaded.key, "world");
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_updates_an_existing_entity() -> Result<()> {
+        let dir = TemporaryDirectory::new()?;
+        let factory = dir.factory()?;
+
+        commit(&factory.create_storage()?, &entity("world", 1, 1))?;
+        commit(&factory.create_storage()?, &entity("world", 1, 2))?;
+
+        let s = factory.create_storage()?;
+        let loaded = s.load(&LookupBy::Key(&EntityKey::new("world")))?.unwrap();
+        assert_eq!(loaded.version, 2);
+        assert_eq!(loaded.to_json_value()?["version"], 2);
+        assert_eq!(s.query_versions(&EntityKey::new("world"))?.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_fails_to_save_stale_versions() -> Result<()> {
+        let dir = TemporaryDirectory::new()?;
+        let factory = dir.factory()?;
+
+        commit(&factory.create_storage()?, &entity("world", 1, 1))?;
+
+        let first = factory.create_storage()?;
+        let second = factory.create_storage()?;
+        first.begin()?;
+        second.begin()?;
+        first.save(&entity("world", 1, 2))?;
+        second.save(&entity("world", 1, 2))?;
+        first.commit()?;
+
+        let e = second.commit().unwrap_err();
+        assert!(ConflictError::is_conflict(&e));
+
+        let e = commit(&factory.create_storage()?, &entity("world", 1, 2)).unwrap_err();
+        assert!(ConflictError::is_conflict(&e));
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_discards_staged_changes_on_rollback() -> Result<()> {
+        let dir = TemporaryDirectory::new()?;
+        let factory = dir.factory()?;
+
+        let s = factory.create_storage()?;
+        s.begin()?;
+        s.save(&entity("world", 1, 1))?;
+        s.rollback(false)?;
+
+        assert!(!dir.0.join("world.json").exists());
+        assert!(factory.tree.staging_directories()?.is_empty());
+        assert!(s.load(&LookupBy::Key(&EntityKey::new("world")))?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_keeps_scripts_in_their_own_files() -> Result<()> {
+        let dir = TemporaryDirectory::new()?;
+        let factory = dir.factory()?;
+
+        let mut e = entity("world", 1, 1);
+        e.serialized = serde_json::json!({
+            "scopes": { "behaviors": { "langs": { "rn": { "entry": "pub fn main() {}" } } } }
+        })
+        .to_string();
+        commit(&factory.create_storage()?, &e)?;
+
+        let script = dir.0.join("world").join("entry.rn");
+        assert_eq!(fs::read_to_string(&script)?, "pub fn main() {}");
+        let on_disk: JsonValue =
+            serde_json::from_str(&fs::read_to_string(dir.0.join("world.json"))?)?;
+        assert!(on_disk.pointer(SCRIPT_POINTER).is_none());
+
+        fs::write(&script, "pub fn main() { 1 }")?;
+
+        let s = factory.create_storage()?;
+        let loaded = s.load(&LookupBy::Key(&EntityKey::new("world")))?.unwrap();
+        assert_eq!(
+            loaded.to_json_value()?.pointer(SCRIPT_POINTER),
+            Some(&JsonValue::String("pub fn main() { 1 }".to_owned()))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_deletes_entities() -> Result<()> {
+        let dir = TemporaryDirectory::new()?;
+        let factory = dir.factory()?;
+
+        commit(&factory.create_storage()?, &entity("world", 1, 1))?;
+
+        let s = factory.create_storage()?;
+        s.begin()?;
+        s.delete(&entity("world", 1, 2))?;
+        s.commit()?;
+
+        assert!(!dir.0.join("world.json").exists());
+        assert!(s.load(&LookupBy::Key(&EntityKey::new("world")))?.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_fails_to_delete_stale_versions() -> Result<()> {
+        let dir = TemporaryDirectory::new()?;
+        let factory = dir.factory()?;
+
+        commit(&factory.create_storage()?, &entity("world", 1, 1))?;
+        commit(&factory.create_storage()?, &entity("world", 1, 2))?;
+
+        let s = factory.create_storage()?;
+        s.begin()?;
+        s.delete(&entity("world", 1, 2))?;
+        let e = s.commit().unwrap_err();
+        assert!(ConflictError::is_conflict(&e));
+
+        assert!(dir.0.join("world.json").is_file());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_finishes_committed_changes_when_migrating() -> Result<()> {
+        let dir = TemporaryDirectory::new()?;
+        let factory = dir.factory()?;
+
+        let s = factory.storage();
+        s.begin()?;
+        s.save(&entity("world", 1, 1))?;
+        let pending = s.pending.take();
+        assert!(s.prepare(&pending)?.is_some());
+        // Leave the changes staged, as if moving them had been interrupted.
+        s.staging.borrow_mut().take();
+
+        assert!(!dir.0.join("world.json").exists());
+
+        let factory = dir.factory()?;
+        assert!(dir.0.join("world.json").is_file());
+        assert!(factory.tree.staging_directories()?.is_empty());
+        let loaded = factory
+            .create_storage()?
+            .load(&LookupBy::Key(&EntityKey::new("world")))?;
+        assert_eq!(loaded.map(|e| e.version), Some(1));
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_keeps_futures_across_factories() -> Result<()> {
+        let dir = TemporaryDirectory::new()?;
+        let now = Utc::now();
+
+        let s = dir.factory()?.create_storage()?;
+        s.begin()?;
+        s.queue(PersistedFuture {
+            key: "future".to_owned(),
+            entity: EntityKey::new("world"),
+            cron: None,
+            time: now,
+            serialized: "{}".to_owned(),
+            attempts: 0,
+        })?;
+        assert!(s.list_futures(&EntityKey::new("world"))?.is_empty());
+        s.commit()?;
+
+        let s = dir.factory()?.create_storage()?;
+        assert_eq!(s.list_futures(&EntityKey::new("world"))?.len(), 1);
+        assert_eq!(
+            s.query_futures_before(now.checked_sub_days(Days::new(1)).unwrap())?,
+            PendingFutures::Waiting(Some(now))
+        );
+        assert_eq!(s.query_futures_before(now)?.number_futures(), Some(1));
+        assert!(s.get_future("future")?.is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_indexes_entity_files_added_by_hand() -> Result<()> {
+        let dir = TemporaryDirectory::new()?;
+        let mut entity: Entity = build_entity()
+            .with_key(EntityKey::new("world"))
+            .identity(Identity::new("".to_owned(), "".to_owned()))
+            .try_into()?;
+        entity.set_gid(EntityGid::new(7))?;
+        fs::write(
+            dir.0.join("world.json"),
+            entity.to_json_value()?.to_string(),
+        )?;
+
+        let s = dir.factory()?.create_storage()?;
+        let gid = EntityGid::new(7);
+        let loaded = s.load(&LookupBy::Gid(&gid))?;
+        assert_eq!(loaded.map(|e| e.version), Some(1));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,420 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    rc::Rc,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+use tracing::trace;
+
+use crate::storage::{
+    DeadFuture, EntityStorage, EntityVersionStorage, FutureStorage, JournalEntry, JournalStorage,
+    MailboxStorage, PendingFutures, PersistedEntity, PersistedFuture, PersistedMail, Storage,
+    StorageFactory,
+};
+use kernel::prelude::{EntityKey, LookupBy};
+
+struct Cached {
+    entity: PersistedEntity,
+    used: u64,
+}
+
+pub const DEFAULT_CACHE_CAPACITY: usize = 4096;
+
+#[derive(Default)]
+struct Entries {
+    entities: HashMap<EntityKey, Cached>,
+    gids: HashMap<u64, EntityKey>,
+    recently_used: BTreeMap<u64, EntityKey>,
+    clock: u64,
+}
+
+impl Entries {
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    fn get(&mut self, key: &EntityKey) -> Option<PersistedEntity> {
+        let used = self.tick();
+        let cached = self.entities.get_mut(key)?;
+        self.recently_used.remove(&cached.used);
+        self.recently_used.insert(used, key.clone());
+        cached.used = used;
+
+        Some(cached.entity.clone())
+    }
+
+    fn insert(&mut self, entity: &PersistedEntity, capacity: usize) {
+        let key = EntityKey::new(&entity.key);
+        let used = self.tick();
+        self.remove(&key);
+        self.gids.insert(entity.gid, key.clone());
+        self.recently_used.insert(used, key.clone());
+        self.entities.insert(
+            key,
+            Cached {
+                entity: entity.clone(),
+                used,
+            },
+        );
+
+        while self.entities.len() > capacity {
+            let Some((_, key)) = self.recently_used.pop_first() else {
+                break;
+            };
+
+            trace!(%key, "evicting");
+            if let Some(evicted) = self.entities.remove(&key) {
+                self.gids.remove(&evicted.entity.gid);
+            }
+        }
+    }
+
+    fn remove(&mut self, key: &EntityKey) {
+        if let Some(removed) = self.entities.remove(key) {
+            self.recently_used.remove(&removed.used);
+            self.gids.remove(&removed.entity.gid);
+        }
+    }
+}
+
+/// Persisted entities shared by every session opened from the same domain,
+/// so entities that haven't changed aren't read from storage again. Entries
+/// are replaced when sessions commit, and once there are more entries than
+/// the capacity those used least recently are evicted. Storage shared with
+/// other processes can change underneath the cache, so a cache can also be
+/// revalidated against the versions in storage as each session begins.
+pub struct EntityCache {
+    capacity: usize,
+    revalidating: bool,
+    entries: Mutex<Entries>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl Default for EntityCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CACHE_CAPACITY)
+    }
+}
+
+impl EntityCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            revalidating: false,
+            entries: Default::default(),
+            hits: Default::default(),
+            misses: Default::default(),
+        }
+    }
+
+    pub fn revalidating(self) -> Self {
+        Self {
+            revalidating: true,
+            ..self
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().expect("Lock error").entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        *self.entries.lock().expect("Lock error") = Default::default();
+    }
+
+    fn lookup(&self, lookup: &LookupBy) -> Option<PersistedEntity> {
+        let mut entries = self.entries.lock().expect("Lock error");
+        let key = match lookup {
+            LookupBy::Key(key) => Some((*key).clone()),
+            LookupBy::Gid(gid) => entries.gids.get(&u64::from(*gid)).cloned(),
+        };
+
+        let found = key.and_then(|key| entries.get(&key));
+
+        match found {
+            Some(_) => self.hits.fetch_add(1, Ordering::Relaxed),
+            None => self.misses.fetch_add(1, Ordering::Relaxed),
+        };
+
+        found
+    }
+
+    /// Caches an entity read from storage, unless a newer version has been
+    /// committed since it was read.
+    fn loaded(&self, entity: &PersistedEntity) {
+        let key = EntityKey::new(&entity.key);
+        let mut entries = self.entries.lock().expect("Lock error");
+        if entries
+            .entities
+            .get(&key)
+            .map(|cached| cached.entity.version < entity.version)
+            .unwrap_or(true)
+        {
+            entries.insert(entity, self.capacity);
+        }
+    }
+
+    /// Evicts every entity whose version differs from the one in storage.
+    fn revalidate(&self, stored: Vec<(EntityKey, u64)>) {
+        let stored: HashMap<EntityKey, u64> = stored.into_iter().collect();
+        let mut entries = self.entries.lock().expect("Lock error");
+        let stale = entries
+            .entities
+            .iter()
+            .filter(|(key, cached)| stored.get(key) != Some(&cached.entity.version))
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+
+        for key in stale {
+            trace!(%key, "stale");
+            entries.remove(&key);
+        }
+    }
+
+    fn committed(&self, pending: &[Pending]) {
+        let mut entries = self.entries.lock().expect("Lock error");
+        for pending in pending {
+            match pending {
+                Pending::Save(e) => entries.insert(e, self.capacity),
+                Pending::Delete(e) => entries.remove(&EntityKey::new(&e.key)),
+            }
+        }
+    }
+
+    fn evict(&self, key: &str) {
+        trace!(%key, "evicting");
+        self.entries
+            .lock()
+            .expect("Lock error")
+            .remove(&EntityKey::new(key));
+    }
+}
+
+/// Opens storage that reads entities through the cache before going to the
+/// storage the factory wraps.
+pub struct CachingStorageFactory {
+    factory: Arc<dyn StorageFactory>,
+    cache: Arc<EntityCache>,
+}
+
+impl CachingStorageFactory {
+    pub fn new(factory: Arc<dyn StorageFactory>, cache: Arc<EntityCache>) -> Self {
+        Self { factory, cache }
+    }
+}
+
+impl StorageFactory for CachingStorageFactory {
+    fn migrate(&self) -> Result<()> {
+        self.factory.migrate()
+    }
+
+    fn create_storage(&self) -> Result<Rc<dyn Storage>> {
+        Ok(Rc::new(CachedStorage {
+            storage: self.factory.create_storage()?,
+            cache: Arc::clone(&self.cache),
+            pending: Default::default(),
+        }))
+    }
+}
+
+enum Pending {
+    Save(PersistedEntity),
+    Delete(PersistedEntity),
+}
+
+impl Pending {
+    fn key(&self) -> &str {
+        match self {
+            Pending::Save(e) => &e.key,
+            Pending::Delete(e) => &e.key,
+        }
+    }
+}
+
+struct CachedStorage {
+    storage: Rc<dyn Storage>,
+    cache: Arc<EntityCache>,
+    pending: RefCell<Vec<Pending>>,
+}
+
+impl Storage for CachedStorage {}
+
+impl EntityStorage for CachedStorage {
+    fn load(&self, lookup: &LookupBy) -> Result<Option<PersistedEntity>> {
+        if let Some(cached) = self.cache.lookup(lookup) {
+            return Ok(Some(cached));
+        }
+
+        let loaded = self.storage.load(lookup)?;
+        if let Some(loaded) = &loaded {
+            self.cache.loaded(loaded);
+        }
+
+        Ok(loaded)
+    }
+
+    fn save(&self, entity: &PersistedEntity) -> Result<()> {
+        if let Err(e) = self.storage.save(entity) {
+            self.cache.evict(&entity.key);
+
+            return Err(e);
+        }
+
+        self.pending
+            .borrow_mut()
+            .push(Pending::Save(entity.clone()));
+
+        Ok(())
+    }
+
+    fn delete(&self, entity: &PersistedEntity) -> Result<()> {
+        if let Err(e) = self.storage.delete(entity) {
+            self.cache.evict(&entity.key);
+
+            return Err(e);
+        }
+
+        self.pending
+            .borrow_mut()
+            .push(Pending::Delete(entity.clone()));
+
+        Ok(())
+    }
+
+    fn begin(&self) -> Result<()> {
+        self.pending.borrow_mut().clear();
+
+        self.storage.begin()?;
+
+        if self.cache.revalidating {
+            self.cache.revalidate(self.storage.stored_versions()?);
+        }
+
+        Ok(())
+    }
+
+    fn rollback(&self, benign: bool) -> Result<()> {
+        self.pending.borrow_mut().clear();
+
+        self.storage.rollback(benign)
+    }
+
+    fn commit(&self) -> Result<()> {
+        let pending = self.pending.take();
+
+        match self.storage.commit() {
+            Ok(()) => {
+                self.cache.committed(&pending);
+
+                Ok(())
+            }
+            Err(e) => {
+                for pending in pending.iter() {
+                    self.cache.evict(pending.key());
+                }
+
+                Err(e)
+            }
+        }
+    }
+
+    fn query_all(&self) -> Result<Vec<PersistedEntity>> {
+        self.storage.query_all()
+    }
+}
+
+impl EntityVersionStorage for CachedStorage {
+    fn query_versions(&self, key: &EntityKey) -> Result<Vec<PersistedEntity>> {
+        self.storage.query_versions(key)
+    }
+}
+
+impl FutureStorage for CachedStorage {
+    fn queue(&self, future: PersistedFuture) -> Result<()> {
+        self.storage.queue(future)
+    }
+
+    fn cancel(&self, key: &str) -> Result<()> {
+        self.storage.cancel(key)
+    }
+
+    fn query_futures_before(&self, now: DateTime<Utc>) -> Result<PendingFutures> {
+        self.storage.query_futures_before(now)
+    }
+
+    fn list_futures(&self, entity: &EntityKey) -> Result<Vec<PersistedFuture>> {
+        self.storage.list_futures(entity)
+    }
+
+    fn get_future(&self, key: &str) -> Result<Option<PersistedFuture>> {
+        self.storage.get_future(key)
+    }
+
+    fn reschedule(&self, key: &str, time: DateTime<Utc>) -> Result<()> {
+        self.storage.reschedule(key, time)
+    }
+
+    fn dead_letter(
+        &self,
+        future: &PersistedFuture,
+        error: &str,
+        failed: DateTime<Utc>,
+    ) -> Result<()> {
+        self.storage.dead_letter(future, error, failed)
+    }
+
+    fn query_dead_letters(&self) -> Result<Vec<DeadFuture>> {
+        self.storage.query_dead_letters()
+    }
+}
+
+impl JournalStorage for CachedStorage {
+    fn append(&self, entry: &JournalEntry) -> Result<()> {
+        self.storage.append(entry)
+    }
+
+    fn query_journal(&self) -> Result<Vec<JournalEntry>> {
+        self.storage.query_journal()
+    }
+}
+
+impl MailboxStorage for CachedStorage {
+    fn post(&self, audience: &EntityKey, time: DateTime<Utc>, serialized: &str) -> Result<u64> {
+        self.storage.post(audience, time, serialized)
+    }
+
+    fn query_mailbox(
+        &self,
+        audience: &EntityKey,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<PersistedMail>> {
+        self.storage.query_mailbox(audience, now)
+    }
+
+    fn acknowledge(&self, audience: &EntityKey, sequence: u64) -> Result<()> {
+        self.storage.acknowledge(audience, sequence)
+    }
+}
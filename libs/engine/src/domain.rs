@@ -1,16 +1,28 @@
-use anyhow::Result;
-use chrono::{DateTime, Utc};
-use std::{rc::Rc, sync::Arc};
-use tracing::{info, trace};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
+use std::{
+    rc::Rc,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+use tracing::{info, trace, warn};
 
 use crate::{
+    cache::{CachingStorageFactory, EntityCache, DEFAULT_CACHE_CAPACITY},
     notifications::Notifier,
-    prelude::{Dependencies, USER_DEPTH},
+    prelude::{Dependencies, EvaluateAs, USER_DEPTH},
     sequences::Sequence,
     session::Session,
-    storage::{PendingFutures, PersistedEntity, StorageFactory},
+    storage::{
+        ConflictError, DeadFuture, JournalEntry, PendingFutures, PersistedEntity, PersistedFuture,
+        PersistedMail, Storage, StorageFactory,
+    },
 };
-use kernel::prelude::*;
+use kernel::{here, prelude::*};
+
+pub const DEFAULT_CONFLICT_RETRIES: usize = 3;
 
 pub trait SessionOpener: Send + Sync + Clone {
     fn open_session(&self) -> Result<Rc<Session>>;
@@ -22,6 +34,29 @@ pub enum AfterTick {
     Empty,
 }
 
+/// How futures that fail to be delivered are tried again, waiting twice as
+/// long after each failure until giving up on them.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    pub attempts: u32,
+    pub delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: 5,
+            delay: Duration::seconds(10),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn delay_after(&self, attempts: u32) -> Duration {
+        self.delay * 2i32.pow(attempts.saturating_sub(1).min(16))
+    }
+}
+
 #[derive(Clone)]
 pub struct Domain {
     storage_factory: Arc<dyn StorageFactory>,
@@ -29,6 +64,10 @@ pub struct Domain {
     identities: Arc<dyn Sequence<Identity>>,
     finder: Arc<dyn Finder>,
     plugins: Arc<RegisteredPlugins>,
+    conflict_retries: usize,
+    conflicts: Arc<AtomicU64>,
+    retry_policy: RetryPolicy,
+    cache: Option<Arc<EntityCache>>,
 }
 
 impl Domain {
@@ -47,39 +86,177 @@ impl Domain {
             identities,
             finder,
             plugins,
+            conflict_retries: DEFAULT_CONFLICT_RETRIES,
+            conflicts: Default::default(),
+            retry_policy: Default::default(),
+            cache: None,
+        }
+    }
+
+    /// How many times work is done again after conflicting with another
+    /// session before giving up.
+    pub fn with_conflict_retries(self, conflict_retries: usize) -> Self {
+        Self {
+            conflict_retries,
+            ..self
+        }
+    }
+
+    pub fn with_retry_policy(self, retry_policy: RetryPolicy) -> Self {
+        Self {
+            retry_policy,
+            ..self
         }
     }
 
+    /// Shares entities read by sessions opened from this domain, so they're
+    /// only read from storage again after they've changed. Only changes
+    /// committed through this domain are seen, changes made by other processes
+    /// sharing the same storage are noticed when saving conflicts.
+    pub fn with_entity_cache(self) -> Self {
+        self.with_entity_cache_capacity(DEFAULT_CACHE_CAPACITY)
+    }
+
+    pub fn with_entity_cache_capacity(self, capacity: usize) -> Self {
+        self.with_shared_entity_cache(EntityCache::new(capacity))
+    }
+
+    /// Like an entity cache, only every cached entity is checked against the
+    /// version in storage as sessions begin, for storage that other processes
+    /// are changing.
+    pub fn with_revalidated_entity_cache(self) -> Self {
+        self.with_shared_entity_cache(EntityCache::default().revalidating())
+    }
+
+    fn with_shared_entity_cache(self, cache: EntityCache) -> Self {
+        if self.cache.is_some() {
+            return self;
+        }
+
+        let cache = Arc::new(cache);
+
+        Self {
+            storage_factory: Arc::new(CachingStorageFactory::new(
+                self.storage_factory,
+                Arc::clone(&cache),
+            )),
+            cache: Some(cache),
+            ..self
+        }
+    }
+
+    /// Versioned scopes of the domain's plugins, for upgrading every entity
+    /// eagerly.
+    pub fn scope_upgrades(&self) -> &ScopeUpgrades {
+        self.plugins.scope_upgrades()
+    }
+
+    pub fn entity_cache(&self) -> Option<&Arc<EntityCache>> {
+        self.cache.as_ref()
+    }
+
+    /// Number of times saving a session has conflicted with another session.
+    pub fn conflicts(&self) -> u64 {
+        self.conflicts.load(Ordering::Relaxed)
+    }
+
+    /// Does the work, which is expected to open and close a session of its
+    /// own, again whenever saving that session conflicts with another.
+    pub fn retry_conflicts<R>(&self, mut work: impl FnMut() -> Result<R>) -> Result<R> {
+        let mut attempt = 0;
+        loop {
+            match work() {
+                Err(e) if ConflictError::is_conflict(&e) => {
+                    let conflicts = self.conflicts.fetch_add(1, Ordering::Relaxed) + 1;
+                    warn!(attempt, conflicts, "conflict: {}", e);
+
+                    if attempt >= self.conflict_retries {
+                        return Err(e);
+                    }
+
+                    attempt += 1;
+                }
+                done => return done,
+            }
+        }
+    }
+
+    /// Performs an action as the actor in a session of its own, retrying on
+    /// conflicts.
+    pub fn perform_as<T: Notifier>(
+        &self,
+        actor: &EntityKey,
+        action: &TaggedJson,
+        notifier: &T,
+    ) -> Result<Effect> {
+        self.retry_conflicts(|| {
+            let session = self.open_session()?;
+            let effect = perform_in(&session, actor, action)?;
+            session.close(notifier)?;
+
+            Ok(effect)
+        })
+    }
+
+    /// Evaluates text in a session of its own. Text is parsed once, should
+    /// saving conflict it's the parsed action that's performed again.
+    pub fn evaluate_and_perform_as<T: Notifier>(
+        &self,
+        evaluate_as: EvaluateAs,
+        text: &str,
+        notifier: &T,
+    ) -> Result<Option<Effect>> {
+        let mut parsed = None;
+
+        self.retry_conflicts(|| {
+            let session = self.open_session()?;
+            let effect = match &parsed {
+                Some((actor, action)) => Some(perform_in(&session, actor, action)?),
+                None => {
+                    let effect = session.evaluate_and_perform_as(evaluate_as, text)?;
+                    parsed = session.parsed();
+                    effect
+                }
+            };
+            session.close(notifier)?;
+
+            Ok(effect)
+        })
+    }
+
     pub fn tick<T: Notifier>(&self, now: DateTime<Utc>, notifier: &T) -> Result<AfterTick> {
         trace!("{:?} tick", now);
 
         let storage = self.storage_factory.create_storage()?;
         match storage.query_futures_before(now)? {
             PendingFutures::Futures(futures) => {
-                let session = self.open_session()?;
                 let processing = futures.len();
 
                 use itertools::Itertools;
                 let futures_by_actor = futures.into_iter().group_by(|f| f.entity.clone());
 
-                for (_key, futures) in futures_by_actor.into_iter() {
-                    let session = session.set_session()?;
+                for (key, futures) in futures_by_actor.into_iter() {
+                    let futures = futures.collect::<Vec<_>>();
 
-                    for future in futures {
-                        info!(key = %future.key, entity = %future.entity, time = %future.time, "delivering");
+                    // Should delivering an actor's futures together fail they're
+                    // delivered individually, so only those failing are retried.
+                    match self.deliver(&futures, now, notifier) {
+                        Ok(()) => {}
+                        Err(e) if futures.len() == 1 => {
+                            self.undelivered_or_warn(&storage, &futures[0], &e, now)
+                        }
+                        Err(e) => {
+                            warn!(entity = %key, "delivering: {:?}", e);
 
-                        let value = serde_json::from_str(&future.serialized)?;
-                        if let Ok(Some(action)) = session.try_deserialize_action(&value) {
-                            if let Some(actor) = session
-                                .recursive_entity(&LookupBy::Key(&future.entity), USER_DEPTH)?
-                            {
-                                let surroundings = session.surroundings(&actor)?;
-                                session.captured(actor, surroundings, action)?;
+                            for future in futures.iter() {
+                                if let Err(e) =
+                                    self.deliver(std::slice::from_ref(future), now, notifier)
+                                {
+                                    self.undelivered_or_warn(&storage, future, &e, now);
+                                }
                             }
                         }
                     }
-
-                    session.close(notifier)?;
                 }
 
                 Ok(AfterTick::Processed(processing))
@@ -89,6 +266,141 @@ impl Domain {
         }
     }
 
+    /// Delivers futures in a session of their own, discarding everything done
+    /// in that session should any of them fail. Futures are only removed, or
+    /// queued again for their following time, along with what they did.
+    fn deliver<T: Notifier>(
+        &self,
+        futures: &[PersistedFuture],
+        now: DateTime<Utc>,
+        notifier: &T,
+    ) -> Result<()> {
+        self.retry_conflicts(|| {
+            let session = self.open_session()?;
+            match deliver_in(&session, futures, now) {
+                Ok(()) => session.close(notifier),
+                Err(e) => {
+                    session.rollback()?;
+
+                    Err(e)
+                }
+            }
+        })
+    }
+
+    /// Queues a future that failed to be delivered to be tried again later,
+    /// or keeps it as a dead letter once it's failed too many times. Futures
+    /// with a cron schedule are then queued for their following time, others
+    /// are removed.
+    fn undelivered(
+        &self,
+        storage: &Rc<dyn Storage>,
+        future: &PersistedFuture,
+        error: &anyhow::Error,
+        now: DateTime<Utc>,
+    ) -> Result<()> {
+        let future = PersistedFuture {
+            attempts: future.attempts + 1,
+            ..future.clone()
+        };
+
+        storage.begin()?;
+
+        let queued = if future.attempts < self.retry_policy.attempts {
+            let time = now + self.retry_policy.delay_after(future.attempts);
+
+            warn!(key = %future.key, attempts = %future.attempts, %time, "retrying: {:?}", error);
+
+            storage.queue(PersistedFuture { time, ..future })
+        } else {
+            storage
+                .dead_letter(&future, &format!("{:?}", error), now)
+                .and_then(|_| match future.following(now) {
+                    Some(time) => storage.queue(PersistedFuture {
+                        time,
+                        attempts: 0,
+                        ..future.clone()
+                    }),
+                    None => storage.cancel(&future.key),
+                })
+        };
+
+        match queued {
+            Ok(()) => storage.commit(),
+            Err(e) => {
+                storage.rollback(false)?;
+
+                Err(e)
+            }
+        }
+    }
+
+    /// Failing to queue or keep an undelivered future is only logged, so
+    /// the remaining futures are still delivered. Those left alone are tried
+    /// again on the following tick.
+    fn undelivered_or_warn(
+        &self,
+        storage: &Rc<dyn Storage>,
+        future: &PersistedFuture,
+        error: &anyhow::Error,
+        now: DateTime<Utc>,
+    ) {
+        if let Err(e) = self.undelivered(storage, future, error, now) {
+            warn!(key = %future.key, "undelivered: {:?}", e);
+        }
+    }
+
+    pub fn query_journal(&self) -> Result<Vec<JournalEntry>> {
+        let storage = self.storage_factory.create_storage()?;
+        storage.query_journal()
+    }
+
+    /// Performs the actions in journal entries again, each entry in a session
+    /// of its own, returning how many entries were replayed. Entities created
+    /// while replaying are given the keys recorded in the entry, so later
+    /// actions referring to them find them again.
+    pub fn replay<T: Notifier>(&self, entries: &[JournalEntry], notifier: &T) -> Result<usize> {
+        let mut replayed = 0;
+
+        for entry in entries.iter().filter(|e| !e.performed.is_empty()) {
+            let session = self.open_session()?;
+            match replay_in(&session, entry) {
+                Ok(()) => {
+                    session.close(notifier)?;
+                    replayed += 1;
+                }
+                Err(e) => {
+                    warn!(time = %entry.time, "replaying: {:?}", e);
+                    session.rollback()?;
+                }
+            }
+        }
+
+        Ok(replayed)
+    }
+
+    pub fn query_dead_letters(&self) -> Result<Vec<DeadFuture>> {
+        let storage = self.storage_factory.create_storage()?;
+        storage.query_dead_letters()
+    }
+
+    /// Notifications the audience hasn't acknowledged, oldest first.
+    pub fn query_mailbox(
+        &self,
+        audience: &EntityKey,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<PersistedMail>> {
+        let storage = self.storage_factory.create_storage()?;
+        storage.query_mailbox(audience, now)
+    }
+
+    pub fn acknowledge(&self, audience: &EntityKey, sequence: u64) -> Result<()> {
+        let storage = self.storage_factory.create_storage()?;
+        storage.begin()?;
+        storage.acknowledge(audience, sequence)?;
+        storage.commit()
+    }
+
     pub fn query_all(&self) -> Result<Vec<PersistedEntity>> {
         let storage = self.storage_factory.create_storage()?;
         storage.query_all()
@@ -129,3 +441,58 @@ impl SessionOpener for Domain {
         self.open_session_with_middleware(vec![])
     }
 }
+
+fn deliver_in(
+    session: &Rc<Session>,
+    futures: &[PersistedFuture],
+    now: DateTime<Utc>,
+) -> Result<()> {
+    let session = session.set_session()?;
+
+    for future in futures {
+        info!(key = %future.key, entity = %future.entity, time = %future.time, "delivering");
+
+        let value = serde_json::from_str(&future.serialized)?;
+        let action = session
+            .try_deserialize_action(&value)?
+            .ok_or_else(|| anyhow!("no action for future"))?;
+        let entity = session
+            .recursive_entity(&LookupBy::Key(&future.entity), USER_DEPTH)?
+            .ok_or_else(|| anyhow!("future for missing entity"))?;
+
+        let surroundings = session.surroundings_for(&entity)?;
+        session.captured(entity, surroundings, action, None)?;
+        session.delivered(future, now);
+    }
+
+    Ok(())
+}
+
+fn replay_in(session: &Rc<Session>, entry: &JournalEntry) -> Result<()> {
+    session.reuse_keys(&entry.created);
+
+    for performed in entry.performed.iter() {
+        info!(actor = %performed.actor, text = ?performed.text, "replaying");
+
+        let action = TaggedJson::new_from(performed.action.clone())?;
+        perform_in(session, &performed.actor, &action)?;
+    }
+
+    Ok(())
+}
+
+fn perform_in(session: &Rc<Session>, actor: &EntityKey, action: &TaggedJson) -> Result<Effect> {
+    let session = session.set_session()?;
+    let actor = session
+        .entity(&LookupBy::Key(actor))?
+        .ok_or(DomainError::EntityNotFound(here!().into()))?;
+
+    let effect = session.perform(Perform::Actor {
+        actor: actor.clone(),
+        action: PerformAction::TaggedJson(action.clone()),
+    })?;
+
+    session.journal_performed(&actor.key(), None, action, &effect)?;
+
+    Ok(effect)
+}
@@ -1,6 +1,7 @@
 mod identifiers;
 mod users;
 
+pub mod cache;
 pub mod domain;
 pub mod notifications;
 pub mod sequences;
@@ -13,6 +14,8 @@ pub mod prelude {
     pub use crate::session::*;
 
     pub use crate::users::model::Credentials;
+    pub use crate::users::model::HasRoles;
     pub use crate::users::model::HasUsernames;
     pub use crate::users::model::HasWellKnownEntities;
+    pub use crate::users::model::Roles;
 }
@@ -4,6 +4,23 @@ use kernel::prelude::{EntityKey, TaggedJson};
 
 pub trait Notifier {
     fn notify(&self, audience: &EntityKey, observed: &TaggedJson) -> Result<()>;
+
+    /// Notifies the audience of something that was also kept in their
+    /// mailbox, which they acknowledge having seen by its sequence number.
+    fn notify_mailed(
+        &self,
+        audience: &EntityKey,
+        observed: &TaggedJson,
+        _sequence: u64,
+    ) -> Result<()> {
+        self.notify(audience, observed)
+    }
+
+    /// Whether the audience will see notifications as they're sent, those who
+    /// won't have them kept in their mailbox instead.
+    fn is_present(&self, _audience: &EntityKey) -> bool {
+        false
+    }
 }
 
 #[derive(Default)]
@@ -5,6 +5,7 @@ use std::sync::Arc;
 use std::time::Instant;
 use std::{
     cell::RefCell,
+    collections::VecDeque,
     env,
     rc::Rc,
     sync::atomic::{AtomicBool, Ordering},
@@ -14,13 +15,15 @@ use tracing::{debug, info, span, trace, warn, Level};
 mod internal;
 mod logs;
 mod state;
+mod suggest;
 
 use crate::identifiers;
 use crate::notifications::Notifier;
 use crate::prelude::DevNullNotifier;
 use crate::sequences::Sequence;
-use crate::storage::{Storage, StorageFactory};
+use crate::storage::{JournalEntry, Performed, PersistedFuture, Storage, StorageFactory};
 use crate::users::model::HasUsernames;
+use kernel::common::{ObservedFuture, SuggestionsReply};
 use kernel::{here, prelude::*};
 use state::State;
 
@@ -67,6 +70,10 @@ pub struct Session {
     identities: Arc<dyn Sequence<Identity>>,
     state: Rc<State>,
     captures: RefCell<Vec<Captured>>,
+    parsed: RefCell<Option<(EntityKey, TaggedJson)>>,
+    performed: RefCell<Vec<Performed>>,
+    created: RefCell<Vec<EntityKey>>,
+    reusing: RefCell<VecDeque<EntityKey>>,
 }
 
 struct Captured {
@@ -76,6 +83,7 @@ struct Captured {
     logs: Logs,
 }
 
+#[derive(Clone, Copy)]
 pub enum EvaluateAs<'a> {
     Name(&'a str),
     Key(&'a EntityKey),
@@ -131,6 +139,10 @@ impl Session {
             identities: Arc::clone(&deps.identities),
             state: Default::default(),
             captures: Default::default(),
+            parsed: Default::default(),
+            performed: Default::default(),
+            created: Default::default(),
+            reusing: Default::default(),
         });
 
         session.initialize()?;
@@ -155,8 +167,10 @@ impl Session {
         actor: EntityPtr,
         surroundings: Surroundings,
         action: Box<dyn Action>,
+        text: Option<&str>,
     ) -> Result<Effect, DomainError> {
         let actor_key = actor.key().clone();
+        let tagged = action.to_tagged_json()?;
         let action = PerformAction::Instance(action.into());
         let perform = Perform::Surroundings {
             surroundings,
@@ -164,12 +178,12 @@ impl Session {
         };
 
         let desc = format!("{:?}", perform);
-        logs::capture(
+        let effect = logs::capture(
             || self.perform(perform.clone()),
             |logs| {
                 let mut captures = self.captures.borrow_mut();
                 captures.push(Captured {
-                    actor_key,
+                    actor_key: actor_key.clone(),
                     time: Utc::now(),
                     desc,
                     logs,
@@ -177,7 +191,51 @@ impl Session {
 
                 Ok(())
             },
-        )
+        )?;
+
+        self.journal_performed(&actor_key, text, &tagged, &effect)?;
+
+        Ok(effect)
+    }
+
+    /// Remembers an action performed on behalf of an actor, so that it's in
+    /// the journal entry for this session once it's committed.
+    pub(crate) fn journal_performed(
+        &self,
+        actor: &EntityKey,
+        text: Option<&str>,
+        action: &TaggedJson,
+        effect: &Effect,
+    ) -> Result<(), DomainError> {
+        self.performed.borrow_mut().push(Performed {
+            actor: actor.clone(),
+            text: text.map(|t| t.to_owned()),
+            action: action.clone().into_tagged(),
+            effect: serde_json::to_value(effect)?,
+        });
+
+        Ok(())
+    }
+
+    /// Removes a future that's been delivered in this session, or queues it
+    /// for the following time in its schedule. Either way it's only saved
+    /// along with everything else done in the session.
+    pub(crate) fn delivered(&self, future: &PersistedFuture, now: DateTime<Utc>) {
+        match future.following(now) {
+            Some(time) => self.state.requeue(PersistedFuture {
+                time,
+                attempts: 0,
+                ..future.clone()
+            }),
+            None => self.state.cancel(&future.key),
+        }
+    }
+
+    /// Hands out these keys to new entities before any fresh ones, so that
+    /// entities created while replaying a journal entry get the keys they
+    /// were originally given.
+    pub(crate) fn reuse_keys(&self, keys: &[EntityKey]) {
+        self.reusing.borrow_mut().extend(keys.iter().cloned());
     }
 
     fn save_logs(&self, forced: bool) -> Result<()> {
@@ -232,6 +290,22 @@ impl Session {
         .context(here!())?)
     }
 
+    /// Surroundings for an entity that may not be an actor. The world and
+    /// areas get surroundings of their own rather than posing as actors.
+    pub(crate) fn surroundings_for(&self, entity: &EntityPtr) -> Result<Surroundings> {
+        let world = self.finder.find_world()?;
+        if entity.key() == world.key() {
+            return Ok(Surroundings::World { world });
+        }
+
+        let area = self.finder.find_area(entity)?;
+        if entity.key() == area.key() {
+            return Ok(Surroundings::Area { world, area });
+        }
+
+        self.surroundings(entity)
+    }
+
     pub fn evaluate_and_perform_as(
         &self,
         evaluate_as: EvaluateAs,
@@ -251,9 +325,12 @@ impl Session {
 
         match self.parse_action(&surroundings, text)? {
             Some(action) => {
-                debug!("{:#?}", action.to_tagged_json()?.into_tagged());
+                let tagged = action.to_tagged_json()?;
+                debug!("{:#?}", tagged.clone().into_tagged());
+
+                self.parsed.replace(Some((actor.key().clone(), tagged)));
 
-                match session.captured(actor, surroundings, action) {
+                match session.captured(actor, surroundings, action, Some(text)) {
                     Ok(i) => Ok(Some(i)),
                     Err(original_err) => {
                         warn!("error: {:?}", original_err);
@@ -271,10 +348,37 @@ impl Session {
                     }
                 }
             }
-            None => Ok(None),
+            None => session.suggestions(&surroundings, text),
         }
     }
 
+    /// The actor and action most recently parsed from text evaluated in this
+    /// session, so the action can be performed again in another session.
+    pub fn parsed(&self) -> Option<(EntityKey, TaggedJson)> {
+        self.parsed.borrow().clone()
+    }
+
+    fn suggestions(
+        &self,
+        surroundings: &Surroundings,
+        text: &str,
+    ) -> Result<Option<Effect>, DomainError> {
+        let verbs = self.plugins.borrow().verbs();
+        let names = self
+            .finder
+            .find_nearby(surroundings)?
+            .iter()
+            .map(|e| e.name())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let suggestions = suggest::suggest(&verbs, &names, text);
+        if suggestions.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(SuggestionsReply { suggestions }.try_into()?))
+    }
+
     pub fn initialize(&self) -> Result<()> {
         let _activated = self.set_session()?;
 
@@ -327,15 +431,56 @@ impl Session {
         Ok(())
     }
 
+    /// Discards everything done in the session, closing it.
+    pub fn rollback(&self) -> Result<()> {
+        info!("rolling back");
+
+        self.open.store(false, Ordering::Relaxed);
+
+        self.storage.rollback(false)
+    }
+
     fn save_changes<T: Notifier>(&self, notifier: &T) -> Result<()> {
+        let performed = self.performed.take();
+        let created = self.created.take();
+
+        if self.state.prevented() {
+            // An action was refused because of a denied write, so nothing
+            // from this session is safe to keep.
+            info!("prevented, rolling back");
+            return self.storage.rollback(false);
+        }
+
         match self.state.close(&self.storage, notifier, &self.finder) {
-            Ok(changes) => {
-                if changes {
+            Ok(flushed) => {
+                if flushed.any() {
                     if should_force_rollback() {
                         let _span = span!(Level::DEBUG, "FORCED").entered();
                         self.storage.rollback(true)
                     } else {
-                        self.storage.commit()
+                        let entry = JournalEntry {
+                            time: Utc::now(),
+                            performed,
+                            raised: flushed.raised,
+                            changed: flushed.changed,
+                            created,
+                        };
+
+                        if let Err(e) = self.storage.append(&entry) {
+                            self.storage.rollback(false)?;
+
+                            return Err(e);
+                        }
+
+                        self.storage.commit()?;
+
+                        for outgoing in flushed.outgoing.iter() {
+                            if let Err(e) = outgoing.send(notifier) {
+                                warn!("notify: {:?}", e);
+                            }
+                        }
+
+                        Ok(())
                     }
                 } else {
                     self.storage.rollback(true)
@@ -363,6 +508,7 @@ impl Session {
         trace!("loading");
         if let Some(persisted) = self.storage.load(lookup)? {
             let added = self.state.add_persisted(persisted)?;
+            self.resolve_prototype(&added.entity)?;
             if depth > 0 {
                 for key in added.find_refs().into_iter() {
                     self.load_entity(&LookupBy::Key(&key), depth - 1)?;
@@ -373,6 +519,24 @@ impl Session {
             Ok(None)
         }
     }
+
+    fn resolve_prototype(&self, entity: &EntityPtr) -> Result<()> {
+        let Some(parent) = entity.borrow().parent().cloned() else {
+            return Ok(());
+        };
+
+        // Prototypes are loaded like any other entity, so their own
+        // prototypes are resolved before they're inherited from here.
+        match self.load_entity(&LookupBy::Key(parent.key()), 0)? {
+            Some(prototype) => entity.borrow_mut().set_prototype(
+                &prototype.borrow(),
+                self.registered_plugins.scope_upgrades(),
+            ),
+            None => warn!(key = ?parent.key(), "missing prototype"),
+        }
+
+        Ok(())
+    }
 }
 
 impl Performer for Session {
@@ -413,7 +577,13 @@ impl ActiveSession for Session {
     }
 
     fn new_key(&self) -> EntityKey {
-        self.keys.following()
+        let key = self
+            .reusing
+            .borrow_mut()
+            .pop_front()
+            .unwrap_or_else(|| self.keys.following());
+        self.created.borrow_mut().push(key.clone());
+        key
     }
 
     fn new_identity(&self) -> Identity {
@@ -459,9 +629,13 @@ impl ActiveSession for Session {
 
         self.state.add_entity(gid, entity)?;
 
-        Ok(self
+        let added = self
             .entity(&LookupBy::Key(&key))?
-            .expect("Bug: Newly added entity has no EntityPtr"))
+            .expect("Bug: Newly added entity has no EntityPtr");
+
+        self.resolve_prototype(&added)?;
+
+        Ok(added)
     }
 
     fn obliterate(&self, entity: &EntityPtr) -> Result<(), DomainError> {
@@ -489,6 +663,47 @@ impl ActiveSession for Session {
 
         self.perform(perform).map(|_| ())
     }
+
+    fn futures(&self, entity: &EntityKey) -> Result<Vec<ObservedFuture>, DomainError> {
+        self.storage
+            .list_futures(entity)?
+            .into_iter()
+            .filter(|future| !self.state.cancelled(&future.key))
+            .map(|future| {
+                let value: JsonValue = serde_json::from_str(&future.serialized)?;
+
+                Ok(ObservedFuture {
+                    key: future.key,
+                    time: future.time.to_rfc3339(),
+                    cron: future.cron,
+                    action: TaggedJson::new_from(value)?,
+                })
+            })
+            .collect()
+    }
+
+    fn versions(&self, key: &EntityKey) -> Result<Vec<EntityVersion>, DomainError> {
+        self.storage
+            .query_versions(key)?
+            .into_iter()
+            .map(|persisted| {
+                Ok(EntityVersion {
+                    version: persisted.version,
+                    value: persisted.to_json_value()?,
+                })
+            })
+            .collect()
+    }
+
+    fn cancel(&self, key: &str) -> Result<bool, DomainError> {
+        if self.state.cancelled(key) || self.storage.get_future(key)?.is_none() {
+            return Ok(false);
+        }
+
+        self.state.cancel(key);
+
+        Ok(true)
+    }
 }
 
 impl Drop for Session {
@@ -561,6 +776,22 @@ impl Middleware for ExpandSurroundingsMiddleware {
                     action,
                 })
             }
+            Perform::World { action } => {
+                let world = self.finder.find_world()?;
+
+                next.handle(Perform::Surroundings {
+                    surroundings: Surroundings::World { world },
+                    action,
+                })
+            }
+            Perform::Area { area, action } => {
+                let world = self.finder.find_world()?;
+
+                next.handle(Perform::Surroundings {
+                    surroundings: Surroundings::Area { world, area },
+                    action,
+                })
+            }
             _ => next.handle(value),
         }
     }
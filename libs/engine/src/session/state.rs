@@ -1,5 +1,5 @@
 use anyhow::{anyhow, Result};
-use burrow_bon::prelude::{Attempted, DottedPaths, Policy, Scoured, SecurityContext};
+use burrow_bon::prelude::{Attempted, DottedPaths, Modified, Policy, Scoured, SecurityContext};
 use chrono::Utc;
 use std::{
     cell::RefCell,
@@ -11,9 +11,13 @@ use tracing::*;
 use super::internal::{Added, Entities, LoadedEntity};
 use crate::{
     notifications::Notifier,
-    storage::{PersistedEntity, PersistedFuture, Storage},
+    storage::{Changed, PersistedEntity, PersistedFuture, Storage},
+    users::model::Usernames,
+};
+use kernel::{
+    common::{DeniedReply, Distant},
+    prelude::*,
 };
-use kernel::prelude::*;
 
 #[derive(Default)]
 pub struct State {
@@ -21,8 +25,11 @@ pub struct State {
     actors: RefCell<Vec<EntityKey>>,
     raised: Rc<RefCell<Vec<Raised>>>,
     futures: Rc<RefCell<Vec<FutureAction>>>,
+    cancelled: RefCell<Vec<String>>,
+    requeued: RefCell<Vec<PersistedFuture>>,
     destroyed: RefCell<Vec<EntityKey>>,
     write_expected: AtomicBool,
+    prevented: AtomicBool,
 }
 
 impl State {
@@ -44,11 +51,34 @@ impl State {
         storage: &Rc<dyn Storage>,
         notifier: &T,
         finder: &Arc<dyn Finder>,
-    ) -> Result<bool> {
-        let entities_changed = self.flush_entities(storage)?;
-        let raised_changed = self.flush_raised(notifier, finder)?;
-        let futures_changed = self.flush_futures(storage)?;
-        Ok(entities_changed || raised_changed || futures_changed)
+    ) -> Result<Flushed> {
+        let changed = self.flush_entities(storage)?;
+        let (raised, outgoing) = self.flush_raised(storage, notifier, finder)?;
+        let scheduled = self.flush_futures(storage)?;
+        Ok(Flushed {
+            changed,
+            raised,
+            outgoing,
+            scheduled,
+        })
+    }
+
+    pub(crate) fn cancel(&self, key: &str) {
+        self.write_expected
+            .store(true, std::sync::atomic::Ordering::Relaxed);
+
+        self.cancelled.borrow_mut().push(key.to_owned());
+    }
+
+    pub(crate) fn requeue(&self, future: PersistedFuture) {
+        self.write_expected
+            .store(true, std::sync::atomic::Ordering::Relaxed);
+
+        self.requeued.borrow_mut().push(future);
+    }
+
+    pub(crate) fn cancelled(&self, key: &str) -> bool {
+        self.cancelled.borrow().iter().any(|k| k == key)
     }
 
     pub fn size(&self) -> usize {
@@ -60,6 +90,10 @@ impl State {
             .load(core::sync::atomic::Ordering::Relaxed)
     }
 
+    pub(crate) fn prevented(&self) -> bool {
+        self.prevented.load(core::sync::atomic::Ordering::Relaxed)
+    }
+
     pub(crate) fn lookup_entity(&self, lookup: &LookupBy) -> Result<Option<EntityPtr>> {
         self.entities.lookup_entity(lookup)
     }
@@ -74,7 +108,23 @@ impl State {
         self.entities.add_entity(gid, entity)
     }
 
-    fn flush_entities(&self, storage: &Rc<dyn Storage>) -> Result<bool> {
+    fn check_permissions(&self) -> Result<Option<WriteDenied>> {
+        let actors = self.actors.borrow();
+        let permissions = Permissions { actors: &actors };
+        let denied = self
+            .entities
+            .foreach_entity_mut(|l| match permissions.check(l) {
+                Ok(_) => Ok(None),
+                Err(e) => match e.downcast::<WriteDenied>() {
+                    Ok(denied) => Ok(Some(denied)),
+                    Err(e) => Err(e),
+                },
+            })?;
+
+        Ok(denied.into_iter().flatten().next())
+    }
+
+    fn flush_entities(&self, storage: &Rc<dyn Storage>) -> Result<Vec<Changed>> {
         let mut destroyed = self.destroyed.borrow_mut();
         let saves = SavesEntities {
             actors: self.actors.borrow().clone(),
@@ -88,33 +138,80 @@ impl State {
         Ok(changes)
     }
 
-    fn flush_raised<T: Notifier>(&self, notifier: &T, finder: &Arc<dyn Finder>) -> Result<bool> {
+    fn flush_raised<T: Notifier>(
+        &self,
+        storage: &Rc<dyn Storage>,
+        notifier: &T,
+        finder: &Arc<dyn Finder>,
+    ) -> Result<(Vec<JsonValue>, Vec<Outgoing>)> {
         let mut pending = self.raised.borrow_mut();
         if pending.is_empty() {
-            return Ok(false);
+            return Ok((Vec::new(), Vec::new()));
         }
 
         info!(pending = %pending.len(), "raising");
 
+        let now = Utc::now();
+        let usernames = finder.find_world()?.scope::<Usernames>()?;
+        let mut outgoing = Vec::new();
+
         for raised in pending.iter() {
             trace!("{:?}", raised.event);
-            let audience_keys = finder.find_audience(&raised.audience)?;
-            for key in audience_keys {
-                notifier.notify(&key, &raised.event)?;
+            for listener in finder.find_audience(&raised.audience)? {
+                let event = match listener.distance {
+                    Some(distance) => Distant {
+                        distance: distance.areas,
+                        direction: distance.direction,
+                        event: raised.event.clone().into_tagged(),
+                    }
+                    .to_tagged_json()?,
+                    None => raised.event.clone(),
+                };
+                // Only players have a mailbox, and only need one when they
+                // aren't around to see the notification as it's sent.
+                let mailed = usernames
+                    .as_ref()
+                    .map(|u| u.is_user(&listener.key))
+                    .unwrap_or_default()
+                    && !notifier.is_present(&listener.key);
+                let sequence = if mailed {
+                    let serialized = event.clone().into_tagged().to_string();
+                    Some(storage.post(&listener.key, now, &serialized)?)
+                } else {
+                    None
+                };
+                outgoing.push(Outgoing {
+                    audience: listener.key,
+                    event,
+                    sequence,
+                });
             }
         }
 
-        pending.clear();
+        let raised = pending
+            .drain(..)
+            .map(|raised| raised.event.into_tagged())
+            .collect();
 
-        Ok(true)
+        Ok((raised, outgoing))
     }
 
     fn flush_futures(&self, storage: &Rc<dyn Storage>) -> Result<bool> {
         let mut futures = self.futures.borrow_mut();
-        if futures.is_empty() {
+        let mut cancelled = self.cancelled.borrow_mut();
+        let mut requeued = self.requeued.borrow_mut();
+        if futures.is_empty() && cancelled.is_empty() && requeued.is_empty() {
             return Ok(false);
         }
 
+        for key in cancelled.drain(..) {
+            storage.cancel(&key)?;
+        }
+
+        for future in requeued.drain(..) {
+            storage.queue(future)?;
+        }
+
         for future in futures.iter() {
             let (cron, time) = match &future.schedule {
                 FutureSchedule::Utc(time) => (None, Some(time.clone())),
@@ -132,6 +229,7 @@ impl State {
                     cron,
                     time,
                     serialized: future.action.clone().into_tagged().to_string(),
+                    attempts: 0,
                 })?;
             }
         }
@@ -182,14 +280,29 @@ impl Performer for State {
                     let _span = span!(Level::DEBUG, "A").entered();
                     info!("action:perform {:?}", &action);
 
-                    let actor = surroundings.actor();
-                    self.include_actor(actor)?;
+                    if let Ok(actor) = surroundings.actor() {
+                        self.include_actor(actor)?;
+                    }
 
                     let res = action.perform(get_my_session()?, &surroundings);
                     if let Ok(effect) = &res {
                         if !action.is_read_only() {
                             self.write_expected
                                 .store(true, std::sync::atomic::Ordering::Relaxed);
+
+                            // Catch denied writes here, rather than during the
+                            // flush, so that the action is prevented instead of
+                            // the whole session failing.
+                            if let Some(denied) = self.check_permissions()? {
+                                warn!("action:denied {}", denied);
+                                self.prevented
+                                    .store(true, std::sync::atomic::Ordering::Relaxed);
+                                return Ok(DeniedReply {
+                                    key: denied.key.to_string(),
+                                    paths: denied.paths,
+                                }
+                                .try_into()?);
+                            }
                         }
                         info!("action:effect {:?}", effect);
                     } else {
@@ -225,87 +338,151 @@ impl Performer for State {
     }
 }
 
+/// Everything saved when closing the state of a session.
+pub struct Flushed {
+    pub changed: Vec<Changed>,
+    pub raised: Vec<JsonValue>,
+    pub outgoing: Vec<Outgoing>,
+    pub scheduled: bool,
+}
+
+/// A notification for a listener, sent once the session that raised it has
+/// been committed. Those kept in the listener's mailbox have a sequence.
+pub struct Outgoing {
+    pub audience: EntityKey,
+    pub event: TaggedJson,
+    pub sequence: Option<u64>,
+}
+
+impl Outgoing {
+    pub fn send<T: Notifier>(&self, notifier: &T) -> Result<()> {
+        match self.sequence {
+            Some(sequence) => notifier.notify_mailed(&self.audience, &self.event, sequence),
+            None => notifier.notify(&self.audience, &self.event),
+        }
+    }
+}
+
+impl Flushed {
+    pub fn any(&self) -> bool {
+        !self.changed.is_empty() || !self.raised.is_empty() || self.scheduled
+    }
+}
+
 pub struct ModifiedEntity(PersistedEntity);
 
-pub struct SavesEntities<'a> {
-    pub actors: Vec<EntityKey>,
-    pub storage: &'a Rc<dyn Storage>,
-    pub destroyed: &'a Vec<EntityKey>,
+/// Returned when flushing an entity would write to paths the session's actors
+/// aren't permitted to modify.
+#[derive(Debug, Clone)]
+pub struct WriteDenied {
+    pub key: EntityKey,
+    pub paths: Vec<String>,
 }
 
-impl<'a> SavesEntities<'a> {
+impl std::fmt::Display for WriteDenied {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "write denied to {} ({})",
+            self.key,
+            self.paths.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for WriteDenied {}
+
+struct Permissions<'a> {
+    actors: &'a [EntityKey],
+}
+
+impl<'a> Permissions<'a> {
+    fn security_contexts(&self, before: &JsonValue) -> Vec<SecurityContext<EntityKey>> {
+        // Ownership is taken from the persisted copy, otherwise an actor could
+        // chown an entity and then modify it in the same transaction.
+        self.actors
+            .iter()
+            .map(|actor| entity_security_context(actor, before))
+            .collect()
+    }
+
+    /// Every actor in the session has to be allowed to write a path. Sessions
+    /// without any actors are internal and so are unrestricted.
     fn apply_permissions(
         &self,
-        sc: SecurityContext<EntityKey>,
-        modified: &DottedPaths,
+        key: &EntityKey,
+        modified: &Modified,
         acls: &Vec<Scoured<Acls>>,
-    ) -> Result<()> {
-        let policy = Policy::new(acls, sc);
-        for path in modified.iter() {
-            // TODO Easy elim-clone
-            match policy.allows(Attempted::Write(path.clone())) {
-                Some(denied) => warn!("{:?} {:?}", denied, path),
-                None => trace!("{:?} allowed", path),
+    ) -> Result<(), WriteDenied> {
+        let mut denied: Vec<String> = Vec::new();
+        for sc in self.security_contexts(&modified.before) {
+            debug!(sc = ?sc, "sc");
+            let policy = Policy::new(acls, sc);
+            for path in modified.paths.iter() {
+                // TODO Easy elim-clone
+                match policy.allows(Attempted::Write(path.clone())) {
+                    Some(reason) => {
+                        warn!("{:?} {:?}", reason, path);
+                        let path = path.to_string();
+                        if !denied.contains(&path) {
+                            denied.push(path);
+                        }
+                    }
+                    None => trace!("{:?} allowed", path),
+                }
             }
         }
 
-        Ok(())
+        if denied.is_empty() {
+            let paths: DottedPaths = acls.iter().map(|s| s.path.clone()).collect();
+            let paths: Vec<String> = paths.into();
+            info!(paths = ?paths, "permitted");
+
+            Ok(())
+        } else {
+            Err(WriteDenied {
+                key: key.clone(),
+                paths: denied,
+            })
+        }
     }
 
-    fn check_for_changes(&self, l: &mut LoadedEntity) -> Result<Option<ModifiedEntity>> {
+    fn check(&self, l: &LoadedEntity) -> Result<Option<Modified>> {
         use burrow_bon::prelude::{AnyChanges, Original};
 
-        let _span = span!(Level::INFO, "flushing", key = l.key.to_string()).entered();
-
-        if let Some(modified) = any_entity_changes(AnyChanges {
+        let Some(modified) = any_entity_changes(AnyChanges {
             before: l.serialized.as_ref().map(Original::String),
             after: l.entity.clone(),
-        })? {
-            if let Some(acls) = burrow_bon::prelude::find_acls(&modified.before) {
-                let from_entity = {
-                    let entity = l.entity.borrow();
-                    let owner = entity.owner().cloned();
-                    let creator = entity.creator().cloned();
-                    (owner, creator)
-                };
-
-                let sc = match from_entity {
-                    (None, None) => {
-                        warn!("no owner/creator");
+        })?
+        else {
+            return Ok(None);
+        };
 
-                        None
-                    }
-                    (None, Some(_creator)) => {
-                        warn!("no owner");
+        if !self.actors.is_empty() {
+            if let Some(acls) = burrow_bon::prelude::find_acls(&modified.before) {
+                self.apply_permissions(&l.key, &modified, &acls)?;
+            }
+        }
 
-                        None
-                    }
-                    (Some(_), None) => todo!(),
-                    (Some(owner), Some(creator)) => match &self.actors.as_slice() {
-                        [actor] => Some(SecurityContext {
-                            actor: actor.clone(),
-                            owner: owner.key().clone(),
-                            creator: creator.key().clone(),
-                        }),
-                        _ => {
-                            warn!("expected 1 actor {:?}", &self.actors);
-
-                            None
-                        }
-                    },
-                };
+        Ok(Some(modified))
+    }
+}
 
-                if let Some(sc) = sc {
-                    let paths: DottedPaths = acls.iter().map(|s| s.path.clone()).collect();
+pub struct SavesEntities<'a> {
+    pub actors: Vec<EntityKey>,
+    pub storage: &'a Rc<dyn Storage>,
+    pub destroyed: &'a Vec<EntityKey>,
+}
 
-                    self.apply_permissions(sc.clone(), &modified.paths, &acls)?;
+impl<'a> SavesEntities<'a> {
+    fn check_for_changes(&self, l: &mut LoadedEntity) -> Result<Option<ModifiedEntity>> {
+        let _span = span!(Level::INFO, "flushing", key = l.key.to_string()).entered();
 
-                    let paths: Vec<String> = paths.into();
-                    debug!(sc = ?sc, "sc");
-                    info!(paths = ?paths, "permitted");
-                }
-            }
+        let permissions = Permissions {
+            actors: &self.actors,
+        };
 
+        if let Some(modified) = permissions.check(l)? {
             // Serialize to string now that we know we'll use this.
             let serialized = modified.after.to_string();
 
@@ -340,13 +517,18 @@ impl<'a> SavesEntities<'a> {
         self.destroyed.contains(key)
     }
 
-    pub fn save_modified_entities(&self, entities: &Entities) -> Result<bool> {
-        Ok(!self
-            .get_modified_entities(entities)?
+    pub fn save_modified_entities(&self, entities: &Entities) -> Result<Vec<Changed>> {
+        self.get_modified_entities(entities)?
             .into_iter()
-            .map(|modified| self.save_entity(&modified))
-            .collect::<Result<Vec<_>>>()?
-            .is_empty())
+            .map(|modified| {
+                self.save_entity(&modified)?;
+
+                Ok(Changed {
+                    key: EntityKey::new(&modified.0.key),
+                    version: modified.0.version,
+                })
+            })
+            .collect()
     }
 
     fn get_modified_entities(&self, entities: &Entities) -> Result<Vec<ModifiedEntity>> {
@@ -0,0 +1,114 @@
+/// Corrections for text that nothing parsed, built by replacing the first
+/// word with similar verbs and any other words with similar words from the
+/// names of nearby entities.
+pub(super) fn suggest(verbs: &[String], names: &[String], text: &str) -> Vec<String> {
+    let words = text
+        .split_whitespace()
+        .map(|w| w.to_lowercase())
+        .collect::<Vec<_>>();
+
+    let Some((first, rest)) = words.split_first() else {
+        return Vec::new();
+    };
+
+    let name_words = names
+        .iter()
+        .flat_map(|n| n.split_whitespace())
+        .map(|w| w.to_lowercase())
+        .collect::<Vec<_>>();
+
+    let rest = rest
+        .iter()
+        .map(|w| closest(&name_words, w).unwrap_or(w.as_str()))
+        .collect::<Vec<_>>();
+
+    let mut suggestions = Vec::new();
+    for verb in similar(verbs, first).into_iter().take(MAXIMUM_SUGGESTIONS) {
+        let suggestion = std::iter::once(verb.as_str())
+            .chain(rest.iter().copied())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        if suggestion != words.join(" ") && !suggestions.contains(&suggestion) {
+            suggestions.push(suggestion);
+        }
+    }
+
+    suggestions
+}
+
+const MAXIMUM_SUGGESTIONS: usize = 3;
+
+/// Shorter words need to be closer, otherwise everything is similar to them.
+fn maximum_distance(word: &str) -> usize {
+    if word.len() <= 4 {
+        1
+    } else {
+        2
+    }
+}
+
+fn is_word(word: &str) -> bool {
+    word.len() > 1 && word.chars().all(|c| c.is_alphabetic())
+}
+
+/// Candidates similar to the word, most similar first. Candidates the word
+/// is a prefix of are considered the most similar.
+fn similar<'c>(candidates: &'c [String], word: &str) -> Vec<&'c String> {
+    if !is_word(word) {
+        return Vec::new();
+    }
+
+    if let Some(exact) = candidates.iter().find(|c| *c == word) {
+        return vec![exact];
+    }
+
+    let mut ranked = candidates
+        .iter()
+        .filter_map(|c| {
+            if c.starts_with(word) {
+                Some((0, c))
+            } else {
+                let d = distance(word, c);
+                (d <= maximum_distance(word)).then_some((d, c))
+            }
+        })
+        .collect::<Vec<_>>();
+
+    ranked.sort();
+
+    ranked.into_iter().map(|(_, c)| c).collect()
+}
+
+fn closest<'c>(candidates: &'c [String], word: &str) -> Option<&'c str> {
+    similar(candidates, word).first().map(|c| c.as_str())
+}
+
+/// Edit distance between two words, counting swapped neighbouring letters as
+/// a single edit since that's how most typos happen.
+fn distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+
+    d[a.len()][b.len()]
+}
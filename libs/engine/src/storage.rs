@@ -1,15 +1,31 @@
 use anyhow::Result;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
 use std::{
     collections::HashMap,
     rc::Rc,
-    sync::{Arc, RwLock},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock,
+    },
 };
+use tracing::warn;
 
-use kernel::prelude::{EntityGid, EntityKey, JsonValue, LookupBy};
+use kernel::prelude::{DomainError, EntityGid, EntityKey, JsonValue, LookupBy};
 
 pub trait EntityStorage: FutureStorage {
     fn load(&self, lookup: &LookupBy) -> Result<Option<PersistedEntity>>;
+
+    /// Key and version of every stored entity, for checking copies of entities
+    /// kept elsewhere are current without loading them again.
+    fn stored_versions(&self) -> Result<Vec<(EntityKey, u64)>> {
+        Ok(self
+            .query_all()?
+            .into_iter()
+            .map(|e| (EntityKey::new(&e.key), e.version))
+            .collect())
+    }
+
     fn save(&self, entity: &PersistedEntity) -> Result<()>;
     fn delete(&self, entity: &PersistedEntity) -> Result<()>;
     fn begin(&self) -> Result<()>;
@@ -18,7 +34,20 @@ pub trait EntityStorage: FutureStorage {
     fn query_all(&self) -> Result<Vec<PersistedEntity>>;
 }
 
-pub trait Storage: EntityStorage + FutureStorage {}
+/// Previous versions of entities, kept whenever they're saved over. Only the
+/// most recent versions of each entity are kept, as configured when creating
+/// the storage.
+pub trait EntityVersionStorage: EntityStorage {
+    /// Versions kept for the entity, the newest first.
+    fn query_versions(&self, key: &EntityKey) -> Result<Vec<PersistedEntity>>;
+}
+
+pub const DEFAULT_VERSION_RETENTION: usize = 20;
+
+pub trait Storage:
+    EntityStorage + EntityVersionStorage + FutureStorage + JournalStorage + MailboxStorage
+{
+}
 
 pub trait StorageFactory: Send + Sync {
     fn migrate(&self) -> Result<()>;
@@ -33,6 +62,33 @@ pub struct PersistedFuture {
     pub cron: Option<String>,
     pub time: chrono::DateTime<chrono::Utc>,
     pub serialized: String,
+    pub attempts: u32,
+}
+
+impl PersistedFuture {
+    /// When a future with a cron schedule is due again after now, futures
+    /// without one are never due again.
+    pub fn following(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        use std::str::FromStr;
+
+        let spec = self.cron.as_ref()?;
+        match cron::Schedule::from_str(spec) {
+            Ok(schedule) => schedule.after(&now).next(),
+            Err(e) => {
+                warn!(key = %self.key, "cron error: {:?}", e);
+                None
+            }
+        }
+    }
+}
+
+/// A future that failed to be delivered too many times to keep trying, kept
+/// along with why it failed the last time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeadFuture {
+    pub future: PersistedFuture,
+    pub error: String,
+    pub failed: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -53,7 +109,113 @@ impl PendingFutures {
 pub trait FutureStorage {
     fn queue(&self, future: PersistedFuture) -> Result<()>;
     fn cancel(&self, key: &str) -> Result<()>;
+    /// Futures due by now, which are left queued until they're cancelled
+    /// or queued again once they've been delivered.
     fn query_futures_before(&self, now: DateTime<Utc>) -> Result<PendingFutures>;
+    fn list_futures(&self, entity: &EntityKey) -> Result<Vec<PersistedFuture>>;
+    fn get_future(&self, key: &str) -> Result<Option<PersistedFuture>>;
+    fn reschedule(&self, key: &str, time: DateTime<Utc>) -> Result<()>;
+    fn dead_letter(
+        &self,
+        future: &PersistedFuture,
+        error: &str,
+        failed: DateTime<Utc>,
+    ) -> Result<()>;
+    fn query_dead_letters(&self) -> Result<Vec<DeadFuture>>;
+}
+
+/// Append only record of committed sessions, entries are appended in the
+/// same transaction as the changes they describe.
+pub trait JournalStorage {
+    fn append(&self, entry: &JournalEntry) -> Result<()>;
+    fn query_journal(&self) -> Result<Vec<JournalEntry>>;
+}
+
+/// Everything a committed session did, in the order it was done.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub time: DateTime<Utc>,
+    pub performed: Vec<Performed>,
+    pub raised: Vec<JsonValue>,
+    pub changed: Vec<Changed>,
+    /// Keys handed out to new entities, in the order they were handed out,
+    /// so that replaying the entry can give them out again.
+    #[serde(default)]
+    pub created: Vec<EntityKey>,
+}
+
+/// An action performed by an actor, along with the text it was parsed from
+/// when there was any.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Performed {
+    pub actor: EntityKey,
+    pub text: Option<String>,
+    pub action: JsonValue,
+    pub effect: JsonValue,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Changed {
+    pub key: EntityKey,
+    pub version: u64,
+}
+
+/// Notifications kept for their audience, so those who weren't around when
+/// they were raised can catch up on them. Notifications are forgotten once
+/// they're acknowledged or no longer fit within the mailbox limits.
+pub trait MailboxStorage {
+    /// Keeps the notification, returning a sequence number greater than
+    /// those of notifications posted before it.
+    fn post(&self, audience: &EntityKey, time: DateTime<Utc>, serialized: &str) -> Result<u64>;
+    /// Notifications that haven't been acknowledged or expired, oldest first.
+    fn query_mailbox(&self, audience: &EntityKey, now: DateTime<Utc>)
+        -> Result<Vec<PersistedMail>>;
+    /// Forgets notifications up to and including the sequence number.
+    fn acknowledge(&self, audience: &EntityKey, sequence: u64) -> Result<()>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PersistedMail {
+    pub sequence: u64,
+    pub audience: EntityKey,
+    pub time: DateTime<Utc>,
+    pub serialized: String,
+}
+
+pub const DEFAULT_MAILBOX_CAPACITY: usize = 100;
+
+pub const DEFAULT_MAILBOX_TTL_HOURS: i64 = 24 * 7;
+
+/// How many notifications are kept for each audience and for how long.
+#[derive(Clone, Debug)]
+pub struct MailboxLimits {
+    pub capacity: usize,
+    pub ttl: Duration,
+}
+
+impl Default for MailboxLimits {
+    fn default() -> Self {
+        Self {
+            capacity: DEFAULT_MAILBOX_CAPACITY,
+            ttl: Duration::hours(DEFAULT_MAILBOX_TTL_HOURS),
+        }
+    }
+}
+
+/// Saving an entity failed because another session saved a newer version of
+/// it first, everything done in the session needs to be done again.
+#[derive(Debug, thiserror::Error)]
+#[error("conflict saving {key} version {version}")]
+pub struct ConflictError {
+    pub key: String,
+    pub version: u64,
+}
+
+impl ConflictError {
+    pub fn is_conflict(e: &anyhow::Error) -> bool {
+        e.chain().any(|e| e.is::<ConflictError>())
+            || matches!(e.downcast_ref::<DomainError>(), Some(DomainError::Anyhow(e)) if Self::is_conflict(e))
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -70,9 +232,42 @@ impl PersistedEntity {
     }
 }
 
-#[derive(Default)]
 pub struct InMemoryStorageFactory {
     entities: Arc<RwLock<HashMap<EntityKey, PersistedEntity>>>,
+    versions: Arc<RwLock<HashMap<EntityKey, Vec<PersistedEntity>>>>,
+    retention: usize,
+    futures: Arc<RwLock<HashMap<String, PersistedFuture>>>,
+    dead: Arc<RwLock<HashMap<String, DeadFuture>>>,
+    journal: Arc<RwLock<Vec<JournalEntry>>>,
+    mailbox: Arc<RwLock<Vec<PersistedMail>>>,
+    sequence: Arc<AtomicU64>,
+    limits: MailboxLimits,
+}
+
+impl Default for InMemoryStorageFactory {
+    fn default() -> Self {
+        Self {
+            entities: Default::default(),
+            versions: Default::default(),
+            retention: DEFAULT_VERSION_RETENTION,
+            futures: Default::default(),
+            dead: Default::default(),
+            journal: Default::default(),
+            mailbox: Default::default(),
+            sequence: Default::default(),
+            limits: Default::default(),
+        }
+    }
+}
+
+impl InMemoryStorageFactory {
+    pub fn with_version_retention(self, retention: usize) -> Self {
+        Self { retention, ..self }
+    }
+
+    pub fn with_mailbox_limits(self, limits: MailboxLimits) -> Self {
+        Self { limits, ..self }
+    }
 }
 
 impl StorageFactory for InMemoryStorageFactory {
@@ -83,8 +278,15 @@ impl StorageFactory for InMemoryStorageFactory {
     fn create_storage(&self) -> Result<Rc<dyn Storage>> {
         Ok(Rc::new(InMemoryStorage {
             entities: self.entities.clone(),
+            versions: self.versions.clone(),
+            retention: self.retention,
             pending: Default::default(),
-            futures: Default::default(),
+            futures: self.futures.clone(),
+            dead: self.dead.clone(),
+            journal: self.journal.clone(),
+            mailbox: self.mailbox.clone(),
+            sequence: self.sequence.clone(),
+            limits: self.limits.clone(),
         }))
     }
 }
@@ -92,11 +294,20 @@ impl StorageFactory for InMemoryStorageFactory {
 enum Pending {
     Save(PersistedEntity),
     Delete(PersistedEntity),
+    Append(JournalEntry),
+    Post(PersistedMail),
 }
 
 pub struct InMemoryStorage {
     entities: Arc<RwLock<HashMap<EntityKey, PersistedEntity>>>,
+    versions: Arc<RwLock<HashMap<EntityKey, Vec<PersistedEntity>>>>,
+    retention: usize,
     futures: Arc<RwLock<HashMap<String, PersistedFuture>>>,
+    dead: Arc<RwLock<HashMap<String, DeadFuture>>>,
+    journal: Arc<RwLock<Vec<JournalEntry>>>,
+    mailbox: Arc<RwLock<Vec<PersistedMail>>>,
+    sequence: Arc<AtomicU64>,
+    limits: MailboxLimits,
     pending: RwLock<Vec<Pending>>,
 }
 
@@ -118,7 +329,7 @@ impl FutureStorage for InMemoryStorage {
     }
 
     fn query_futures_before(&self, now: DateTime<Utc>) -> Result<PendingFutures> {
-        let mut futures = self.futures.write().expect("Lock error");
+        let futures = self.futures.read().expect("Lock error");
         let mut pending = Vec::new();
 
         for (_k, future) in futures.iter() {
@@ -127,16 +338,124 @@ impl FutureStorage for InMemoryStorage {
             }
         }
 
-        for future in pending.iter() {
-            futures.remove(&future.key);
-        }
-
         if pending.is_empty() {
             Ok(PendingFutures::Waiting(None))
         } else {
             Ok(PendingFutures::Futures(pending))
         }
     }
+
+    fn list_futures(&self, entity: &EntityKey) -> Result<Vec<PersistedFuture>> {
+        let futures = self.futures.read().expect("Lock error");
+        let mut listed = futures
+            .values()
+            .filter(|f| f.entity == *entity)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        listed.sort_by(|a, b| a.time.cmp(&b.time).then_with(|| a.key.cmp(&b.key)));
+
+        Ok(listed)
+    }
+
+    fn get_future(&self, key: &str) -> Result<Option<PersistedFuture>> {
+        let futures = self.futures.read().expect("Lock error");
+
+        Ok(futures.get(key).cloned())
+    }
+
+    fn reschedule(&self, key: &str, time: DateTime<Utc>) -> Result<()> {
+        let mut futures = self.futures.write().expect("Lock error");
+        if let Some(future) = futures.get_mut(key) {
+            future.time = time;
+        }
+
+        Ok(())
+    }
+
+    fn dead_letter(
+        &self,
+        future: &PersistedFuture,
+        error: &str,
+        failed: DateTime<Utc>,
+    ) -> Result<()> {
+        let mut dead = self.dead.write().expect("Lock error");
+        dead.insert(
+            future.key.clone(),
+            DeadFuture {
+                future: future.clone(),
+                error: error.to_owned(),
+                failed,
+            },
+        );
+
+        Ok(())
+    }
+
+    fn query_dead_letters(&self) -> Result<Vec<DeadFuture>> {
+        let dead = self.dead.read().expect("Lock error");
+
+        Ok(dead.values().cloned().collect())
+    }
+}
+
+impl JournalStorage for InMemoryStorage {
+    fn append(&self, entry: &JournalEntry) -> Result<()> {
+        let mut pending = self.pending.write().expect("Lock error");
+        pending.push(Pending::Append(entry.clone()));
+
+        Ok(())
+    }
+
+    fn query_journal(&self) -> Result<Vec<JournalEntry>> {
+        let journal = self.journal.read().expect("Lock error");
+
+        Ok(journal.clone())
+    }
+}
+
+impl MailboxStorage for InMemoryStorage {
+    fn post(&self, audience: &EntityKey, time: DateTime<Utc>, serialized: &str) -> Result<u64> {
+        let sequence = self.sequence.fetch_add(1, Ordering::Relaxed) + 1;
+        let mut pending = self.pending.write().expect("Lock error");
+        pending.push(Pending::Post(PersistedMail {
+            sequence,
+            audience: audience.clone(),
+            time,
+            serialized: serialized.to_owned(),
+        }));
+
+        Ok(sequence)
+    }
+
+    fn query_mailbox(
+        &self,
+        audience: &EntityKey,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<PersistedMail>> {
+        let mailbox = self.mailbox.read().expect("Lock error");
+
+        Ok(mailbox
+            .iter()
+            .filter(|m| m.audience == *audience && m.time >= now - self.limits.ttl)
+            .cloned()
+            .collect())
+    }
+
+    fn acknowledge(&self, audience: &EntityKey, sequence: u64) -> Result<()> {
+        let mut mailbox = self.mailbox.write().expect("Lock error");
+        mailbox.retain(|m| m.audience != *audience || m.sequence > sequence);
+
+        Ok(())
+    }
+}
+
+impl EntityVersionStorage for InMemoryStorage {
+    fn query_versions(&self, key: &EntityKey) -> Result<Vec<PersistedEntity>> {
+        let versions = self.versions.read().expect("Lock error");
+
+        Ok(versions.get(key).cloned().unwrap_or_default())
+    }
 }
 
 impl EntityStorage for InMemoryStorage {
@@ -185,11 +504,63 @@ impl EntityStorage for InMemoryStorage {
     fn commit(&self) -> Result<()> {
         let mut pending = self.pending.write().expect("Lock error");
         let mut entities = self.entities.write().expect("Lock error");
+        let mut versions = self.versions.write().expect("Lock error");
+        let mut journal = self.journal.write().expect("Lock error");
+        let mut mailbox = self.mailbox.write().expect("Lock error");
+
+        for pending in pending.iter() {
+            if let Pending::Save(e) = pending {
+                let stored = entities.get(&EntityKey::new(&e.key)).map(|s| s.version);
+                let expected = (e.version > 1).then_some(e.version - 1);
+                if stored != expected {
+                    return Err(ConflictError {
+                        key: e.key.clone(),
+                        version: e.version,
+                    }
+                    .into());
+                }
+            }
+        }
 
         for pending in pending.iter() {
             match pending {
-                Pending::Save(e) => entities.insert(EntityKey::new(&e.key), e.clone()),
+                Pending::Save(e) => {
+                    let key = EntityKey::new(&e.key);
+                    let replaced = entities.insert(key.clone(), e.clone());
+                    if let Some(replaced) = replaced.filter(|_| self.retention > 0) {
+                        let kept = versions.entry(key).or_default();
+                        kept.insert(0, replaced);
+                        kept.truncate(self.retention);
+                    }
+                    None
+                }
                 Pending::Delete(e) => entities.remove(&EntityKey::new(&e.key)),
+                Pending::Append(entry) => {
+                    journal.push(entry.clone());
+                    None
+                }
+                Pending::Post(mail) => {
+                    let expired = mail.time - self.limits.ttl;
+                    let kept = mailbox
+                        .iter()
+                        .filter(|m| m.audience == mail.audience)
+                        .count();
+                    let mut excess = (kept + 1).saturating_sub(self.limits.capacity);
+                    mailbox.retain(|m| {
+                        if m.audience != mail.audience {
+                            return true;
+                        }
+                        if excess > 0 {
+                            excess -= 1;
+                            return false;
+                        }
+                        m.time >= expired
+                    });
+                    if self.limits.capacity > 0 {
+                        mailbox.push(mail.clone());
+                    }
+                    None
+                }
             };
         }
 
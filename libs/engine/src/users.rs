@@ -1,9 +1,12 @@
 pub mod model {
     use anyhow::Result;
     use serde::{Deserialize, Serialize};
-    use std::collections::HashMap;
+    use std::collections::{BTreeSet, HashMap};
 
-    use kernel::prelude::{DomainError, EntityKey, EntityPtr, OpenScope, OpenScopeRefMut, Scope};
+    use burrow_bon::prelude::{Perm, Subject};
+    use kernel::prelude::{
+        Acls, DomainError, EntityKey, EntityPtr, OpenScope, OpenScopeRefMut, Role, Scope,
+    };
 
     #[derive(Debug, Serialize, Deserialize, Default)]
     pub struct Usernames {
@@ -18,6 +21,10 @@ pub mod model {
         pub fn set(&mut self, name: &str, key: &EntityKey) {
             self.users.insert(name.to_owned(), key.clone());
         }
+
+        pub fn is_user(&self, key: &EntityKey) -> bool {
+            self.users.values().any(|k| k == key)
+        }
     }
 
     impl Scope for Usernames {
@@ -61,11 +68,28 @@ pub mod model {
 
     const WEB: &str = "web";
 
-    #[derive(Debug, Serialize, Deserialize, Default)]
+    /// Credentials are only readable by the owner of the entity, even the
+    /// entity itself can't see their own password hashes.
+    fn owner_only() -> Acls {
+        Acls::from_iter([(Perm::Read, Subject::Owner)].into_iter())
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
     pub struct Credentials {
+        #[serde(default = "owner_only")]
+        acls: Acls,
         passwords: HashMap<String, String>,
     }
 
+    impl Default for Credentials {
+        fn default() -> Self {
+            Self {
+                acls: owner_only(),
+                passwords: Default::default(),
+            }
+        }
+    }
+
     impl Credentials {
         pub fn get(&self) -> Option<&String> {
             self.passwords.get(WEB)
@@ -82,6 +106,71 @@ pub mod model {
         }
     }
 
+    /// Roles granted to a user, users without any are players.
+    #[derive(Debug, Serialize, Deserialize, Default)]
+    pub struct Roles {
+        granted: BTreeSet<Role>,
+    }
+
+    impl Roles {
+        pub fn has(&self, role: Role) -> bool {
+            role == Role::default() || self.granted.iter().any(|granted| *granted >= role)
+        }
+
+        pub fn grant(&mut self, role: Role) -> bool {
+            self.granted.insert(role)
+        }
+
+        pub fn revoke(&mut self, role: Role) -> bool {
+            self.granted.remove(&role)
+        }
+
+        pub fn granted(&self) -> impl Iterator<Item = &Role> {
+            self.granted.iter()
+        }
+    }
+
+    impl Scope for Roles {
+        fn scope_key() -> &'static str {
+            "roles"
+        }
+    }
+
+    pub trait HasRoles {
+        fn has_role(&self, role: Role) -> Result<bool, DomainError>;
+
+        /// Returns false if the role had already been granted.
+        fn grant_role(&self, role: Role) -> Result<bool, DomainError>;
+
+        /// Returns false if the role hadn't been granted.
+        fn revoke_role(&self, role: Role) -> Result<bool, DomainError>;
+    }
+
+    impl HasRoles for EntityPtr {
+        fn has_role(&self, role: Role) -> Result<bool, DomainError> {
+            Ok(self
+                .scope::<Roles>()?
+                .map(|roles| roles.has(role))
+                .unwrap_or(role == Role::default()))
+        }
+
+        fn grant_role(&self, role: Role) -> Result<bool, DomainError> {
+            let mut roles = self.scope_mut::<Roles>()?;
+            let granted = roles.grant(role);
+            roles.save()?;
+
+            Ok(granted)
+        }
+
+        fn revoke_role(&self, role: Role) -> Result<bool, DomainError> {
+            let mut roles = self.scope_mut::<Roles>()?;
+            let revoked = roles.revoke(role);
+            roles.save()?;
+
+            Ok(revoked)
+        }
+    }
+
     const LIMBO: &str = "limbo";
     const ENCYCLOPEDIA: &str = "encyclopedia";
     const WELCOME_AREA: &str = "welcomeArea";
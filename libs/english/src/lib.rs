@@ -7,7 +7,6 @@ pub use nom::{
     sequence::{delimited, pair, preceded, separated_pair, terminated, tuple},
     IResult,
 };
-use nom::{bytes::complete::tag_no_case, combinator::eof};
 pub use tracing::*;
 
 #[cfg(test)]
@@ -110,59 +109,75 @@ fn literal(i: &str) -> IResult<&str, English> {
     map(uppercase_word, |v| English::Literal(v.into()))(i)
 }
 
-fn word(i: &str) -> IResult<&str, &str> {
-    take_while1(move |c| "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ".contains(c))(i)
-}
-
-fn node_to_parser<'a>(node: &'a English) -> Box<dyn FnMut(&'a str) -> IResult<&'a str, Node> + 'a> {
-    match node {
-        English::Literal(v) => Box::new(map(tag_no_case::<&str, &str, _>(v), |_| Node::Ignored)),
-        English::Phrase(_) => todo!(),
-        English::OneOf(_) => todo!(),
-        English::Optional(_) => todo!(),
-        English::Unheld => Box::new(map(word, |w| Node::Unheld(w.into()))),
-        English::Held => Box::new(map(word, |w| Node::Held(w.into()))),
-        English::Contained => Box::new(map(word, |w| Node::Contained(w.into()))),
-        English::Numbered(_) => todo!(),
-        English::Text => todo!(),
-    }
-}
+/// Continues matching the remaining tokens, given the captures so far.
+type Continue<'c> = &'c dyn Fn(&[&str], Vec<Node>) -> Option<Vec<Node>>;
 
-fn nodes_to_parser<'a>(nodes: &'a [English]) -> impl FnMut(&'a str) -> IResult<&'a str, Node> {
-    move |mut i: &'a str| {
-        // TODO Would love to move this up and out of the closure.
-        let terms = nodes.iter().map(node_to_parser).collect::<Vec<_>>();
+fn match_terms(
+    terms: &[English],
+    tokens: &[&str],
+    nodes: Vec<Node>,
+    k: Continue,
+) -> Option<Vec<Node>> {
+    let Some((term, remaining)) = terms.split_first() else {
+        return k(tokens, nodes);
+    };
 
-        let mut accumulator: Vec<Node> = vec![];
-        for mut term in terms {
-            let (r, term_node) = term(i)?;
-            let (r, _) = alt((spaces, eof))(r)?;
-            i = r;
+    match_term(term, tokens, nodes, &|tokens, nodes| {
+        match_terms(remaining, tokens, nodes, k)
+    })
+}
 
-            match term_node {
-                Node::Ignored => {}
-                _ => accumulator.push(term_node),
-            }
+fn match_term(term: &English, tokens: &[&str], nodes: Vec<Node>, k: Continue) -> Option<Vec<Node>> {
+    match term {
+        English::Literal(v) => match tokens.split_first() {
+            Some((token, rest)) if token.eq_ignore_ascii_case(v) => k(rest, nodes),
+            _ => None,
+        },
+        English::Numbered(n) => match tokens.split_first() {
+            Some((token, rest)) if *token == format!("#{}", n) => k(rest, nodes),
+            _ => None,
+        },
+        English::Phrase(terms) => match_terms(terms, tokens, nodes, k),
+        English::OneOf(alternatives) => alternatives
+            .iter()
+            .find_map(|a| match_term(a, tokens, nodes.clone(), k)),
+        English::Optional(term) => {
+            match_term(term, tokens, nodes.clone(), k).or_else(|| k(tokens, nodes))
         }
-
-        Ok((i, Node::Phrase(accumulator)))
+        // Captures take as few words as they can while still allowing the
+        // rest of the phrase to match, so "PUT #held IN #held" captures
+        // "brass key" from "put brass key in chest".
+        English::Unheld | English::Held | English::Contained | English::Text => (1..=tokens.len())
+            .find_map(|n| {
+                let captured = tokens[..n].join(" ");
+                let mut nodes = nodes.clone();
+                nodes.push(match term {
+                    English::Unheld => Node::Unheld(captured),
+                    English::Held => Node::Held(captured),
+                    English::Contained => Node::Contained(captured),
+                    _ => Node::Text(captured),
+                });
+                k(&tokens[n..], nodes)
+            }),
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Node {
-    Ignored,
     Held(String),
     Unheld(String),
     Contained(String),
+    Text(String),
     Phrase(Vec<Node>),
 }
 
 pub fn try_parse(english: &[English], text: &str) -> Option<Node> {
-    match nodes_to_parser(english)(text) {
-        Ok((_, node)) => Some(node),
-        Err(_) => None,
-    }
+    let tokens = text.split_whitespace().collect::<Vec<_>>();
+
+    match_terms(english, &tokens, Vec::new(), &|rest, nodes| {
+        rest.is_empty().then_some(nodes)
+    })
+    .map(Node::Phrase)
 }
 
 pub fn to_tongue(text: &str) -> Option<Vec<English>> {
@@ -107,3 +107,36 @@ fn should_parse_all_english_fixtures() {
         assert_eq!(actual, fixture.expected);
     }
 }
+
+#[test]
+fn should_capture_phrases_between_literals() {
+    let english = to_tongue("PUT #held (INSIDE (OF)?|IN) #unheld").unwrap();
+
+    assert_eq!(
+        try_parse(&english, "put brass key inside of chest"),
+        Some(Node::Phrase(vec![
+            Node::Held("brass key".into()),
+            Node::Unheld("chest".into())
+        ]))
+    );
+    assert_eq!(
+        try_parse(&english, "PUT key in chest"),
+        Some(Node::Phrase(vec![
+            Node::Held("key".into()),
+            Node::Unheld("chest".into())
+        ]))
+    );
+    assert_eq!(try_parse(&english, "put key"), None);
+    assert_eq!(try_parse(&english, "hold key"), None);
+}
+
+#[test]
+fn should_skip_missing_optional_captures() {
+    let english = to_tongue("DROP (#held)?").unwrap();
+
+    assert_eq!(try_parse(&english, "drop"), Some(Node::Phrase(vec![])));
+    assert_eq!(
+        try_parse(&english, "drop 2 coins"),
+        Some(Node::Phrase(vec![Node::Held("2 coins".into())]))
+    );
+}
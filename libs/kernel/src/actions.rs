@@ -127,6 +127,13 @@ pub enum Perform {
         surroundings: Surroundings,
         action: PerformAction,
     },
+    World {
+        action: PerformAction,
+    },
+    Area {
+        area: EntityPtr,
+        action: PerformAction,
+    },
     Delivery(Incoming),
     Raised(Raised),
     Schedule(FutureAction),
@@ -143,6 +150,8 @@ impl Perform {
                 surroundings: _,
                 action: _,
             } => "Surroundings",
+            Perform::World { action: _ } => "World",
+            Perform::Area { area: _, action: _ } => "Area",
             Perform::Delivery(_) => "Delivery",
             Perform::Raised(_) => "Raised",
             Perform::Schedule(_) => "Schedule",
@@ -197,8 +206,9 @@ impl From<EffectReply> for Effect {
 }
 
 use replies::{
-    AreaObservation, EditorReply, EntityObservation, InsideObservation, MarkdownReply, Reply,
-    SimpleReply,
+    AmbiguousReply, AreaObservation, DeniedReply, EditorReply, EntityObservation, FuturesReply,
+    HistoryReply, InsideObservation, InvitationsReply, MarkdownReply, ModerationReply, Reply,
+    SimpleReply, SuggestionsReply,
 };
 
 impl TryFrom<EntityObservation> for Effect {
@@ -241,6 +251,62 @@ impl TryFrom<EditorReply> for Effect {
     }
 }
 
+impl TryFrom<AmbiguousReply> for Effect {
+    type Error = TaggedJsonError;
+
+    fn try_from(value: AmbiguousReply) -> std::result::Result<Self, Self::Error> {
+        Ok(Self::Reply(value.to_tagged_json()?.into()))
+    }
+}
+
+impl TryFrom<SuggestionsReply> for Effect {
+    type Error = TaggedJsonError;
+
+    fn try_from(value: SuggestionsReply) -> std::result::Result<Self, Self::Error> {
+        Ok(Self::Reply(value.to_tagged_json()?.into()))
+    }
+}
+
+impl TryFrom<FuturesReply> for Effect {
+    type Error = TaggedJsonError;
+
+    fn try_from(value: FuturesReply) -> std::result::Result<Self, Self::Error> {
+        Ok(Self::Reply(value.to_tagged_json()?.into()))
+    }
+}
+
+impl TryFrom<HistoryReply> for Effect {
+    type Error = TaggedJsonError;
+
+    fn try_from(value: HistoryReply) -> std::result::Result<Self, Self::Error> {
+        Ok(Self::Reply(value.to_tagged_json()?.into()))
+    }
+}
+
+impl TryFrom<ModerationReply> for Effect {
+    type Error = TaggedJsonError;
+
+    fn try_from(value: ModerationReply) -> std::result::Result<Self, Self::Error> {
+        Ok(Self::Reply(value.to_tagged_json()?.into()))
+    }
+}
+
+impl TryFrom<InvitationsReply> for Effect {
+    type Error = TaggedJsonError;
+
+    fn try_from(value: InvitationsReply) -> std::result::Result<Self, Self::Error> {
+        Ok(Self::Reply(value.to_tagged_json()?.into()))
+    }
+}
+
+impl TryFrom<DeniedReply> for Effect {
+    type Error = TaggedJsonError;
+
+    fn try_from(value: DeniedReply) -> std::result::Result<Self, Self::Error> {
+        Ok(Self::Reply(value.to_tagged_json()?.into()))
+    }
+}
+
 impl TryFrom<SimpleReply> for Effect {
     type Error = TaggedJsonError;
 
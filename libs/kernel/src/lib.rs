@@ -28,7 +28,7 @@ pub mod prelude {
 }
 
 mod finder {
-    use crate::model::{Audience, DomainError, EntityKey, EntityPtr, Found, Item};
+    use crate::model::{Audience, DomainError, EntityPtr, Found, Item, Listener};
     use crate::surround::Surroundings;
 
     pub trait Finder: Send + Sync {
@@ -42,7 +42,11 @@ mod finder {
             item: &Item,
         ) -> Result<Option<Found>, DomainError>;
 
-        fn find_audience(&self, audience: &Audience) -> Result<Vec<EntityKey>, DomainError>;
+        fn find_audience(&self, audience: &Audience) -> Result<Vec<Listener>, DomainError>;
+
+        /// Entities near enough to the surroundings that they may be referred
+        /// to, not including the actor, the area or the world.
+        fn find_nearby(&self, surroundings: &Surroundings) -> Result<Vec<EntityPtr>, DomainError>;
     }
 }
 
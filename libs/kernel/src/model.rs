@@ -1,4 +1,5 @@
 use anyhow::Result;
+use burrow_bon::prelude::{DottedPaths, SecurityContext};
 use serde::Serialize;
 use std::{
     cell::RefCell,
@@ -183,3 +184,65 @@ pub fn any_entity_changes(
         after: value_after,
     })
 }
+
+/// Paths that differ between two serialized forms of an entity.
+pub fn entity_json_differences(
+    before: &JsonValue,
+    after: &JsonValue,
+) -> Result<Vec<String>, CompareError> {
+    use burrow_bon::prelude::TreeDiff;
+
+    let diff = TreeDiff {};
+
+    Ok(diff
+        .any_changes(AnyChanges {
+            before: before.clone(),
+            after: after.clone(),
+        })?
+        .map(|modified| modified.paths.into())
+        .unwrap_or_default())
+}
+
+/// Security context for an actor against the serialized form of an entity.
+/// When the owner or creator is missing the other takes its place, and if
+/// both are missing then only rules for everybody or principals can apply.
+pub fn entity_security_context(actor: &EntityKey, value: &JsonValue) -> SecurityContext<EntityKey> {
+    let lookup = |pointer: &str| {
+        value
+            .pointer(pointer)
+            .and_then(|v| v.as_str())
+            .map(EntityKey::new)
+    };
+
+    let (owner, creator) = match (lookup("/owner/value/key"), lookup("/creator/key")) {
+        (None, None) => (EntityKey::blank(), EntityKey::blank()),
+        (None, Some(creator)) => (creator.clone(), creator),
+        (Some(owner), None) => (owner.clone(), owner),
+        (Some(owner), Some(creator)) => (owner, creator),
+    };
+
+    SecurityContext::new(actor.clone(), owner, creator)
+}
+
+/// Removes anything the actor is unable to read from the serialized form of
+/// an entity, returning the paths that were removed.
+pub fn redact_entity_json(actor: &EntityKey, value: &mut JsonValue) -> DottedPaths {
+    let context = entity_security_context(actor, value);
+
+    burrow_bon::prelude::redact(value, context)
+}
+
+/// Copies anything the actor is unable to read from `existing` into `value`,
+/// for saving values that were redacted before being sent to the actor.
+pub fn restore_redacted_entity_json(
+    actor: &EntityKey,
+    existing: &JsonValue,
+    value: &mut JsonValue,
+) {
+    let mut redacted = existing.clone();
+    for path in redact_entity_json(actor, &mut redacted).into_iter() {
+        if let Some(restoring) = path.lookup(existing) {
+            path.replace(value, restoring.clone());
+        }
+    }
+}
@@ -7,7 +7,7 @@ use std::{
 use thiserror::Error;
 use tracing::*;
 
-pub use burrow_bon::prelude::Acls;
+pub use burrow_bon::prelude::{Acls, DottedPath};
 pub use replies::JsonValue;
 
 use super::EntityPtr;
@@ -119,6 +119,36 @@ pub enum Audience {
     Everybody,
     Individuals(Vec<EntityKey>),
     Area(EntityKey),
+    /// Those in the area and in areas up to depth routes away from it.
+    Nearby {
+        area: EntityKey,
+        depth: u32,
+    },
+}
+
+/// Somebody in the audience of an event, along with how far away from it
+/// they were when they weren't right there.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Listener {
+    pub key: EntityKey,
+    pub distance: Option<Distance>,
+}
+
+impl Listener {
+    pub fn here(key: EntityKey) -> Self {
+        Self {
+            key,
+            distance: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Distance {
+    /// Number of routes between the listener and the event.
+    pub areas: u32,
+    /// Name of the route the listener would take towards the event.
+    pub direction: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -183,12 +213,20 @@ pub enum Item {
     Contained(Box<Item>),
     Quantified(Quantity, Box<Item>),
     Held(Box<Item>),
+    /// The nth (starting at 1) of several entities matching the item, as in
+    /// "the second key".
+    Ordinal(usize, Box<Item>),
+    /// Whatever the actor last referred to, "it" or "them".
+    Pronoun,
+    /// Everything, as in "drop all".
+    All,
 }
 
 #[derive(Debug, Clone)]
 pub enum Found {
     One(EntityPtr),
     Quantified(Quantity, EntityPtr),
+    Many(Vec<EntityPtr>),
 }
 
 impl From<EntityPtr> for Found {
@@ -198,16 +236,48 @@ impl From<EntityPtr> for Found {
 }
 
 impl Found {
+    /// The entity that was found, for quantified results this is the stack
+    /// the quantity is to be taken from.
     pub fn one(self) -> Result<EntityPtr, DomainError> {
         match self {
-            Found::One(one) => Ok(one),
-            Found::Quantified(_, _) => todo!(),
+            Found::One(one) | Found::Quantified(_, one) => Ok(one),
+            Found::Many(mut many) => match many.len() {
+                1 => Ok(many.remove(0)),
+                _ => Err(DomainError::MultipleFound),
+            },
+        }
+    }
+
+    pub fn quantity(&self) -> Quantity {
+        match self {
+            Found::One(_) | Found::Many(_) => 1.into(),
+            Found::Quantified(q, _) => q.clone(),
         }
     }
 
     pub fn entity(&self) -> Result<&EntityPtr, DomainError> {
         match self {
             Found::One(e) | Found::Quantified(_, e) => Ok(e),
+            Found::Many(many) => match many.as_slice() {
+                [e] => Ok(e),
+                _ => Err(DomainError::MultipleFound),
+            },
+        }
+    }
+
+    /// Splits this into individual finds, for actions that can be done to
+    /// several entities at once.
+    pub fn many(self) -> Vec<Found> {
+        match self {
+            Found::Many(many) => many.into_iter().map(Found::One).collect(),
+            found => vec![found],
+        }
+    }
+
+    pub fn entities(&self) -> Vec<EntityPtr> {
+        match self {
+            Found::One(e) | Found::Quantified(_, e) => vec![e.clone()],
+            Found::Many(many) => many.clone(),
         }
     }
 }
@@ -216,10 +286,7 @@ impl TryInto<EntityPtr> for Found {
     type Error = anyhow::Error;
 
     fn try_into(self) -> Result<EntityPtr, Self::Error> {
-        match self {
-            Found::One(one) => Ok(one),
-            Found::Quantified(_, _) => todo!(),
-        }
+        Ok(self.one()?)
     }
 }
 
@@ -328,6 +395,14 @@ pub enum DomainError {
     SessionClosed,
     #[error("Container required")]
     ContainerRequired,
+    #[error("Actor required")]
+    ActorRequired,
+    #[error("Area required")]
+    AreaRequired,
+    #[error("Multiple entities found")]
+    MultipleFound,
+    #[error("Ambiguous")]
+    Ambiguous(Vec<EntityKey>),
     #[error("Entity not found")]
     EntityNotFound(ErrorContext),
     #[error("Impossible")]
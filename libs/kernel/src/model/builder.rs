@@ -75,7 +75,10 @@ impl EntityBuilder {
     }
 
     pub fn copying(mut self, template: &Entity) -> Result<Self> {
-        let scopes: ScopeMap = template.scopes.clone().into();
+        let scopes = ScopeMap {
+            scopes: template.scopes.clone(),
+            versions: template.versions.clone(),
+        };
         let properties = scopes.scope::<Properties>()?.unwrap_or_default();
         let mut props = properties.props();
         props.remove_property(GID_PROPERTY);
@@ -88,6 +91,14 @@ impl EntityBuilder {
         Ok(self)
     }
 
+    /// Build an instance of a prototype, anything the instance doesn't
+    /// override is inherited from the prototype.
+    pub fn instance_of(mut self, prototype: &Entity) -> Self {
+        self.class = prototype.class.clone();
+        self.parent = Some(prototype.entity_ref());
+        self
+    }
+
     pub fn area(self) -> Self {
         self.class(EntityClass::area())
     }
@@ -141,10 +152,9 @@ impl TryInto<Entity> for EntityBuilder {
         .into_iter()
         .collect::<HashMap<_, _>>();
 
-        let mut map: HashMap<_, _> = self.scopes.clone().map(|v| v.into()).unwrap_or_default();
-        map.extend(props);
+        let mut scopes = self.scopes.clone().unwrap_or_default();
+        scopes.scopes.extend(props);
 
-        let scopes: ScopeMap = map.into();
         let owner = self.owner.or_else(|| self.creator.clone());
         Ok(Entity::new_heavily_customized(
             key,
@@ -3,8 +3,14 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::str::FromStr;
 
-use super::base::{Acls, DomainError, EntityClass, EntityKey, Identity, JsonValue};
-use super::{EntityRef, LoadAndStoreScope, ScopeMap, ScopeValue, StoreScope};
+use super::base::{
+    Acls, DomainError, EntityClass, EntityKey, Identity, JsonValue, DESTROYED_PROPERTY,
+    GID_PROPERTY,
+};
+use super::{
+    keep_scope_version, local_overrides, merge_inherited, EntityRef, LoadAndStoreScope, Scope,
+    ScopeMap, ScopeUpgrades, ScopeValue, StoreScope,
+};
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct AddAcls<T> {
@@ -27,6 +33,10 @@ impl<T> From<T> for AddAcls<T> {
 /// outside of the Entity itself.  The only other thing that could change is
 /// possibly `acls, only that's probably infrequent.  As a rule going forward,
 /// these should be considered immutable.
+///
+/// An entity's `parent` is its prototype. Inherited scopes the entity doesn't
+/// override locally are resolved through the prototype, which the session
+/// supplies via `set_prototype` as entities are loaded.
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Entity {
     key: EntityKey,
@@ -37,6 +47,14 @@ pub struct Entity {
     pub(super) owner: Option<AddAcls<EntityRef>>,
     pub(super) parent: Option<EntityRef>,
     pub(super) scopes: HashMap<String, ScopeValue>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub(super) versions: HashMap<String, u32>,
+    #[serde(skip)]
+    inherited: HashMap<String, JsonValue>,
+    #[serde(skip)]
+    inherited_versions: HashMap<String, u32>,
+    #[serde(skip)]
+    resolved: HashMap<String, JsonValue>,
 }
 
 impl Entity {
@@ -80,10 +98,67 @@ impl Entity {
             creator,
             owner: owner.map(|v| v.into()),
             parent,
-            scopes: scopes.into(),
+            scopes: scopes.scopes,
+            versions: scopes.versions,
+            inherited: Default::default(),
+            inherited_versions: Default::default(),
+            resolved: Default::default(),
         }
     }
 
+    pub fn parent(&self) -> Option<&EntityRef> {
+        self.parent.as_ref()
+    }
+
+    /// Provide the scopes inherited from this entity's prototype, along with
+    /// the versions they were serialized by. Only scopes registered as
+    /// inherited are taken from the prototype.
+    pub fn set_prototype(&mut self, prototype: &Entity, scopes: &ScopeUpgrades) {
+        let inherited: HashMap<String, JsonValue> = prototype
+            .resolved_scopes()
+            .into_iter()
+            .filter(|(key, _)| scopes.is_inherited(key))
+            .collect();
+        self.resolved = inherited
+            .iter()
+            .map(|(key, value)| {
+                let value = match self.scopes.get(key) {
+                    Some(local) => merge_inherited(value, local.json_value()),
+                    None => value.clone(),
+                };
+                (key.clone(), value)
+            })
+            .collect();
+        self.inherited = inherited;
+        self.inherited_versions = prototype
+            .inherited_versions
+            .iter()
+            .chain(prototype.versions.iter())
+            .filter(|(key, _)| scopes.is_inherited(key))
+            .map(|(key, version)| (key.clone(), *version))
+            .collect();
+    }
+
+    /// Every scope of this entity with inherited values merged in, less the
+    /// properties that only ever apply to this entity.
+    pub fn resolved_scopes(&self) -> HashMap<String, JsonValue> {
+        let mut scopes: HashMap<String, JsonValue> = self
+            .scopes
+            .iter()
+            .map(|(key, value)| (key.clone(), value.json_value().clone()))
+            .collect();
+        scopes.extend(self.resolved.clone());
+
+        if let Some(JsonValue::Object(core)) =
+            scopes.get_mut("props").and_then(|p| p.get_mut("core"))
+        {
+            core.remove(GID_PROPERTY);
+            core.remove(DESTROYED_PROPERTY);
+        }
+
+        scopes
+    }
+
     pub fn key(&self) -> &EntityKey {
         &self.key
     }
@@ -96,6 +171,46 @@ impl Entity {
         Ok(serde_json::to_value(self)?)
     }
 
+    /// Serialize, leaving out anything the actor isn't permitted to read.
+    pub fn to_json_value_for(&self, actor: &EntityKey) -> Result<JsonValue, DomainError> {
+        let mut value = self.to_json_value()?;
+        super::redact_entity_json(actor, &mut value);
+        Ok(value)
+    }
+
+    /// Serialize a single scope, leaving out anything the actor isn't
+    /// permitted to read. Only the scope and who the entity belongs to are
+    /// serialized, rather than the whole entity.
+    pub fn scope_json_for(
+        &self,
+        actor: &EntityKey,
+        scope_key: &str,
+    ) -> Result<Option<JsonValue>, DomainError> {
+        let Some(scope) = self
+            .resolved
+            .get(scope_key)
+            .or_else(|| self.load_scope(scope_key))
+        else {
+            return Ok(None);
+        };
+
+        let mut scopes = serde_json::Map::new();
+        scopes.insert(scope_key.to_owned(), scope.clone());
+
+        let mut value = serde_json::Map::new();
+        value.insert("acls".to_owned(), serde_json::to_value(&self.acls)?);
+        value.insert("owner".to_owned(), serde_json::to_value(&self.owner)?);
+        value.insert("creator".to_owned(), serde_json::to_value(&self.creator)?);
+        value.insert("scopes".to_owned(), JsonValue::Object(scopes));
+
+        let mut value = JsonValue::Object(value);
+        super::redact_entity_json(actor, &mut value);
+
+        Ok(value
+            .pointer_mut(&format!("/scopes/{}", scope_key))
+            .map(|scope| scope.take()))
+    }
+
     pub fn entity_ref(&self) -> EntityRef {
         EntityRef::new_from_entity(self, None)
     }
@@ -103,6 +218,11 @@ impl Entity {
 
 impl StoreScope for Entity {
     fn store_scope(&mut self, scope_key: &str, value: JsonValue) {
+        if let Some(inherited) = self.inherited.get(scope_key) {
+            self.resolved
+                .insert(scope_key.to_owned(), merge_inherited(inherited, &value));
+        }
+
         let previous = self.scopes.remove(scope_key);
         let value = ScopeValue::Intermediate {
             value: value.into(),
@@ -110,6 +230,22 @@ impl StoreScope for Entity {
         };
         self.scopes.insert(scope_key.to_owned(), value);
     }
+
+    fn store_scope_of<T: Scope>(&mut self, value: JsonValue) {
+        let scope_key = T::scope_key();
+        match self.inherited.get(scope_key) {
+            Some(inherited) if T::inherited() => {
+                let local = self.scopes.get(scope_key).map(|v| v.json_value());
+                let value = local_overrides(inherited, local, value);
+                self.store_scope(scope_key, value);
+            }
+            _ => self.store_scope(scope_key, value),
+        }
+    }
+
+    fn store_scope_version(&mut self, scope_key: &str, version: u32) {
+        keep_scope_version(&mut self.versions, scope_key, version);
+    }
 }
 
 impl LoadAndStoreScope for Entity {
@@ -118,8 +254,39 @@ impl LoadAndStoreScope for Entity {
     }
 
     fn remove_scope(&mut self, scope_key: &str) -> Option<ScopeValue> {
+        if let Some(inherited) = self.inherited.get(scope_key) {
+            self.resolved
+                .insert(scope_key.to_owned(), inherited.clone());
+        }
+
+        self.versions.remove(scope_key);
         self.scopes.remove(scope_key)
     }
+
+    fn load_scope_of<T: Scope>(&self) -> Option<&JsonValue> {
+        if T::inherited() {
+            if let Some(value) = self.resolved.get(T::scope_key()) {
+                return Some(value);
+            }
+        }
+
+        self.load_scope(T::scope_key())
+    }
+
+    fn load_scope_version(&self, scope_key: &str) -> u32 {
+        self.versions.get(scope_key).copied().unwrap_or_default()
+    }
+
+    fn load_scope_version_of<T: Scope>(&self) -> u32 {
+        let scope_key = T::scope_key();
+        if T::inherited() && !self.scopes.contains_key(scope_key) {
+            if let Some(version) = self.inherited_versions.get(scope_key) {
+                return *version;
+            }
+        }
+
+        self.load_scope_version(scope_key)
+    }
 }
 
 impl TryFrom<JsonValue> for Entity {
@@ -110,6 +110,10 @@ impl Scope for Properties {
     fn scope_key() -> &'static str {
         "props"
     }
+
+    fn inherited() -> bool {
+        true
+    }
 }
 
 pub trait HasProps<T> {
@@ -2,10 +2,10 @@ use anyhow::{Context, Result};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use tracing::*;
 
-use super::DomainError;
+use super::{DomainError, Entity, Properties};
 use crate::here;
 use replies::{Json, JsonValue};
 
@@ -13,6 +13,197 @@ pub trait Scope: Serialize + DeserializeOwned + Default + std::fmt::Debug {
     fn scope_key() -> &'static str
     where
         Self: Sized;
+
+    /// Schema version of the scope, bump this and upgrade from the previous
+    /// version in `upgrade_scope` when the serialized form changes.
+    fn scope_version() -> u32
+    where
+        Self: Sized,
+    {
+        0
+    }
+
+    /// Upgrade the scope's JSON from version `from` to the version after it.
+    /// Versions without an upgrade are left alone, which is all that's needed
+    /// when fields were only added with defaults.
+    fn upgrade_scope(_from: u32, value: JsonValue) -> Result<JsonValue>
+    where
+        Self: Sized,
+    {
+        Ok(value)
+    }
+
+    /// Whether entities inherit this scope from their prototype. Scopes that
+    /// describe what something is, rather than its per-instance state, like
+    /// where it is, opt in by returning true and are registered with the
+    /// plugin's `ScopeUpgrades`.
+    fn inherited() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+}
+
+type UpgradeFn = fn(&mut Entity) -> Result<bool, DomainError>;
+
+/// Versioned scopes registered by plugin factories, so every entity can be
+/// upgraded eagerly rather than as each scope is loaded. This is also how
+/// the scopes entities inherit from their prototypes are known before any
+/// of them are loaded.
+pub struct ScopeUpgrades {
+    scopes: HashMap<String, UpgradeFn>,
+    inherited: HashSet<String>,
+}
+
+impl Default for ScopeUpgrades {
+    fn default() -> Self {
+        let mut upgrades = Self {
+            scopes: Default::default(),
+            inherited: Default::default(),
+        };
+        upgrades.register::<Properties>();
+        upgrades
+    }
+}
+
+impl ScopeUpgrades {
+    pub fn register<T: Scope>(&mut self) {
+        self.scopes
+            .insert(T::scope_key().to_owned(), upgrade_stored_scope::<T>);
+        if T::inherited() {
+            self.inherited.insert(T::scope_key().to_owned());
+        }
+    }
+
+    pub fn is_inherited(&self, scope_key: &str) -> bool {
+        self.inherited.contains(scope_key)
+    }
+
+    /// Upgrade the entity's scopes that were stored by an older version,
+    /// returning the number of scopes that were changed.
+    pub fn upgrade(&self, entity: &mut Entity) -> Result<usize, DomainError> {
+        let mut changed = 0;
+        for upgrade in self.scopes.values() {
+            if upgrade(entity)? {
+                changed += 1;
+            }
+        }
+
+        Ok(changed)
+    }
+}
+
+fn upgrade_stored_scope<T: Scope>(entity: &mut Entity) -> Result<bool, DomainError> {
+    let version = entity.load_scope_version(T::scope_key());
+    if version >= T::scope_version() {
+        return Ok(false);
+    }
+
+    let Some(value) = entity.load_scope(T::scope_key()) else {
+        return Ok(false);
+    };
+
+    let value = upgrade_scope_json::<T>(version, value.clone()).context(here!())?;
+    entity.store_scope(T::scope_key(), value);
+    entity.store_scope_version(T::scope_key(), T::scope_version());
+
+    Ok(true)
+}
+
+fn upgrade_scope_json<T: Scope>(version: u32, mut value: JsonValue) -> Result<JsonValue> {
+    for from in version..T::scope_version() {
+        debug!(scope = T::scope_key(), from = from, "scope:upgrading");
+
+        value = T::upgrade_scope(from, value)?;
+    }
+
+    Ok(value)
+}
+
+fn deserialize_scope<T: Scope>(value: &JsonValue, version: u32) -> Result<T> {
+    if version < T::scope_version() {
+        Ok(serde_json::from_value(upgrade_scope_json::<T>(
+            version,
+            value.clone(),
+        )?)?)
+    } else {
+        Ok(serde_json::from_value(value.clone())?)
+    }
+}
+
+/// Store the scope's JSON along with the version it was serialized by.
+fn store_serialized<T: Scope, O: StoreScope + ?Sized>(
+    owner: &mut O,
+    value: &T,
+) -> Result<(), DomainError> {
+    owner.store_scope_of::<T>(serde_json::to_value(value)?);
+    owner.store_scope_version(T::scope_key(), T::scope_version());
+    Ok(())
+}
+
+/// Layer a scope's local JSON over the JSON inherited from a prototype, local
+/// fields win and objects are merged field by field.
+pub fn merge_inherited(inherited: &JsonValue, local: &JsonValue) -> JsonValue {
+    match (inherited, local) {
+        (JsonValue::Object(inherited), JsonValue::Object(local)) => {
+            let mut merged = inherited.clone();
+            for (key, value) in local.iter() {
+                let value = match inherited.get(key) {
+                    Some(inherited) => merge_inherited(inherited, value),
+                    None => value.clone(),
+                };
+                merged.insert(key.clone(), value);
+            }
+            JsonValue::Object(merged)
+        }
+        (_, local) => local.clone(),
+    }
+}
+
+/// The inverse of `merge_inherited`, reduces a scope's JSON to the fields
+/// that differ from what's inherited. Fields that are already overridden
+/// locally stay overridden, even if they're now equal to the inherited value.
+pub fn local_overrides(
+    inherited: &JsonValue,
+    local: Option<&JsonValue>,
+    value: JsonValue,
+) -> JsonValue {
+    fn overrides(
+        inherited: &JsonValue,
+        local: Option<&JsonValue>,
+        value: JsonValue,
+    ) -> Option<JsonValue> {
+        match (inherited, value) {
+            (JsonValue::Object(inherited), JsonValue::Object(value)) => {
+                let mut changed = serde_json::Map::new();
+                for (key, value) in value.into_iter() {
+                    let local = local.and_then(|l| l.get(&key));
+                    let value = match inherited.get(&key) {
+                        Some(inherited) => overrides(inherited, local, value),
+                        None => Some(value),
+                    };
+                    if let Some(value) = value {
+                        changed.insert(key, value);
+                    }
+                }
+                if changed.is_empty() && local.is_none() {
+                    None
+                } else {
+                    Some(JsonValue::Object(changed))
+                }
+            }
+            (inherited, value) => {
+                if local.is_some() || *inherited != value {
+                    Some(value)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    overrides(inherited, local, value).unwrap_or_else(|| JsonValue::Object(Default::default()))
 }
 
 #[derive(Clone, Deserialize)]
@@ -66,23 +257,55 @@ pub struct ModifiedScope {
     previous: Option<Json>,
 }
 
+/// Scopes by key, along with the versions of those serialized by a versioned
+/// scope. Only the scopes themselves are serialized.
 #[derive(Clone, Serialize, Deserialize, Default)]
-pub struct ScopeMap(HashMap<String, ScopeValue>);
+#[serde(transparent)]
+pub struct ScopeMap {
+    pub(super) scopes: HashMap<String, ScopeValue>,
+    #[serde(skip)]
+    pub(super) versions: HashMap<String, u32>,
+}
 
 impl From<HashMap<String, ScopeValue>> for ScopeMap {
-    fn from(value: HashMap<String, ScopeValue>) -> Self {
-        Self(value)
+    fn from(scopes: HashMap<String, ScopeValue>) -> Self {
+        Self {
+            scopes,
+            versions: Default::default(),
+        }
     }
 }
 
 impl From<ScopeMap> for HashMap<String, ScopeValue> {
     fn from(value: ScopeMap) -> Self {
-        value.0
+        value.scopes
+    }
+}
+
+/// Versions are only kept for scopes that have one, so unversioned scopes
+/// leave nothing behind.
+pub(super) fn keep_scope_version(
+    versions: &mut HashMap<String, u32>,
+    scope_key: &str,
+    version: u32,
+) {
+    if version == 0 {
+        versions.remove(scope_key);
+    } else {
+        versions.insert(scope_key.to_owned(), version);
     }
 }
 
 pub trait StoreScope {
     fn store_scope(&mut self, scope_key: &str, value: JsonValue);
+
+    fn store_scope_of<T: Scope>(&mut self, value: JsonValue) {
+        self.store_scope(T::scope_key(), value)
+    }
+
+    /// Remember the version a scope was serialized by, which is kept next to
+    /// the scope's JSON rather than inside it.
+    fn store_scope_version(&mut self, _scope_key: &str, _version: u32) {}
 }
 
 impl StoreScope for HashMap<String, ScopeValue> {
@@ -98,7 +321,11 @@ impl StoreScope for HashMap<String, ScopeValue> {
 
 impl StoreScope for ScopeMap {
     fn store_scope(&mut self, scope_key: &str, value: JsonValue) {
-        self.0.store_scope(scope_key, value);
+        self.scopes.store_scope(scope_key, value);
+    }
+
+    fn store_scope_version(&mut self, scope_key: &str, version: u32) {
+        keep_scope_version(&mut self.versions, scope_key, version);
     }
 }
 
@@ -106,18 +333,32 @@ pub trait LoadAndStoreScope: StoreScope {
     fn load_scope(&self, scope_key: &str) -> Option<&JsonValue>;
     fn remove_scope(&mut self, scope_key: &str) -> Option<ScopeValue>;
 
+    fn load_scope_of<T: Scope>(&self) -> Option<&JsonValue> {
+        self.load_scope(T::scope_key())
+    }
+
+    /// Version the scope was serialized by, scopes stored without a version
+    /// are from before the scope had one.
+    fn load_scope_version(&self, _scope_key: &str) -> u32 {
+        0
+    }
+
+    fn load_scope_version_of<T: Scope>(&self) -> u32 {
+        self.load_scope_version(T::scope_key())
+    }
+
     fn rename_scope(&mut self, old_key: &str, new_key: &str) {
+        let version = self.load_scope_version(old_key);
         if let Some(value) = self.remove_scope(old_key) {
             self.store_scope(new_key, value.json_value().clone());
+            self.store_scope_version(new_key, version);
         }
     }
     fn add_scope_by_key(&mut self, scope_key: &str) {
         self.store_scope(scope_key, JsonValue::Object(Default::default()));
     }
     fn replace_scope<T: Scope>(&mut self, value: &T) -> Result<(), DomainError> {
-        let json = serde_json::to_value(value)?;
-        self.store_scope(T::scope_key(), json);
-        Ok(())
+        store_serialized(self, value)
     }
 }
 
@@ -133,11 +374,16 @@ impl LoadAndStoreScope for HashMap<String, ScopeValue> {
 
 impl LoadAndStoreScope for ScopeMap {
     fn load_scope(&self, scope_key: &str) -> Option<&JsonValue> {
-        self.0.load_scope(scope_key)
+        self.scopes.load_scope(scope_key)
     }
 
     fn remove_scope(&mut self, scope_key: &str) -> Option<ScopeValue> {
-        self.0.remove_scope(scope_key)
+        self.versions.remove(scope_key);
+        self.scopes.remove_scope(scope_key)
+    }
+
+    fn load_scope_version(&self, scope_key: &str) -> u32 {
+        self.versions.get(scope_key).copied().unwrap_or_default()
     }
 }
 
@@ -150,12 +396,11 @@ where
     O: LoadAndStoreScope,
 {
     fn scope<T: Scope>(&self) -> Result<Option<OpenedScope<T>>, DomainError> {
-        let Some(value) = self.load_scope(T::scope_key()) else {
+        let Some(value) = self.load_scope_of::<T>() else {
             return Ok(None);
         };
 
-        let json = value.clone();
-        let value = serde_json::from_value(json).context(here!())?;
+        let value = deserialize_scope(value, self.load_scope_version_of::<T>()).context(here!())?;
 
         Ok(Some(OpenedScope::new(value)))
     }
@@ -180,8 +425,10 @@ where
     O: LoadAndStoreScope,
 {
     fn scope_mut<T: Scope + Serialize>(&self) -> Result<OpenedScopeMut<T>, DomainError> {
-        let value = match self.load_scope(T::scope_key()) {
-            Some(value) => serde_json::from_value(value.clone()).context(here!())?,
+        let value = match self.load_scope_of::<T>() {
+            Some(value) => {
+                deserialize_scope(value, self.load_scope_version_of::<T>()).context(here!())?
+            }
             None => T::default(),
         };
 
@@ -202,8 +449,10 @@ where
 {
     fn scope_mut<T: Scope + Serialize>(&self) -> Result<OpenedScopeRefMut<T, O>, DomainError> {
         let owner = self.borrow();
-        let value = match owner.load_scope(T::scope_key()) {
-            Some(value) => serde_json::from_value(value.clone()).context(here!())?,
+        let value = match owner.load_scope_of::<T>() {
+            Some(value) => {
+                deserialize_scope(value, owner.load_scope_version_of::<T>()).context(here!())?
+            }
             None => T::default(),
         };
 
@@ -266,8 +515,7 @@ impl<T: Scope + Serialize> OpenedScopeMut<T> {
     where
         O: StoreScope,
     {
-        entity.store_scope(T::scope_key(), serde_json::to_value(&self.target)?);
-        Ok(())
+        store_serialized(entity, &self.target)
     }
 }
 
@@ -307,10 +555,8 @@ where
     }
 
     pub fn save(&mut self) -> Result<(), DomainError> {
-        let value = serde_json::to_value(&self.target)?;
         let mut owner = self.owner.borrow_mut();
-        owner.store_scope(T::scope_key(), value);
-        Ok(())
+        store_serialized(&mut *owner, &self.target)
     }
 }
 
@@ -376,6 +622,133 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    pub fn test_upgrading_old_versions_on_load() -> Result<()> {
+        let mut w = Whatever::default();
+        w.store_scope("versioned", serde_json::json!({ "name": "A" }));
+
+        let read = w.scope::<VersionedScope>()?.unwrap();
+        assert_eq!(read.names, vec!["A"]);
+        assert_eq!(read.count, 1);
+
+        let mut scope = w.scope_mut::<VersionedScope>()?;
+        scope.names.push("B".to_owned());
+        scope.save(&mut w)?;
+
+        assert_eq!(
+            w.load_scope("versioned"),
+            Some(&serde_json::json!({ "names": ["A", "B"], "count": 1 }))
+        );
+        assert_eq!(w.load_scope_version("versioned"), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_loading_versions_without_upgrades() -> Result<()> {
+        let mut w = Whatever::default();
+        w.store_scope("tallies", serde_json::json!({ "apples": 3 }));
+
+        let mut scope = w.scope_mut::<TalliesScope>()?;
+        assert_eq!(scope.0.get("apples"), Some(&3));
+        scope.0.insert("pears".to_owned(), 2);
+        scope.save(&mut w)?;
+
+        assert_eq!(
+            w.load_scope("tallies"),
+            Some(&serde_json::json!({ "apples": 3, "pears": 2 }))
+        );
+        assert_eq!(w.load_scope_version("tallies"), 1);
+
+        let read = w.scope::<TalliesScope>()?.unwrap();
+        assert_eq!(read.0.len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_merging_inherited_scopes() {
+        let inherited =
+            serde_json::json!({ "name": "Sword", "stats": { "damage": 4, "weight": 2 } });
+        let local = serde_json::json!({ "stats": { "damage": 6 } });
+
+        assert_eq!(
+            merge_inherited(&inherited, &local),
+            serde_json::json!({ "name": "Sword", "stats": { "damage": 6, "weight": 2 } })
+        );
+    }
+
+    #[test]
+    pub fn test_local_overrides_only_keep_changes() {
+        let inherited =
+            serde_json::json!({ "name": "Sword", "stats": { "damage": 4, "weight": 2 } });
+        let value = serde_json::json!({ "name": "Sword", "stats": { "damage": 6, "weight": 2 } });
+
+        assert_eq!(
+            local_overrides(&inherited, None, value.clone()),
+            serde_json::json!({ "stats": { "damage": 6 } })
+        );
+
+        let local = serde_json::json!({ "name": "Sword" });
+        assert_eq!(
+            local_overrides(&inherited, Some(&local), value),
+            serde_json::json!({ "name": "Sword", "stats": { "damage": 6 } })
+        );
+    }
+
+    #[derive(Debug, Deserialize, Serialize, Default)]
+    pub struct VersionedScope {
+        names: Vec<String>,
+        count: u32,
+    }
+
+    impl Scope for VersionedScope {
+        fn scope_key() -> &'static str
+        where
+            Self: Sized,
+        {
+            "versioned"
+        }
+
+        fn scope_version() -> u32
+        where
+            Self: Sized,
+        {
+            2
+        }
+
+        fn upgrade_scope(from: u32, mut value: JsonValue) -> Result<JsonValue>
+        where
+            Self: Sized,
+        {
+            match from {
+                0 => value["names"] = serde_json::json!([value["name"].take()]),
+                _ => value["count"] = 1.into(),
+            }
+
+            Ok(value)
+        }
+    }
+
+    #[derive(Debug, Deserialize, Serialize, Default)]
+    pub struct TalliesScope(HashMap<String, u32>);
+
+    impl Scope for TalliesScope {
+        fn scope_key() -> &'static str
+        where
+            Self: Sized,
+        {
+            "tallies"
+        }
+
+        fn scope_version() -> u32
+        where
+            Self: Sized,
+        {
+            1
+        }
+    }
+
     #[derive(Debug, Deserialize, Serialize, Default)]
     pub struct ExampleScope {
         values: Vec<String>,
@@ -393,6 +766,7 @@ mod tests {
     #[derive(Default)]
     pub struct Whatever {
         scopes: HashMap<String, ScopeValue>,
+        versions: HashMap<String, u32>,
     }
 
     impl StoreScope for Whatever {
@@ -404,6 +778,10 @@ mod tests {
             };
             self.scopes.insert(scope_key.to_owned(), value);
         }
+
+        fn store_scope_version(&mut self, scope_key: &str, version: u32) {
+            keep_scope_version(&mut self.versions, scope_key, version);
+        }
     }
 
     impl LoadAndStoreScope for Whatever {
@@ -412,7 +790,12 @@ mod tests {
         }
 
         fn remove_scope(&mut self, scope_key: &str) -> Option<ScopeValue> {
+            self.versions.remove(scope_key);
             self.scopes.remove(scope_key)
         }
+
+        fn load_scope_version(&self, scope_key: &str) -> u32 {
+            self.versions.get(scope_key).copied().unwrap_or_default()
+        }
     }
 }
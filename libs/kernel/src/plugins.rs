@@ -1,7 +1,7 @@
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use replies::{TaggedJson, WorkingCopy};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::rc::Rc;
 use std::time::Instant;
@@ -21,11 +21,14 @@ pub trait PluginFactory: Send + Sync {
     fn create_plugin(&self) -> Result<Box<dyn Plugin>>;
 
     fn stop(&self) -> Result<()>;
+
+    fn register_upgrades(&self, _upgrades: &mut ScopeUpgrades) {}
 }
 
 #[derive(Default)]
 pub struct RegisteredPlugins {
     factories: Vec<Box<dyn PluginFactory>>,
+    upgrades: ScopeUpgrades,
 }
 
 impl RegisteredPlugins {
@@ -33,9 +36,16 @@ impl RegisteredPlugins {
     where
         P: PluginFactory + 'static,
     {
+        factory.register_upgrades(&mut self.upgrades);
+
         self.factories.push(Box::new(factory))
     }
 
+    /// Versioned scopes of every registered plugin.
+    pub fn scope_upgrades(&self) -> &ScopeUpgrades {
+        &self.upgrades
+    }
+
     pub fn create_plugins(&self) -> Result<SessionPlugins> {
         Ok(SessionPlugins::new(
             self.factories
@@ -107,6 +117,18 @@ impl HasArgumentType for String {
     }
 }
 
+impl HasArgumentType for Role {
+    fn argument_type() -> ArgumentType {
+        ArgumentType::String
+    }
+}
+
+impl HasArgumentType for u64 {
+    fn argument_type() -> ArgumentType {
+        ArgumentType::Number
+    }
+}
+
 impl HasArgumentType for WorkingCopy {
     fn argument_type() -> ArgumentType {
         ArgumentType::String
@@ -131,10 +153,48 @@ pub struct ArgSchema {
     ty: ArgumentType,
 }
 
+/// Privileges needed to perform actions, each role includes the privileges
+/// of the roles before it.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum Role {
+    #[default]
+    Player,
+    Builder,
+    Admin,
+}
+
+impl std::fmt::Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Role::Player => write!(f, "player"),
+            Role::Builder => write!(f, "builder"),
+            Role::Admin => write!(f, "admin"),
+        }
+    }
+}
+
+impl std::str::FromStr for Role {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "player" => Ok(Role::Player),
+            "builder" => Ok(Role::Builder),
+            "admin" => Ok(Role::Admin),
+            _ => Err(anyhow::anyhow!("unknown role {}", s)),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ActionSchema {
     name: String,
     args: Vec<ArgSchema>,
+    grammar: Option<String>,
+    role: Role,
 }
 
 impl ActionSchema {
@@ -142,9 +202,21 @@ impl ActionSchema {
         Self {
             name: name.to_owned(),
             args: Vec::new(),
+            grammar: None,
+            role: Role::default(),
         }
     }
 
+    pub fn role(mut self, role: Role) -> Self {
+        self.role = role;
+        self
+    }
+
+    pub fn grammar(mut self, grammar: &str) -> Self {
+        self.grammar = Some(grammar.to_owned());
+        self
+    }
+
     pub fn arg(mut self, name: &str, ty: ArgumentType) -> Self {
         self.args.push(ArgSchema {
             name: name.to_owned(),
@@ -164,6 +236,17 @@ impl ActionSchema {
     pub fn number(self, name: &str) -> Self {
         self.arg(name, ArgumentType::Number)
     }
+
+    /// The word that begins the action, taken from the grammar's leading
+    /// literal. Actions without a grammar can't be typed, so have no verb.
+    pub fn verb(&self) -> Option<String> {
+        self.grammar
+            .as_ref()?
+            .split_whitespace()
+            .next()
+            .filter(|w| w.chars().all(|c| c.is_ascii_uppercase()))
+            .map(|w| w.to_lowercase())
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -180,9 +263,14 @@ impl Schema {
         Self::default()
     }
 
-    pub fn action<A: Action + HasActionSchema>(mut self) -> Self {
+    pub fn action<A: Action + HasActionSchema>(self) -> Self {
+        self.action_requiring::<A>(Role::default())
+    }
+
+    /// Adds an action that only actors with the role may perform.
+    pub fn action_requiring<A: Action + HasActionSchema>(mut self, role: Role) -> Self {
         self.actions
-            .push(<A>::action_schema(ActionSchema::new(&<A>::tag())));
+            .push(<A>::action_schema(ActionSchema::new(&<A>::tag())).role(role));
 
         self
     }
@@ -201,6 +289,18 @@ impl Schema {
             })
             .collect::<Vec<(_, _)>>()
     }
+
+    pub fn verbs(&self) -> Vec<String> {
+        self.actions.iter().flat_map(|a| a.verb()).collect()
+    }
+
+    /// The role each of the schema's actions requires, by tag.
+    pub fn roles(&self) -> HashMap<String, Role> {
+        self.actions
+            .iter()
+            .map(|a| (a.name.to_owned(), a.role))
+            .collect()
+    }
 }
 
 #[derive(Debug, Default, Clone)]
@@ -213,6 +313,10 @@ impl SchemaCollection {
             .map(|(plugin, schema)| (plugin.to_owned(), schema.actions()))
             .collect::<Vec<_>>()
     }
+
+    pub fn roles(&self) -> HashMap<String, Role> {
+        self.0.values().flat_map(|schema| schema.roles()).collect()
+    }
 }
 
 impl From<HashMap<String, Schema>> for SchemaCollection {
@@ -242,6 +346,12 @@ pub trait Plugin: ParsesActions {
         Ok(())
     }
 
+    /// Words that begin the actions this plugin parses, used to suggest
+    /// what may have been meant when nothing parses.
+    fn verbs(&self) -> Vec<String> {
+        self.schema().verbs()
+    }
+
     fn sources(&self) -> Vec<Box<dyn ActionSource>> {
         vec![]
     }
@@ -273,6 +383,17 @@ impl SessionPlugins {
             .into()
     }
 
+    pub fn verbs(&self) -> Vec<String> {
+        let mut verbs = self
+            .plugins
+            .iter()
+            .flat_map(|p| p.verbs())
+            .collect::<Vec<_>>();
+        verbs.sort();
+        verbs.dedup();
+        verbs
+    }
+
     pub fn initialize(&mut self) -> anyhow::Result<()> {
         let all_schema = self.schema();
 
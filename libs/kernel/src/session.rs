@@ -2,16 +2,24 @@ use anyhow::Result;
 use std::ops::Deref;
 use std::{cell::RefCell, rc::Rc};
 
-use replies::TaggedJson;
+use replies::{ObservedFuture, TaggedJson};
 
 use crate::actions::{Action, FutureAction, Performer};
 use crate::model::{
     Audience, DomainError, Entity, EntityKey, EntityPtr, EntityPtrResolver, Found, Identity, Item,
+    JsonValue,
 };
 use crate::surround::Surroundings;
 
 pub type SessionRef = Rc<dyn ActiveSession>;
 
+/// A previous version of an entity, as it was saved.
+#[derive(Debug, Clone)]
+pub struct EntityVersion {
+    pub version: u64,
+    pub value: JsonValue,
+}
+
 pub enum Raising {
     TaggedJson(TaggedJson),
 }
@@ -48,6 +56,14 @@ pub trait ActiveSession: Performer + EntityPtrResolver {
 
     fn schedule(&self, future: FutureAction) -> Result<(), DomainError>;
 
+    fn futures(&self, entity: &EntityKey) -> Result<Vec<ObservedFuture>, DomainError>;
+
+    /// Cancels a scheduled future, returning false if there was no such future.
+    fn cancel(&self, key: &str) -> Result<bool, DomainError>;
+
+    /// Previous versions of the entity, the newest first.
+    fn versions(&self, key: &EntityKey) -> Result<Vec<EntityVersion>, DomainError>;
+
     fn try_deserialize_action(
         &self,
         value: &TaggedJson,
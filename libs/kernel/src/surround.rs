@@ -1,6 +1,6 @@
 use serde::Serialize;
 
-use crate::model::EntityPtr;
+use crate::model::{DomainError, EntityPtr};
 
 #[derive(Debug, Clone, Serialize)]
 pub enum Surroundings {
@@ -9,13 +9,22 @@ pub enum Surroundings {
         actor: EntityPtr,
         area: EntityPtr,
     },
+    /// Surroundings of world level actions, things like the weather that
+    /// aren't being done by anybody or happening anywhere in particular.
+    World { world: EntityPtr },
+    /// Surroundings of things happening in an area, like resets, without
+    /// anybody doing them.
+    Area { world: EntityPtr, area: EntityPtr },
 }
 
 impl Surroundings {
-    pub fn unpack(&self) -> (EntityPtr, EntityPtr, EntityPtr) {
+    pub fn unpack(&self) -> Result<(EntityPtr, EntityPtr, EntityPtr), DomainError> {
         match self {
             Surroundings::Actor { world, actor, area } => {
-                (world.clone(), actor.clone(), area.clone())
+                Ok((world.clone(), actor.clone(), area.clone()))
+            }
+            Surroundings::World { world: _ } | Surroundings::Area { world: _, area: _ } => {
+                Err(DomainError::ActorRequired)
             }
         }
     }
@@ -27,16 +36,33 @@ impl Surroundings {
                 actor: _,
                 area: _,
             } => world,
+            Surroundings::World { world } => world,
+            Surroundings::Area { world, area: _ } => world,
         }
     }
 
-    pub fn actor(&self) -> &EntityPtr {
+    pub fn actor(&self) -> Result<&EntityPtr, DomainError> {
         match self {
             Surroundings::Actor {
                 world: _,
                 actor,
                 area: _,
-            } => actor,
+            } => Ok(actor),
+            Surroundings::World { world: _ } | Surroundings::Area { world: _, area: _ } => {
+                Err(DomainError::ActorRequired)
+            }
+        }
+    }
+
+    pub fn area(&self) -> Result<&EntityPtr, DomainError> {
+        match self {
+            Surroundings::Actor {
+                world: _,
+                actor: _,
+                area,
+            } => Ok(area),
+            Surroundings::Area { world: _, area } => Ok(area),
+            Surroundings::World { world: _ } => Err(DomainError::AreaRequired),
         }
     }
 }
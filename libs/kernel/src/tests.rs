@@ -97,6 +97,18 @@ impl ActiveSession for KeysOnlySession {
         todo!()
     }
 
+    fn futures(&self, _entity: &EntityKey) -> Result<Vec<common::ObservedFuture>, DomainError> {
+        todo!()
+    }
+
+    fn cancel(&self, _key: &str) -> Result<bool, DomainError> {
+        todo!()
+    }
+
+    fn versions(&self, _key: &EntityKey) -> Result<Vec<EntityVersion>, DomainError> {
+        todo!()
+    }
+
     fn try_deserialize_action(
         &self,
         _tagged: &TaggedJson,
@@ -104,3 +116,14 @@ impl ActiveSession for KeysOnlySession {
         todo!()
     }
 }
+
+#[test]
+fn it_only_takes_verbs_from_grammars() {
+    assert_eq!(
+        ActionSchema::new("holdAction")
+            .grammar("HOLD item:Item")
+            .verb(),
+        Some("hold".to_owned())
+    );
+    assert_eq!(ActionSchema::new("saveEntityJsonAction").verb(), None);
+}
@@ -1,6 +1,9 @@
 use proc_macro::TokenStream;
-use quote::quote;
-use syn::{parse_macro_input, Data, DataStruct, DeriveInput, Fields};
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, punctuated::Punctuated, token::Comma, Data, DataStruct, DeriveInput, Field,
+    Fields, LitStr,
+};
 
 #[proc_macro_derive(Reply)]
 pub fn derive_reply(input: TokenStream) -> TokenStream {
@@ -65,38 +68,99 @@ pub fn derive_deserialize_tagged(input: TokenStream) -> TokenStream {
 #[proc_macro_derive(HasActionSchema)]
 pub fn derive_has_action_schema(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
-    let name = input.ident;
 
-    let fields = match &input.data {
+    has_action_schema(&input, None).into()
+}
+
+fn named_fields(input: &DeriveInput) -> &Punctuated<Field, Comma> {
+    match &input.data {
         Data::Struct(DataStruct {
             fields: Fields::Named(fields),
             ..
         }) => &fields.named,
         _ => panic!("expected a struct with named fields"),
-    };
+    }
+}
+
+fn has_action_schema(input: &DeriveInput, grammar: Option<&LitStr>) -> proc_macro2::TokenStream {
+    let name = &input.ident;
+    let fields = named_fields(input);
     let field_name = fields.iter().map(|field| &field.ident);
     let field_type = fields.iter().map(|field| &field.ty);
+    let grammar = grammar.map(|grammar| {
+        quote! {
+            schema = schema.grammar(#grammar);
+        }
+    });
 
-    let done = quote! {
+    quote! {
         impl HasActionSchema for #name {
             fn action_schema(mut schema: ActionSchema) -> ActionSchema {
                 #(
                     schema = schema.arg(stringify!(#field_name), <#field_type>::argument_type());
                 )*
+                #grammar
                 schema
             }
         }
-    };
+    }
+}
 
-    done.into()
+/// Generates a parser named after the action, so `HoldAction` gets a
+/// `HoldActionParser`, that binds each capture in the grammar to the
+/// action's fields in the order they're declared.
+fn parses_actions(input: &DeriveInput, grammar: &LitStr) -> proc_macro2::TokenStream {
+    let name = &input.ident;
+    let parser = format_ident!("{}Parser", name);
+    let field_name = named_fields(input).iter().map(|field| &field.ident);
+
+    quote! {
+        pub struct #parser {}
+
+        impl ParsesActions for #parser {
+            fn try_parse_action(&self, i: &str) -> EvaluationResult {
+                let mut captures = parse_grammar(#grammar, i)?.into_iter();
+                let action = #name {
+                    #(
+                        #field_name: FromCapture::from_capture(captures.next())?,
+                    )*
+                };
+
+                Ok(Some(Box::new(action)))
+            }
+        }
+    }
 }
 
 #[proc_macro_attribute]
-pub fn action(_metadata: TokenStream, item: TokenStream) -> TokenStream {
+pub fn action(metadata: TokenStream, item: TokenStream) -> TokenStream {
     let item = parse_macro_input!(item as DeriveInput);
+
+    if metadata.is_empty() {
+        let done = quote! {
+            #[derive(Debug, Serialize, Deserialize, ToTaggedJson, DeserializeTagged, HasActionSchema)]
+            #item
+        };
+
+        return done.into();
+    }
+
+    let grammar = parse_macro_input!(metadata as LitStr);
+    if english::to_tongue(&grammar.value()).is_none() {
+        return syn::Error::new(grammar.span(), "unable to parse action grammar")
+            .to_compile_error()
+            .into();
+    }
+
+    let schema = has_action_schema(&item, Some(&grammar));
+    let parser = parses_actions(&item, &grammar);
     let done = quote! {
-        #[derive(Debug, Serialize, Deserialize, ToTaggedJson, DeserializeTagged, HasActionSchema)]
+        #[derive(Debug, Serialize, Deserialize, ToTaggedJson, DeserializeTagged)]
         #item
+
+        #schema
+
+        #parser
     };
 
     done.into()
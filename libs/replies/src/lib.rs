@@ -59,6 +59,102 @@ pub struct EntityObservation {
     pub wearing: Option<Vec<ObservedEntity>>,
 }
 
+/// Sent when a description matches several entities equally well, the actor
+/// is expected to choose one of the candidates.
+#[derive(Clone, Serialize, Deserialize, PartialEq, ToTaggedJson, Reply, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AmbiguousReply {
+    pub candidates: Vec<ObservedEntity>,
+}
+
+/// Sent when nothing understood what was typed, with corrections that may
+/// have been meant instead.
+#[derive(Clone, Serialize, Deserialize, PartialEq, ToTaggedJson, Reply, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SuggestionsReply {
+    pub suggestions: Vec<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ObservedFuture {
+    pub key: String,
+    pub time: String,
+    pub cron: Option<String>,
+    pub action: TaggedJson,
+}
+
+/// The futures scheduled for an entity, soonest first.
+#[derive(Clone, Serialize, Deserialize, PartialEq, ToTaggedJson, Reply, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FuturesReply {
+    pub entity: ObservedEntity,
+    pub futures: Vec<ObservedFuture>,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ObservedModeration {
+    pub time: String,
+    pub restriction: String,
+    pub change: String,
+    pub reason: Option<String>,
+    pub expires: Option<String>,
+    pub by: Option<String>,
+}
+
+/// How an actor is being moderated along with everything moderators have
+/// done to them, the oldest first.
+#[derive(Clone, Serialize, Deserialize, PartialEq, ToTaggedJson, Reply, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ModerationReply {
+    pub entity: ObservedEntity,
+    pub frozen: bool,
+    pub muted: bool,
+    pub log: Vec<ObservedModeration>,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ObservedInvitation {
+    pub code: String,
+    pub inviter: Option<String>,
+    pub created: String,
+    pub expires: String,
+}
+
+/// Invitation codes that can still be used to register.
+#[derive(Clone, Serialize, Deserialize, PartialEq, ToTaggedJson, Reply, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct InvitationsReply {
+    pub invitations: Vec<ObservedInvitation>,
+}
+
+/// Sent instead of an action's reply when it tried to change parts of an
+/// entity the actor isn't allowed to, none of its changes are kept.
+#[derive(Clone, Serialize, Deserialize, PartialEq, ToTaggedJson, Reply, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DeniedReply {
+    pub key: String,
+    pub paths: Vec<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ObservedVersion {
+    pub version: u64,
+    pub differences: Vec<String>,
+}
+
+/// Previous versions of an entity, the newest first, along with the paths
+/// that differ between each of them and the entity as it is now.
+#[derive(Clone, Serialize, Deserialize, PartialEq, ToTaggedJson, Reply, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryReply {
+    pub entity: ObservedEntity,
+    pub versions: Vec<ObservedVersion>,
+}
+
 #[derive(Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub enum WorkingCopy {
@@ -164,6 +260,11 @@ pub enum Carrying {
         item: ObservedEntity,
         area: ObservedEntity,
     },
+    Given {
+        actor: ObservedEntity,
+        item: ObservedEntity,
+        receiver: ObservedEntity,
+    },
 }
 
 impl DomainEvent for Carrying {}
@@ -183,6 +284,52 @@ pub enum Moving {
 
 impl DomainEvent for Moving {}
 
+/// Changes to locks, where the item is the locked container or, for routes,
+/// the area the route leaves from.
+#[derive(Serialize, Deserialize, ToTaggedJson, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum Locking {
+    Locked {
+        actor: ObservedEntity,
+        item: ObservedEntity,
+        route: Option<String>,
+    },
+    Unlocked {
+        actor: ObservedEntity,
+        item: ObservedEntity,
+        route: Option<String>,
+    },
+    Opened {
+        actor: ObservedEntity,
+        item: ObservedEntity,
+        route: Option<String>,
+    },
+    Closed {
+        actor: ObservedEntity,
+        item: ObservedEntity,
+        route: Option<String>,
+    },
+}
+
+impl DomainEvent for Locking {}
+
+#[derive(Serialize, Deserialize, ToTaggedJson, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum Consuming {
+    Ate {
+        actor: ObservedEntity,
+        item: ObservedEntity,
+        area: ObservedEntity,
+    },
+    Drank {
+        actor: ObservedEntity,
+        item: ObservedEntity,
+        area: ObservedEntity,
+    },
+}
+
+impl DomainEvent for Consuming {}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Spoken {
     pub who: ObservedEntity,
@@ -203,10 +350,23 @@ impl Spoken {
 pub enum Talking {
     Conversation(Spoken),
     Whispering(Spoken),
+    Shouting(Spoken),
 }
 
 impl DomainEvent for Talking {}
 
+/// An event that happened in another area, as noticed by somebody the
+/// distance away from it in the direction given, when that's known.
+#[derive(Serialize, Deserialize, ToTaggedJson, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Distant {
+    pub distance: u32,
+    pub direction: Option<String>,
+    pub event: JsonValue,
+}
+
+impl DomainEvent for Distant {}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Emoted {
     pub who: ObservedEntity,
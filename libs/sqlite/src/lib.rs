@@ -7,8 +7,10 @@ use std::{rc::Rc, sync::Mutex};
 use tracing::*;
 
 use engine::{
-    storage::EntityStorage,
-    storage::{FutureStorage, PendingFutures, Storage, StorageFactory},
+    storage::{ConflictError, EntityStorage, EntityVersionStorage, DEFAULT_VERSION_RETENTION},
+    storage::{DeadFuture, FutureStorage, PendingFutures, Storage, StorageFactory},
+    storage::{JournalEntry, JournalStorage},
+    storage::{MailboxLimits, MailboxStorage, PersistedMail},
     storage::{PersistedEntity, PersistedFuture},
 };
 use kernel::prelude::{EntityGid, EntityKey, LookupBy};
@@ -20,6 +22,8 @@ where
     C: AsConnection,
 {
     conn: C,
+    retention: usize,
+    limits: MailboxLimits,
 }
 
 enum SetupQuery {
@@ -78,14 +82,95 @@ impl Migrate for Connection {
                 )"#,
         ))?;
 
+        // Several futures may be scheduled for the same instant, this index was
+        // originally unique so it's replaced.
+        exec(SetupQuery::Execute(r#"DROP INDEX IF EXISTS futures_time"#))?;
+
+        exec(SetupQuery::Execute(
+            r#"CREATE INDEX IF NOT EXISTS futures_by_time ON futures (time)"#,
+        ))?;
+
+        exec(SetupQuery::Execute(
+            r#"CREATE INDEX IF NOT EXISTS futures_by_entity ON futures (entity)"#,
+        ))?;
+
+        if !self.has_column("futures", "attempts")? {
+            exec(SetupQuery::Execute(
+                r#"ALTER TABLE futures ADD COLUMN attempts INTEGER NOT NULL DEFAULT 0"#,
+            ))?;
+        }
+
+        exec(SetupQuery::Execute(
+            r#"
+                CREATE TABLE IF NOT EXISTS dead_futures (
+                    key TEXT NOT NULL PRIMARY KEY,
+                    entity TEXT NOT NULL,
+                    time TIMESTAMP NOT NULL,
+                    cron TEXT,
+                    serialized TEXT NOT NULL,
+                    attempts INTEGER NOT NULL,
+                    error TEXT NOT NULL,
+                    failed TIMESTAMP NOT NULL
+                )"#,
+        ))?;
+
+        exec(SetupQuery::Execute(
+            r#"
+                CREATE TABLE IF NOT EXISTS entity_versions (
+                    key TEXT NOT NULL,
+                    version INTEGER NOT NULL,
+                    gid INTEGER,
+                    serialized TEXT NOT NULL,
+                    PRIMARY KEY (key, version)
+                )"#,
+        ))?;
+
+        exec(SetupQuery::Execute(
+            r#"
+                CREATE TABLE IF NOT EXISTS journal (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    time TIMESTAMP NOT NULL,
+                    serialized TEXT NOT NULL
+                )"#,
+        ))?;
+
+        exec(SetupQuery::Execute(
+            r#"
+                CREATE TABLE IF NOT EXISTS mailbox (
+                    sequence INTEGER PRIMARY KEY AUTOINCREMENT,
+                    audience TEXT NOT NULL,
+                    time TIMESTAMP NOT NULL,
+                    serialized TEXT NOT NULL
+                )"#,
+        ))?;
+
         exec(SetupQuery::Execute(
-            r#"CREATE UNIQUE INDEX IF NOT EXISTS futures_time ON futures (time)"#,
+            r#"CREATE INDEX IF NOT EXISTS mailbox_by_audience ON mailbox (audience, sequence)"#,
         ))?;
 
         Ok(())
     }
 }
 
+trait HasColumn {
+    fn has_column(&self, table: &str, column: &str) -> Result<bool>;
+}
+
+impl HasColumn for Connection {
+    fn has_column(&self, table: &str, column: &str) -> Result<bool> {
+        let mut stmt = self.prepare(&format!("PRAGMA table_info({})", table))?;
+        let names = stmt.query_map([], |row| row.get::<_, String>(1))?;
+
+        for name in names {
+            if name? == column {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+}
+
 struct Owned {
     conn: Connection,
 }
@@ -121,8 +206,12 @@ impl<C> SqliteStorage<C>
 where
     C: AsConnection,
 {
-    pub fn wrap(conn: C) -> Result<Rc<Self>> {
-        Ok(Rc::new(Self { conn }))
+    pub fn wrap(conn: C, retention: usize, limits: MailboxLimits) -> Result<Rc<Self>> {
+        Ok(Rc::new(Self {
+            conn,
+            retention,
+            limits,
+        }))
     }
 
     fn connection(&self) -> &Connection {
@@ -163,6 +252,53 @@ where
         entities.into_iter().map(|v| Ok(v?)).collect::<Result<_>>()
     }
 
+    fn query_futures<T: rusqlite::Params>(
+        &self,
+        query: &str,
+        params: T,
+    ) -> Result<Vec<PersistedFuture>> {
+        let mut stmt = self.connection().prepare(query)?;
+
+        let futures = stmt.query_map(params, |row| {
+            Ok(PersistedFuture {
+                key: row.get(0)?,
+                entity: EntityKey::from_string(row.get(1)?),
+                time: row.get(2)?,
+                cron: row.get(3)?,
+                serialized: row.get(4)?,
+                attempts: row.get(5)?,
+            })
+        })?;
+
+        futures.into_iter().map(|v| Ok(v?)).collect::<Result<_>>()
+    }
+
+    /// Copies the version of the entity being saved over, forgetting about
+    /// versions older than those being retained.
+    fn keep_version(&self, entity: &PersistedEntity) -> Result<()> {
+        if self.retention == 0 {
+            return Ok(());
+        }
+
+        let replacing = entity.version - 1;
+
+        let mut stmt = self.connection().prepare(
+            "INSERT OR REPLACE INTO entity_versions (key, version, gid, serialized) SELECT key, version, gid, serialized FROM entities WHERE key = ?1 AND version = ?2",
+        )?;
+
+        stmt.execute((&entity.key, &replacing))
+            .with_context(|| "keeping version")?;
+
+        let mut stmt = self
+            .connection()
+            .prepare("DELETE FROM entity_versions WHERE key = ?1 AND version <= ?2")?;
+
+        stmt.execute((&entity.key, replacing as i64 - self.retention as i64))
+            .with_context(|| "forgetting versions")?;
+
+        Ok(())
+    }
+
     fn load_by_key(&self, key: &EntityKey) -> Result<Option<PersistedEntity>> {
         self.single_query(
             "SELECT key, gid, version, serialized FROM entities WHERE key = ?;",
@@ -184,7 +320,7 @@ where
 {
     fn queue(&self, future: PersistedFuture) -> Result<()> {
         let mut stmt = self.connection().prepare(
-            "INSERT OR REPLACE INTO futures (key, entity, time, cron, serialized) VALUES (?1, ?2, ?3, ?4, ?5)",
+            "INSERT OR REPLACE INTO futures (key, entity, time, cron, serialized, attempts) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
         )?;
 
         let affected = stmt
@@ -194,6 +330,7 @@ where
                 &future.time,
                 &future.cron,
                 &future.serialized,
+                &future.attempts,
             ))
             .with_context(|| "inserting future")?;
 
@@ -233,64 +370,208 @@ where
 
         trace!(?upcoming, "query-futures");
 
+        let pending = self.query_futures(
+            "SELECT key, entity, time, cron, serialized, attempts FROM futures WHERE time <= ?1 ORDER BY time",
+            [&now],
+        )?;
+
+        if pending.is_empty() {
+            return Ok(PendingFutures::Waiting(upcoming));
+        }
+
+        Ok(PendingFutures::Futures(pending))
+    }
+
+    fn list_futures(&self, entity: &EntityKey) -> Result<Vec<PersistedFuture>> {
+        self.query_futures(
+            "SELECT key, entity, time, cron, serialized, attempts FROM futures WHERE entity = ?1 ORDER BY time, key",
+            [entity.key_to_string()],
+        )
+    }
+
+    fn get_future(&self, key: &str) -> Result<Option<PersistedFuture>> {
+        let mut futures = self.query_futures(
+            "SELECT key, entity, time, cron, serialized, attempts FROM futures WHERE key = ?1",
+            [key],
+        )?;
+
+        Ok(futures.pop())
+    }
+
+    fn reschedule(&self, key: &str, time: DateTime<Utc>) -> Result<()> {
+        let mut stmt = self
+            .connection()
+            .prepare("UPDATE futures SET time = ?1 WHERE key = ?2")?;
+
+        let affected = stmt
+            .execute((&time, key))
+            .with_context(|| "rescheduling future")?;
+
+        if affected != 1 {
+            warn!("reschedule:noop");
+        } else {
+            info!(%key, %time, "reschedule");
+        }
+
+        Ok(())
+    }
+
+    fn dead_letter(
+        &self,
+        future: &PersistedFuture,
+        error: &str,
+        failed: DateTime<Utc>,
+    ) -> Result<()> {
         let mut stmt = self.connection().prepare(
-            "SELECT key, entity, time, cron, serialized FROM futures WHERE time <= ?1 ORDER BY time",
+            "INSERT OR REPLACE INTO dead_futures (key, entity, time, cron, serialized, attempts, error, failed) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
         )?;
 
-        let futures = stmt.query_map([&now], |row| {
-            Ok(PersistedFuture {
-                key: row.get(0)?,
-                entity: EntityKey::from_string(row.get(1)?),
-                time: row.get(2)?,
-                cron: row.get(3)?,
-                serialized: row.get(4)?,
+        stmt.execute((
+            &future.key,
+            future.entity.key_to_string(),
+            &future.time,
+            &future.cron,
+            &future.serialized,
+            &future.attempts,
+            error,
+            &failed,
+        ))
+        .with_context(|| "inserting dead future")?;
+
+        warn!(key = %future.key, attempts = %future.attempts, "dead-letter");
+
+        Ok(())
+    }
+
+    fn query_dead_letters(&self) -> Result<Vec<DeadFuture>> {
+        let mut stmt = self.connection().prepare(
+            "SELECT key, entity, time, cron, serialized, attempts, error, failed FROM dead_futures ORDER BY failed",
+        )?;
+
+        let dead = stmt.query_map([], |row| {
+            Ok(DeadFuture {
+                future: PersistedFuture {
+                    key: row.get(0)?,
+                    entity: EntityKey::from_string(row.get(1)?),
+                    time: row.get(2)?,
+                    cron: row.get(3)?,
+                    serialized: row.get(4)?,
+                    attempts: row.get(5)?,
+                },
+                error: row.get(6)?,
+                failed: row.get(7)?,
             })
         })?;
 
-        let pending: Vec<PersistedFuture> =
-            futures.into_iter().map(|v| Ok(v?)).collect::<Result<_>>()?;
+        dead.into_iter().map(|v| Ok(v?)).collect::<Result<_>>()
+    }
+}
 
-        if pending.is_empty() {
-            return Ok(PendingFutures::Waiting(upcoming));
-        }
+impl<C> JournalStorage for SqliteStorage<C>
+where
+    C: AsConnection,
+{
+    fn append(&self, entry: &JournalEntry) -> Result<()> {
+        let mut stmt = self
+            .connection()
+            .prepare("INSERT INTO journal (time, serialized) VALUES (?1, ?2)")?;
 
-        for future in pending.iter().filter(|r| r.cron.is_some()) {
-            if let Some(spec) = &future.cron {
-                use std::str::FromStr;
-                match cron::Schedule::from_str(&spec) {
-                    Ok(schedule) => {
-                        let time = schedule.after(&now).take(1).next();
-
-                        let mut stmt = self
-                            .connection()
-                            .prepare("UPDATE futures SET time = ?1 WHERE key = ?2")
-                            .with_context(|| "updating cron future")?;
-
-                        let _updating = stmt.execute((time, &future.key))?;
-                    }
-                    Err(e) => warn!("cron error: {:?}", e),
-                }
-            }
-        }
+        stmt.execute((&entry.time, serde_json::to_string(entry)?))
+            .with_context(|| "appending journal")?;
+
+        Ok(())
+    }
 
+    fn query_journal(&self) -> Result<Vec<JournalEntry>> {
         let mut stmt = self
             .connection()
-            .prepare("DELETE FROM futures WHERE time <= ?1 AND cron IS NULL")
-            .with_context(|| "deleting pending futures")?;
-
-        let _deleted = stmt.execute((now,))?;
-
-        /*
-        if deleted != pending.len() {
-            warn!(
-                pending = %pending.len(),
-                deleted = %deleted,
-                "query-futures:mismatch",
-            );
-        }
-        */
+            .prepare("SELECT serialized FROM journal ORDER BY id")?;
 
-        Ok(PendingFutures::Futures(pending))
+        let entries = stmt.query_map([], |row| row.get::<_, String>(0))?;
+
+        entries
+            .into_iter()
+            .map(|v| Ok(serde_json::from_str(&v?)?))
+            .collect::<Result<_>>()
+    }
+}
+
+impl<C> MailboxStorage for SqliteStorage<C>
+where
+    C: AsConnection,
+{
+    fn post(&self, audience: &EntityKey, time: DateTime<Utc>, serialized: &str) -> Result<u64> {
+        let mut stmt = self
+            .connection()
+            .prepare("INSERT INTO mailbox (audience, time, serialized) VALUES (?1, ?2, ?3)")?;
+
+        stmt.execute((audience.key_to_string(), &time, serialized))
+            .with_context(|| "posting mail")?;
+
+        let sequence = self.connection().last_insert_rowid() as u64;
+
+        let mut stmt = self.connection().prepare(
+            r#"
+                DELETE FROM mailbox WHERE audience = ?1 AND (time < ?2 OR sequence <= (
+                    SELECT sequence FROM mailbox WHERE audience = ?1 ORDER BY sequence DESC LIMIT 1 OFFSET ?3
+                ))"#,
+        )?;
+
+        stmt.execute((
+            audience.key_to_string(),
+            &(time - self.limits.ttl),
+            self.limits.capacity as i64,
+        ))
+        .with_context(|| "trimming mailbox")?;
+
+        Ok(sequence)
+    }
+
+    fn query_mailbox(
+        &self,
+        audience: &EntityKey,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<PersistedMail>> {
+        let mut stmt = self.connection().prepare(
+            "SELECT sequence, audience, time, serialized FROM mailbox WHERE audience = ?1 AND time >= ?2 ORDER BY sequence",
+        )?;
+
+        let mail = stmt.query_map(
+            (audience.key_to_string(), &(now - self.limits.ttl)),
+            |row| {
+                Ok(PersistedMail {
+                    sequence: row.get(0)?,
+                    audience: EntityKey::from_string(row.get(1)?),
+                    time: row.get(2)?,
+                    serialized: row.get(3)?,
+                })
+            },
+        )?;
+
+        mail.into_iter().map(|v| Ok(v?)).collect::<Result<_>>()
+    }
+
+    fn acknowledge(&self, audience: &EntityKey, sequence: u64) -> Result<()> {
+        let mut stmt = self
+            .connection()
+            .prepare("DELETE FROM mailbox WHERE audience = ?1 AND sequence <= ?2")?;
+
+        stmt.execute((audience.key_to_string(), &sequence))
+            .with_context(|| "acknowledging mail")?;
+
+        Ok(())
+    }
+}
+
+impl<C> EntityVersionStorage for SqliteStorage<C>
+where
+    C: AsConnection,
+{
+    fn query_versions(&self, key: &EntityKey) -> Result<Vec<PersistedEntity>> {
+        self.multiple_query(
+            "SELECT key, gid, version, serialized FROM entity_versions WHERE key = ? ORDER BY version DESC;",
+            [key.key_to_string()],
+        )
     }
 }
 
@@ -307,6 +588,18 @@ where
         }
     }
 
+    fn stored_versions(&self) -> Result<Vec<(EntityKey, u64)>> {
+        let mut stmt = self
+            .connection()
+            .prepare("SELECT key, version FROM entities;")?;
+
+        let versions = stmt.query_map([], |row| {
+            Ok((EntityKey::new(&row.get::<_, String>(0)?), row.get(1)?))
+        })?;
+
+        Ok(versions.collect::<Result<Vec<_>, _>>()?)
+    }
+
     fn save(&self, entity: &PersistedEntity) -> Result<()> {
         let affected = if entity.version == 1 {
             debug!(%entity.key, %entity.gid, "inserting");
@@ -324,6 +617,8 @@ where
         } else {
             debug!(%entity.key, %entity.gid, "updating");
 
+            self.keep_version(entity)?;
+
             let mut stmt = self.connection().prepare(
                     "UPDATE entities SET gid = ?1, version = ?2, serialized = ?3 WHERE key = ?4 AND version = ?5",
                 )?;
@@ -338,7 +633,11 @@ where
         };
 
         if affected != 1 {
-            Err(anyhow!("no rows affected by save"))
+            Err(ConflictError {
+                key: entity.key.clone(),
+                version: entity.version,
+            }
+            .into())
         } else {
             Ok(())
         }
@@ -412,6 +711,8 @@ impl InMemoryKeepAlive {
 
 pub struct Factory {
     uri: String,
+    retention: usize,
+    limits: MailboxLimits,
     _id: String,
     _keep_alive: Option<InMemoryKeepAlive>,
 }
@@ -429,10 +730,20 @@ impl Factory {
 
         Ok(Factory {
             uri,
+            retention: DEFAULT_VERSION_RETENTION,
+            limits: Default::default(),
             _id: id,
             _keep_alive: keep_alive,
         })
     }
+
+    pub fn with_version_retention(self, retention: usize) -> Self {
+        Self { retention, ..self }
+    }
+
+    pub fn with_mailbox_limits(self, limits: MailboxLimits) -> Self {
+        Self { limits, ..self }
+    }
 }
 
 impl StorageFactory for Factory {
@@ -442,19 +753,37 @@ impl StorageFactory for Factory {
     }
 
     fn create_storage(&self) -> Result<Rc<dyn Storage>> {
-        Ok(SqliteStorage::wrap(Owned::new(&self.uri)?)?)
+        Ok(SqliteStorage::wrap(
+            Owned::new(&self.uri)?,
+            self.retention,
+            self.limits.clone(),
+        )?)
     }
 }
 
 pub struct ConnectionPool {
     pool: r2d2::Pool<SqliteConnectionManager>,
+    retention: usize,
+    limits: MailboxLimits,
 }
 
 impl ConnectionPool {
     pub fn new(path: &str) -> Result<Self> {
         let manager = SqliteConnectionManager::file(path);
         let pool = r2d2::Pool::new(manager)?;
-        Ok(Self { pool })
+        Ok(Self {
+            pool,
+            retention: DEFAULT_VERSION_RETENTION,
+            limits: Default::default(),
+        })
+    }
+
+    pub fn with_version_retention(self, retention: usize) -> Self {
+        Self { retention, ..self }
+    }
+
+    pub fn with_mailbox_limits(self, limits: MailboxLimits) -> Self {
+        Self { limits, ..self }
     }
 }
 
@@ -465,9 +794,13 @@ impl StorageFactory for ConnectionPool {
     }
 
     fn create_storage(&self) -> Result<Rc<dyn Storage>> {
-        Ok(SqliteStorage::wrap(Pooled {
-            conn: self.pool.get()?,
-        })?)
+        Ok(SqliteStorage::wrap(
+            Pooled {
+                conn: self.pool.get()?,
+            },
+            self.retention,
+            self.limits.clone(),
+        )?)
     }
 }
 
@@ -476,6 +809,7 @@ mod tests {
     use super::*;
     use anyhow::Result;
     use chrono::Days;
+    use engine::storage::{Changed, Performed};
 
     fn get_storage() -> Result<Rc<dyn Storage>> {
         let s = Factory::new(MEMORY_SPECIAL)?;
@@ -612,6 +946,31 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn it_keeps_previous_versions() -> Result<()> {
+        let s = get_storage()?;
+
+        for version in 1..=(DEFAULT_VERSION_RETENTION as u64 + 2) {
+            s.save(&PersistedEntity {
+                key: "world".to_string(),
+                gid: 1,
+                version,
+                serialized: format!("{{\"version\":{}}}", version),
+            })?;
+        }
+
+        let versions = s.query_versions(&EntityKey::new("world"))?;
+
+        assert_eq!(versions.len(), DEFAULT_VERSION_RETENTION);
+        assert_eq!(versions[0].version, DEFAULT_VERSION_RETENTION as u64 + 1);
+        assert_eq!(
+            versions[0].serialized,
+            format!("{{\"version\":{}}}", versions[0].version)
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn it_queues_futures() -> Result<()> {
         let s = get_storage()?;
@@ -624,6 +983,7 @@ mod tests {
             cron: None,
             time,
             serialized: "{}".to_owned(),
+            attempts: 0,
         })?;
 
         let pending = s.query_futures_before(time.checked_add_days(Days::new(1)).unwrap())?;
@@ -632,7 +992,7 @@ mod tests {
 
         let pending = s.query_futures_before(time.checked_add_days(Days::new(1)).unwrap())?;
 
-        assert_eq!(pending, PendingFutures::Waiting(None));
+        assert_eq!(pending.number_futures(), Some(1));
 
         Ok(())
     }
@@ -649,6 +1009,7 @@ mod tests {
             cron: None,
             time,
             serialized: "{}".to_owned(),
+            attempts: 0,
         })?;
 
         s.cancel("test-1")?;
@@ -659,4 +1020,161 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn it_queues_futures_for_the_same_time() -> Result<()> {
+        let s = get_storage()?;
+
+        let time = Utc::now();
+
+        for key in ["test-1", "test-2"] {
+            s.queue(PersistedFuture {
+                key: key.to_owned(),
+                entity: EntityKey::new("E-0"),
+                cron: None,
+                time,
+                serialized: "{}".to_owned(),
+                attempts: 0,
+            })?;
+        }
+
+        let pending = s.query_futures_before(time)?;
+
+        assert_eq!(pending.number_futures(), Some(2));
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_lists_and_reschedules_futures() -> Result<()> {
+        let s = get_storage()?;
+
+        let time = Utc::now();
+
+        for (key, entity) in [("test-1", "E-0"), ("test-2", "E-1"), ("test-3", "E-0")] {
+            s.queue(PersistedFuture {
+                key: key.to_owned(),
+                entity: EntityKey::new(entity),
+                cron: None,
+                time,
+                serialized: "{}".to_owned(),
+                attempts: 0,
+            })?;
+        }
+
+        let listed = s.list_futures(&EntityKey::new("E-0"))?;
+
+        assert_eq!(
+            listed.iter().map(|f| f.key.as_str()).collect::<Vec<_>>(),
+            vec!["test-1", "test-3"]
+        );
+
+        let later = time.checked_add_days(Days::new(1)).unwrap();
+
+        s.reschedule("test-3", later)?;
+
+        assert_eq!(s.get_future("test-3")?.map(|f| f.time), Some(later));
+        assert_eq!(s.get_future("test-4")?, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_appends_to_the_journal() -> Result<()> {
+        let s = get_storage()?;
+
+        let entry = JournalEntry {
+            time: Utc::now(),
+            performed: vec![Performed {
+                actor: EntityKey::new("E-0"),
+                text: Some("look".to_owned()),
+                action: serde_json::json!({ "lookAction": {} }),
+                effect: serde_json::json!("ok"),
+            }],
+            raised: Vec::new(),
+            changed: vec![Changed {
+                key: EntityKey::new("E-0"),
+                version: 2,
+            }],
+            created: Vec::new(),
+        };
+
+        s.begin()?;
+        s.append(&entry)?;
+        s.commit()?;
+
+        s.begin()?;
+        s.append(&entry)?;
+        s.rollback(true)?;
+
+        assert_eq!(s.query_journal()?, vec![entry]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_keeps_mail_until_acknowledged() -> Result<()> {
+        let s = Factory::new(MEMORY_SPECIAL)?.with_mailbox_limits(MailboxLimits {
+            capacity: 2,
+            ttl: chrono::Duration::hours(1),
+        });
+        s.migrate()?;
+        let s = s.create_storage()?;
+
+        let audience = EntityKey::new("E-0");
+        let now = Utc::now();
+
+        s.begin()?;
+        let first = s.post(&audience, now, "{}")?;
+        let second = s.post(&audience, now, "{}")?;
+        let third = s.post(&audience, now, "{}")?;
+        s.post(&EntityKey::new("E-1"), now, "{}")?;
+        s.commit()?;
+
+        assert!(first < second && second < third);
+
+        let sequences = |now| -> Result<Vec<u64>> {
+            Ok(s.query_mailbox(&audience, now)?
+                .into_iter()
+                .map(|m| m.sequence)
+                .collect())
+        };
+
+        assert_eq!(sequences(now)?, vec![second, third]);
+        assert!(sequences(now + chrono::Duration::hours(2))?.is_empty());
+
+        s.begin()?;
+        s.acknowledge(&audience, second)?;
+        s.commit()?;
+
+        assert_eq!(sequences(now)?, vec![third]);
+        assert_eq!(s.query_mailbox(&EntityKey::new("E-1"), now)?.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_keeps_dead_letters() -> Result<()> {
+        let s = get_storage()?;
+
+        let time = Utc::now();
+        let future = PersistedFuture {
+            key: "test-1".to_owned(),
+            entity: EntityKey::new("E-0"),
+            cron: None,
+            time,
+            serialized: "{}".to_owned(),
+            attempts: 5,
+        };
+
+        s.dead_letter(&future, "failed", time)?;
+
+        let dead = s.query_dead_letters()?;
+
+        assert_eq!(dead.len(), 1);
+        assert_eq!(dead[0].future, future);
+        assert_eq!(dead[0].error, "failed");
+
+        Ok(())
+    }
 }
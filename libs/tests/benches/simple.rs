@@ -1,7 +1,9 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use pprof::criterion::{Output, PProfProfiler};
 
-use tests::{evaluate_text_in_new_domain, HoldingKeyInVessel, USERNAME};
+use tests::{
+    evaluate_text_in_new_cached_domain, evaluate_text_in_new_domain, HoldingKeyInVessel, USERNAME,
+};
 
 pub fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function("look once", |b| {
@@ -24,6 +26,16 @@ pub fn criterion_benchmark(c: &mut Criterion) {
         })
     });
 
+    c.bench_function("look 10 times cached", |b| {
+        b.iter(|| {
+            black_box(evaluate_text_in_new_cached_domain::<HoldingKeyInVessel>(
+                USERNAME,
+                10,
+                &["look"],
+            ))
+        })
+    });
+
     c.bench_function("drop", |b| {
         b.iter(|| {
             black_box(evaluate_text_in_new_domain::<HoldingKeyInVessel>(
@@ -1,9 +1,12 @@
 use anyhow::Result;
 use plugins_core::chat::ChatPluginFactory;
+use plugins_core::choosing::ChoosingPluginFactory;
+use plugins_core::consuming::ConsumingPluginFactory;
 use plugins_core::emote::EmotePluginFactory;
 use plugins_core::fashion::FashionPluginFactory;
 use plugins_core::helping::HelpingPluginFactory;
 use plugins_core::memory::MemoryPluginFactory;
+use plugins_core::moderation::ModerationPluginFactory;
 use plugins_core::security::SecurityPluginFactory;
 use std::rc::Rc;
 use std::sync::Arc;
@@ -16,6 +19,7 @@ use engine::{
 use kernel::prelude::{Effect, RegisteredPlugins};
 use plugins_core::building::BuildingPluginFactory;
 use plugins_core::carrying::CarryingPluginFactory;
+use plugins_core::locking::LockingPluginFactory;
 use plugins_core::looking::LookingPluginFactory;
 use plugins_core::moving::MovingPluginFactory;
 use plugins_core::DefaultFinder;
@@ -108,8 +112,29 @@ pub fn evaluate_text_in_new_domain<W>(
 where
     W: WorldFixture + Default,
 {
-    let domain = make_domain()?;
+    evaluate_text_in_domain::<W>(make_domain()?, username, times, text)
+}
 
+pub fn evaluate_text_in_new_cached_domain<W>(
+    username: &str,
+    times: usize,
+    text: &'static [&'static str],
+) -> Result<()>
+where
+    W: WorldFixture + Default,
+{
+    evaluate_text_in_domain::<W>(make_domain()?.with_entity_cache(), username, times, text)
+}
+
+fn evaluate_text_in_domain<W>(
+    domain: Domain,
+    username: &str,
+    times: usize,
+    text: &'static [&'static str],
+) -> Result<()>
+where
+    W: WorldFixture + Default,
+{
     assert!(times > 0);
 
     evaluate_fixture::<W, _>(&domain, username, text)?;
@@ -134,11 +159,15 @@ where
     registered_plugins.register(EmotePluginFactory::default());
     registered_plugins.register(MovingPluginFactory::default());
     registered_plugins.register(CarryingPluginFactory::default());
+    registered_plugins.register(LockingPluginFactory::default());
+    registered_plugins.register(ConsumingPluginFactory::default());
     registered_plugins.register(FashionPluginFactory::default());
     registered_plugins.register(MemoryPluginFactory::default());
     registered_plugins.register(SecurityPluginFactory::default());
+    registered_plugins.register(ModerationPluginFactory::default());
     registered_plugins.register(HelpingPluginFactory::default());
     registered_plugins.register(BuildingPluginFactory::default());
+    registered_plugins.register(ChoosingPluginFactory::default());
     let finder = Arc::new(DefaultFinder::default());
     let keys = Arc::new(DeterministicKeys::new());
     let identities = Arc::new(DeterministicKeys::new());
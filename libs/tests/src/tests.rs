@@ -1,10 +1,30 @@
 use anyhow::Result;
+use std::collections::HashMap;
+use std::sync::Arc;
 use tokio::task::JoinHandle;
 
-use crate::{evaluate_fixture, test_domain_with, HoldingKeyInVessel, Noop, WorldFixture, USERNAME};
-use engine::prelude::{Domain, Session, SessionOpener};
-use engine::storage::{PersistedEntity, StorageFactory};
-use kernel::prelude::JsonValue;
+use crate::{
+    evaluate_fixture, make_domain, test_domain_with, HoldingKeyInVessel, Noop, WorldFixture,
+    USERNAME,
+};
+use engine::cache::{CachingStorageFactory, EntityCache};
+use engine::prelude::{
+    Credentials, DevNullNotifier, Domain, EvaluateAs, HasRoles, HasUsernames, Session,
+    SessionOpener,
+};
+use engine::storage::{InMemoryStorageFactory, PersistedEntity, StorageFactory};
+use kernel::prelude::{
+    build_entity, ActiveSession, Effect, Entity, EntityKey, EntityPtrResolver, JsonAs, JsonValue,
+    LookupBy, OpenScopeRefMut, RegisteredPlugins, Role, Surroundings, ToTaggedJson,
+};
+use plugins_core::building::actions::SaveEntityJsonAction;
+use plugins_core::carrying::CarryingPluginFactory;
+use plugins_core::consuming::model::{Consumable, Edible, Portions};
+use plugins_core::consuming::ConsumingPluginFactory;
+use plugins_core::looking::LookingPluginFactory;
+use plugins_core::{BuildSurroundings, QuickThing};
+use plugins_rune::{Behaviors, RuneBehavior, RunePluginFactory, RUNE_EXTENSION};
+use replies::{DeniedReply, WorkingCopy};
 
 async fn test_domain() -> Result<AsyncFriendlyDomain> {
     let storage_factory = sqlite::Factory::new(sqlite::MEMORY_SPECIAL)?;
@@ -84,6 +104,567 @@ async fn it_can_go_east() -> Result<()> {
     Ok(())
 }
 
+fn prepared_domain() -> Result<(Domain, EntityKey)> {
+    let domain = make_domain()?;
+    evaluate_fixture::<HoldingKeyInVessel, _>(&domain, USERNAME, &[])?;
+
+    let session = domain.open_session()?;
+    let world = session.world()?.expect("No world");
+    let actor = world.find_name_key(USERNAME)?.expect("No actor");
+    session.close(&DevNullNotifier {})?;
+
+    Ok((domain, actor))
+}
+
+#[test]
+fn it_prevents_players_from_building() -> Result<()> {
+    let (domain, _actor) = prepared_domain()?;
+
+    for text in [
+        r#"@dig "North Exit" to "South Exit" for "New Area""#,
+        "@obliterate vessel",
+        "edit raw vessel",
+    ] {
+        let effect = domain.evaluate_and_perform_as(
+            EvaluateAs::Name(USERNAME),
+            text,
+            &DevNullNotifier {},
+        )?;
+
+        assert_eq!(effect, Some(Effect::Prevented), "{}", text);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn it_prevents_players_from_saving_entity_json() -> Result<()> {
+    let (domain, actor) = prepared_domain()?;
+
+    let action = SaveEntityJsonAction::new(actor.clone(), WorkingCopy::Json(JsonValue::Null));
+    let effect = domain.perform_as(&actor, &action.to_tagged_json()?, &DevNullNotifier {})?;
+
+    assert_eq!(effect, Effect::Prevented);
+
+    Ok(())
+}
+
+fn find_named_key(domain: &Domain, name: &str) -> Result<Option<EntityKey>> {
+    Ok(domain
+        .query_all()?
+        .into_iter()
+        .map(|p| p.to_json_value())
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .find(|p| p.pointer("/scopes/props/core/name/value") == Some(&name.into()))
+        .and_then(|p| {
+            p.pointer("/key")
+                .and_then(|k| k.as_str())
+                .map(EntityKey::new)
+        }))
+}
+
+#[test]
+fn it_removes_unreadable_paths_from_entity_json() -> Result<()> {
+    let (domain, actor) = prepared_domain()?;
+
+    let vessel = find_named_key(&domain, "Vessel")?.expect("No vessel");
+
+    let session = domain.open_session()?;
+    let admin = session.entity(&LookupBy::Key(&actor))?.expect("No actor");
+    admin.grant_role(Role::Admin)?;
+    let vessel = session.entity(&LookupBy::Key(&vessel))?.expect("No vessel");
+    let mut credentials = vessel.scope_mut::<Credentials>()?;
+    credentials.set("hunter2".to_owned());
+    credentials.save()?;
+    session.close(&DevNullNotifier {})?;
+
+    let effect = domain.evaluate_and_perform_as(
+        EvaluateAs::Key(&actor),
+        "edit raw vessel",
+        &DevNullNotifier {},
+    )?;
+    let Some(Effect::Reply(reply)) = effect else {
+        panic!("Unexpected effect: {:?}", effect);
+    };
+    let reply = serde_json::to_string(&reply)?;
+
+    assert!(reply.contains("Vessel"));
+    assert!(!reply.contains("hunter2"));
+
+    Ok(())
+}
+
+#[test]
+fn it_prevents_denied_writes_and_rolls_back() -> Result<()> {
+    let (domain, actor) = prepared_domain()?;
+
+    let vessel = find_named_key(&domain, "Vessel")?.expect("No vessel");
+
+    // Nobody owns the vessel, so this leaves it writable by nobody.
+    let session = domain.open_session()?;
+    let entity = session.entity(&LookupBy::Key(&vessel))?.expect("No vessel");
+    let mut json = entity.borrow().to_json_value()?;
+    json["acls"] = serde_json::json!({ "rules": [{ "perm": "Write", "sub": ["Owner"] }] });
+    *entity.borrow_mut() = Entity::from_value(json)?;
+    session.close(&DevNullNotifier {})?;
+
+    let before = domain.query_all()?;
+
+    let effect = domain.evaluate_and_perform_as(
+        EvaluateAs::Key(&actor),
+        "drop vessel",
+        &DevNullNotifier {},
+    )?;
+
+    let Some(effect) = effect else {
+        panic!("Expected an effect");
+    };
+    let denied: DeniedReply = effect.json_as()?;
+    assert_eq!(
+        denied,
+        DeniedReply {
+            key: vessel.to_string(),
+            paths: vec![
+                "scopes.location.container.gid".to_owned(),
+                "scopes.location.container.key".to_owned(),
+                "scopes.location.container.name".to_owned(),
+            ],
+        }
+    );
+
+    let after = domain.query_all()?;
+
+    assert_eq!(
+        before.iter().map(|p| &p.serialized).collect::<Vec<_>>(),
+        after.iter().map(|p| &p.serialized).collect::<Vec<_>>()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn it_obliterates_entities_kept_in_a_directory() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let storage_factory = directory::Factory::new(dir.path().to_str().unwrap())?;
+    storage_factory.migrate()?;
+
+    let domain = test_domain_with(storage_factory)?;
+    evaluate_fixture::<HoldingKeyInVessel, _>(&domain, USERNAME, &[])?;
+
+    let vessel = find_named_key(&domain, "Vessel")?.expect("No vessel");
+    assert!(dir.path().join(format!("{}.json", vessel)).is_file());
+
+    let session = domain.open_session()?;
+    let world = session.world()?.expect("No world");
+    let actor = world.find_name_key(USERNAME)?.expect("No actor");
+    let admin = session.entity(&LookupBy::Key(&actor))?.expect("No actor");
+    admin.grant_role(Role::Admin)?;
+    session.close(&DevNullNotifier {})?;
+
+    let effect = domain.evaluate_and_perform_as(
+        EvaluateAs::Key(&actor),
+        "@obliterate vessel",
+        &DevNullNotifier {},
+    )?;
+
+    assert!(matches!(effect, Some(Effect::Reply(_))), "{:?}", effect);
+    assert_eq!(find_named_key(&domain, "Vessel")?, None);
+    assert!(!dir.path().join(format!("{}.json", vessel)).exists());
+
+    Ok(())
+}
+
+#[test]
+fn it_holds_items_again_when_saving_conflicts() -> Result<()> {
+    let mut plugins = RegisteredPlugins::default();
+    plugins.register(CarryingPluginFactory::default());
+    let mut build = BuildSurroundings::new_with_plugins(plugins)?;
+    let (_session, surroundings) = build.ground(vec![QuickThing::Object("Rake")]).build()?;
+    build.close()?;
+
+    let domain = build.domain().unwrap();
+    let mut attempts = 0;
+    domain.retry_conflicts(|| {
+        let session = domain.open_session()?;
+        session.evaluate_and_perform("burrow", "hold rake")?;
+
+        // Another session holds the rake first, so saving the above fails.
+        if attempts == 0 {
+            let other = domain.open_session()?;
+            other.evaluate_and_perform("burrow", "hold rake")?;
+            other.close(&DevNullNotifier {})?;
+        }
+
+        attempts += 1;
+
+        session.close(&DevNullNotifier {})?;
+
+        Ok(())
+    })?;
+
+    assert_eq!(attempts, 2);
+    assert_eq!(domain.conflicts(), 1);
+
+    let (_, person, _) = surroundings.unpack()?;
+    let person = domain
+        .query_entity(&LookupBy::Key(&person.key()))?
+        .unwrap()
+        .to_json_value()?;
+    assert_eq!(
+        person["scopes"]["containing"]["holding"]
+            .as_array()
+            .unwrap()
+            .len(),
+        1
+    );
+
+    Ok(())
+}
+
+#[test]
+fn it_replays_journaled_actions_in_another_world() -> Result<()> {
+    let build_world = || -> Result<(BuildSurroundings, Surroundings)> {
+        let mut plugins = RegisteredPlugins::default();
+        plugins.register(CarryingPluginFactory::default());
+        let mut build = BuildSurroundings::new_with_plugins(plugins)?;
+        let (_session, surroundings) = build.ground(vec![QuickThing::Object("Rake")]).build()?;
+        build.close()?;
+
+        Ok((build, surroundings))
+    };
+
+    let (original, surroundings) = build_world()?;
+    let (replaying, _) = build_world()?;
+
+    let domain = original.domain().unwrap();
+    let (_, person, _) = surroundings.unpack()?;
+    domain.evaluate_and_perform_as(
+        EvaluateAs::Key(&person.key()),
+        "hold rake",
+        &DevNullNotifier {},
+    )?;
+
+    let journal = domain.query_journal()?;
+    let performed = journal.last().unwrap();
+    assert_eq!(performed.performed.len(), 1);
+    assert_eq!(performed.performed[0].text.as_deref(), Some("hold rake"));
+    assert!(performed.changed.iter().any(|c| c.key == person.key()));
+
+    let replayed = replaying.domain().unwrap();
+    assert_eq!(replayed.replay(&journal, &DevNullNotifier {})?, 1);
+
+    let person = replayed
+        .query_entity(&LookupBy::Key(&person.key()))?
+        .unwrap()
+        .to_json_value()?;
+    assert_eq!(
+        person["scopes"]["containing"]["holding"]
+            .as_array()
+            .unwrap()
+            .len(),
+        1
+    );
+
+    Ok(())
+}
+
+#[test]
+fn it_replays_created_entities_with_their_original_keys() -> Result<()> {
+    let build_world = || -> Result<(BuildSurroundings, Surroundings)> {
+        let mut plugins = RegisteredPlugins::default();
+        plugins.register(CarryingPluginFactory::default());
+        let mut build = BuildSurroundings::new_with_plugins(plugins)?;
+        let (_session, surroundings) = build
+            .hands(vec![QuickThing::Multiple("Coin", 4.0)])
+            .build()?;
+        build.close()?;
+
+        Ok((build, surroundings))
+    };
+
+    let (original, surroundings) = build_world()?;
+    let (replaying, _) = build_world()?;
+
+    let domain = original.domain().unwrap();
+    let (_, person, _) = surroundings.unpack()?;
+    domain.evaluate_and_perform_as(
+        EvaluateAs::Key(&person.key()),
+        "drop 2 coin",
+        &DevNullNotifier {},
+    )?;
+
+    let journal = domain.query_journal()?;
+    let created = journal.last().unwrap().created.clone();
+    assert_eq!(created.len(), 1);
+
+    // Hand out a key so the replaying world's keys no longer line up.
+    let replayed = replaying.domain().unwrap();
+    let session = replayed.open_session()?;
+    session.new_key();
+    session.close(&DevNullNotifier {})?;
+
+    assert_eq!(replayed.replay(&journal, &DevNullNotifier {})?, 1);
+    assert!(replayed
+        .query_entity(&LookupBy::Key(&created[0]))?
+        .is_some());
+
+    Ok(())
+}
+
+/// A rake on the ground with a cached domain that's looked around once, and
+/// the domain without the cache for changing entities underneath it.
+fn cached_rake_domain(
+    cached: impl FnOnce(Domain) -> Domain,
+) -> Result<(Domain, Domain, EntityKey)> {
+    let mut plugins = RegisteredPlugins::default();
+    plugins.register(CarryingPluginFactory::default());
+    plugins.register(LookingPluginFactory::default());
+    let mut build = BuildSurroundings::new_with_plugins(plugins)?;
+    let (_session, surroundings) = build.ground(vec![QuickThing::Object("Rake")]).build()?;
+    build.close()?;
+
+    let uncached = build.domain().unwrap().clone();
+    let domain = cached(uncached.clone());
+
+    perform_with_retries(&domain, "look")?;
+
+    let (_, person, _) = surroundings.unpack()?;
+
+    Ok((uncached, domain, person.key()))
+}
+
+/// Returns how many attempts performing took.
+fn perform_with_retries(domain: &Domain, text: &str) -> Result<usize> {
+    let mut attempts = 0;
+    domain.retry_conflicts(|| {
+        attempts += 1;
+        let session = domain.open_session()?;
+        session.evaluate_and_perform("burrow", text)?;
+        session.close(&DevNullNotifier {})
+    })?;
+
+    Ok(attempts)
+}
+
+fn holding(domain: &Domain, person: &EntityKey) -> Result<usize> {
+    let person = domain
+        .query_entity(&LookupBy::Key(person))?
+        .unwrap()
+        .to_json_value()?;
+
+    Ok(person["scopes"]["containing"]["holding"]
+        .as_array()
+        .map(|h| h.len())
+        .unwrap_or_default())
+}
+
+#[test]
+fn it_reads_entities_through_the_cache_until_they_change() -> Result<()> {
+    let (uncached, domain, person) = cached_rake_domain(|d| d.with_entity_cache())?;
+    let cache = domain.entity_cache().unwrap();
+
+    let misses = cache.misses();
+    perform_with_retries(&domain, "look")?;
+    assert_eq!(cache.misses(), misses);
+    assert!(cache.hits() > 0);
+
+    assert_eq!(perform_with_retries(&domain, "hold rake")?, 1);
+    assert_eq!(holding(&uncached, &person)?, 1);
+    assert_eq!(perform_with_retries(&domain, "drop rake")?, 1);
+    assert_eq!(holding(&uncached, &person)?, 0);
+    assert_eq!(cache.misses(), misses);
+
+    Ok(())
+}
+
+#[test]
+fn it_conflicts_with_changes_made_without_the_cache() -> Result<()> {
+    let (uncached, domain, person) = cached_rake_domain(|d| d.with_entity_cache())?;
+
+    perform_with_retries(&uncached, "hold rake")?;
+
+    // The cached rake is still on the ground, so the first attempt conflicts
+    // and the second finds it's already held.
+    assert_eq!(perform_with_retries(&domain, "hold rake")?, 2);
+    assert_eq!(holding(&uncached, &person)?, 1);
+
+    Ok(())
+}
+
+#[test]
+fn it_revalidates_cached_entities_as_sessions_begin() -> Result<()> {
+    let (uncached, domain, person) = cached_rake_domain(|d| d.with_revalidated_entity_cache())?;
+
+    perform_with_retries(&uncached, "hold rake")?;
+
+    assert_eq!(perform_with_retries(&domain, "drop rake")?, 1);
+    assert_eq!(holding(&uncached, &person)?, 0);
+
+    Ok(())
+}
+
+#[test]
+fn it_evicts_cached_entities_beyond_capacity() -> Result<()> {
+    let mut plugins = RegisteredPlugins::default();
+    plugins.register(LookingPluginFactory::default());
+    let mut build = BuildSurroundings::new_with_plugins(plugins)?;
+    let (_session, _surroundings) = build
+        .ground(vec![
+            QuickThing::Object("Rake"),
+            QuickThing::Object("Shovel"),
+        ])
+        .build()?;
+    build.close()?;
+
+    let domain = build
+        .domain()
+        .unwrap()
+        .clone()
+        .with_entity_cache_capacity(2);
+    let cache = domain.entity_cache().unwrap();
+
+    let session = domain.open_session()?;
+    session.evaluate_and_perform("burrow", "look")?;
+    session.close(&DevNullNotifier {})?;
+
+    assert_eq!(cache.len(), 2);
+
+    Ok(())
+}
+
+#[test]
+fn it_evicts_the_least_recently_used_cached_entities() -> Result<()> {
+    let cache = Arc::new(EntityCache::new(2));
+    let factory = CachingStorageFactory::new(
+        Arc::new(InMemoryStorageFactory::default()),
+        Arc::clone(&cache),
+    );
+    let storage = factory.create_storage()?;
+
+    let entity = |key: &str, gid: u64| PersistedEntity {
+        key: key.to_owned(),
+        gid,
+        version: 1,
+        serialized: "{}".to_owned(),
+    };
+    let a = EntityKey::new("a");
+    let b = EntityKey::new("b");
+
+    storage.begin()?;
+    storage.save(&entity("a", 1))?;
+    storage.save(&entity("b", 2))?;
+    storage.commit()?;
+
+    storage.begin()?;
+    assert!(storage.load(&LookupBy::Key(&a))?.is_some());
+    storage.save(&entity("c", 3))?;
+    storage.commit()?;
+
+    let misses = cache.misses();
+    assert!(storage.load(&LookupBy::Key(&a))?.is_some());
+    assert_eq!(cache.misses(), misses);
+    assert!(storage.load(&LookupBy::Key(&b))?.is_some());
+    assert_eq!(cache.misses(), misses + 1);
+
+    Ok(())
+}
+
+#[test]
+fn it_keeps_notifications_for_receivers_until_acknowledged() -> Result<()> {
+    let mut plugins = RegisteredPlugins::default();
+    plugins.register(CarryingPluginFactory::default());
+    let mut build = BuildSurroundings::new_with_plugins(plugins)?;
+    let carla = build.with(build_entity().living().name("Carla"))?;
+    let (_session, surroundings) = build
+        .hands(vec![QuickThing::Object("key")])
+        .occupying(vec![QuickThing::Actual(carla.clone())])
+        .build()?;
+    let (world, person, _) = surroundings.unpack()?;
+    world.add_username_to_key("carla", &carla.key())?;
+    build.close()?;
+
+    let domain = build.domain().unwrap();
+    domain.evaluate_and_perform_as(
+        EvaluateAs::Key(&person.key()),
+        "give key to Carla",
+        &DevNullNotifier {},
+    )?;
+
+    let now = chrono::Utc::now();
+    let mail = domain.query_mailbox(&carla.key(), now)?;
+    assert_eq!(mail.len(), 1);
+    assert!(mail[0].serialized.contains("given"));
+
+    domain.acknowledge(&carla.key(), mail[0].sequence)?;
+
+    assert!(domain.query_mailbox(&carla.key(), now)?.is_empty());
+
+    Ok(())
+}
+
+const ATE_SCRIPT: &str = r#"
+    struct Fed { item }
+
+    pub fn ate(state, bag) {
+        Fed { item: bag.item().unwrap().name() }
+    }
+
+    pub fn handlers() {
+        #{
+            "consuming": #{
+                "ate": ate
+            }
+        }
+    }
+"#;
+
+#[test]
+fn it_applies_rune_handlers_for_eating() -> Result<()> {
+    let mut plugins = RegisteredPlugins::default();
+    plugins.register(RunePluginFactory::default());
+    plugins.register(CarryingPluginFactory::default());
+    plugins.register(ConsumingPluginFactory::default());
+    let mut build = BuildSurroundings::new_with_plugins(plugins)?;
+    let bread = build.make(QuickThing::Object("Bread"))?;
+    let mut edible = bread.scope_mut::<Edible>()?;
+    *edible.portions_mut() = Portions::new(2);
+    edible.save()?;
+    let mut behaviors = bread.scope_mut::<Behaviors>()?;
+    behaviors.langs = Some(HashMap::from([(
+        RUNE_EXTENSION.to_owned(),
+        RuneBehavior {
+            entry: ATE_SCRIPT.to_owned(),
+            state: None,
+        },
+    )]));
+    behaviors.save()?;
+    let (_session, surroundings) = build
+        .hands(vec![QuickThing::Actual(bread.clone())])
+        .build()?;
+    let (_, person, _) = surroundings.unpack()?;
+    build.close()?;
+
+    let domain = build.domain().unwrap();
+    domain.evaluate_and_perform_as(
+        EvaluateAs::Key(&person.key()),
+        "eat bread",
+        &DevNullNotifier {},
+    )?;
+
+    // Handlers returning a struct have it saved as their script's state.
+    let bread = domain
+        .query_entity(&LookupBy::Key(&bread.key()))?
+        .unwrap()
+        .to_json_value()?;
+    assert_eq!(
+        bread["scopes"]["behaviors"]["langs"][RUNE_EXTENSION]["state"],
+        serde_json::json!({ "item": "Bread" })
+    );
+
+    Ok(())
+}
+
 /*
 #[cfg(test)]
 #[ctor::ctor]
@@ -166,6 +166,24 @@ impl ActiveSession for AgentSession {
 
         Ok(())
     }
+
+    fn futures(
+        &self,
+        _entity: &kernel::prelude::EntityKey,
+    ) -> Result<Vec<kernel::common::ObservedFuture>, DomainError> {
+        unimplemented!("AgentSession:futures")
+    }
+
+    fn cancel(&self, _key: &str) -> Result<bool, DomainError> {
+        unimplemented!("AgentSession:cancel")
+    }
+
+    fn versions(
+        &self,
+        _key: &kernel::prelude::EntityKey,
+    ) -> Result<Vec<EntityVersion>, DomainError> {
+        unimplemented!("AgentSession:versions")
+    }
 }
 
 pub trait Agent {
@@ -314,6 +332,15 @@ where
                     area: self.get(area)?,
                 })
             }
+            rpc_proto::Surroundings::World { world } => Ok(kernel::prelude::Surroundings::World {
+                world: self.get(world)?,
+            }),
+            rpc_proto::Surroundings::Area { world, area } => {
+                Ok(kernel::prelude::Surroundings::Area {
+                    world: self.get(world)?,
+                    area: self.get(area)?,
+                })
+            }
         }
     }
 }
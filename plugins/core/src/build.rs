@@ -4,7 +4,7 @@ use std::{rc::Rc, sync::Arc};
 use engine::{domain, prelude::*, sequences::DeterministicKeys, storage::InMemoryStorageFactory};
 use kernel::{
     prelude::{
-        build_entity, CoreProps, Entity, EntityBuilder, EntityKey, EntityPtr, MutCoreProps,
+        build_entity, CoreProps, Effect, Entity, EntityBuilder, EntityKey, EntityPtr, MutCoreProps,
         OpenScopeRefMut, Quantity, RegisteredPlugins, SessionRef, SetSession, Surroundings,
         WORLD_KEY,
     },
@@ -197,14 +197,19 @@ pub struct BuildSurroundings {
     #[allow(dead_code)] // TODO Combine with Rc<Session>?
     set: SetSession<Session>,
     session: Rc<Session>,
+    domain: Option<domain::Domain>,
 }
 
 impl BuildSurroundings {
     pub fn new() -> Result<Self> {
+        Self::new_with_plugins(RegisteredPlugins::default())
+    }
+
+    pub fn new_with_plugins(plugins: RegisteredPlugins) -> Result<Self> {
         let keys = Arc::new(DeterministicKeys::new());
         let identities = Arc::new(DeterministicKeys::new());
         let storage_factory = Arc::new(InMemoryStorageFactory::default());
-        let plugins = Arc::new(RegisteredPlugins::default());
+        let plugins = Arc::new(plugins);
         let finder = Arc::new(DefaultFinder::default());
         let domain = domain::Domain::new(storage_factory, plugins, finder, keys, identities);
         let session = domain.open_session()?;
@@ -232,6 +237,7 @@ impl BuildSurroundings {
             session,
             world,
             set,
+            domain: Some(domain),
         })
     }
 
@@ -249,9 +255,16 @@ impl BuildSurroundings {
             session,
             world,
             set,
+            domain: None,
         })
     }
 
+    /// The domain sessions were opened from, unless building in a session
+    /// that was given.
+    pub fn domain(&self) -> Option<&domain::Domain> {
+        self.domain.as_ref()
+    }
+
     pub fn plain(&mut self) -> &mut Self {
         self
     }
@@ -379,6 +392,12 @@ impl BuildSurroundings {
         ))
     }
 
+    /// Parses and performs text as the built actor, using the plugins this
+    /// was created with.
+    pub fn evaluate(&self, text: &str) -> Result<Option<Effect>> {
+        Ok(self.session.evaluate_and_perform("burrow", text)?)
+    }
+
     pub fn flush(&mut self) -> Result<&mut Self> {
         self.session.flush(&DevNullNotifier {})?;
 
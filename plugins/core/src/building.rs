@@ -36,15 +36,22 @@ impl Plugin for BuildingPlugin {
 
     fn schema(&self) -> Schema {
         Schema::empty()
-            .action::<actions::EditAction>()
-            .action::<actions::DuplicateAction>()
-            .action::<actions::BidirectionalDigAction>()
-            .action::<actions::ObliterateAction>()
-            .action::<actions::LimboAction>()
-            .action::<actions::MakeItemAction>()
-            .action::<actions::BuildAreaAction>()
-            .action::<actions::AddScopeAction>()
-            .action::<actions::ChangeOwnerAction>()
+            .action_requiring::<actions::EditAction>(Role::Builder)
+            .action_requiring::<actions::SaveQuickEditAction>(Role::Builder)
+            .action_requiring::<actions::EditRawAction>(Role::Admin)
+            .action_requiring::<actions::SaveEntityJsonAction>(Role::Admin)
+            .action_requiring::<actions::DuplicateAction>(Role::Builder)
+            .action_requiring::<actions::InstantiateAction>(Role::Builder)
+            .action_requiring::<actions::BidirectionalDigAction>(Role::Builder)
+            .action_requiring::<actions::ObliterateAction>(Role::Admin)
+            .action_requiring::<actions::LimboAction>(Role::Builder)
+            .action_requiring::<actions::MakeItemAction>(Role::Builder)
+            .action_requiring::<actions::BuildAreaAction>(Role::Builder)
+            .action_requiring::<actions::AddScopeAction>(Role::Builder)
+            .action_requiring::<actions::ChangeOwnerAction>(Role::Admin)
+            .action_requiring::<actions::FuturesAction>(Role::Builder)
+            .action_requiring::<actions::HistoryAction>(Role::Builder)
+            .action_requiring::<actions::RevertAction>(Role::Builder)
     }
 
     fn sources(&self) -> Vec<Box<dyn ActionSource>> {
@@ -56,6 +63,7 @@ impl ParsesActions for BuildingPlugin {
     fn try_parse_action(&self, i: &str) -> EvaluationResult {
         try_parsing(parser::EditActionParser {}, i)
             .or_else(|_| try_parsing(parser::DuplicateActionParser {}, i))
+            .or_else(|_| try_parsing(parser::InstantiateActionParser {}, i))
             .or_else(|_| try_parsing(parser::BidirectionalDigActionParser {}, i))
             .or_else(|_| try_parsing(parser::ObliterateActionParser {}, i))
             .or_else(|_| try_parsing(parser::LimboActionParser {}, i))
@@ -63,6 +71,9 @@ impl ParsesActions for BuildingPlugin {
             .or_else(|_| try_parsing(parser::BuildAreaParser {}, i))
             .or_else(|_| try_parsing(parser::ScopeActionParser {}, i))
             .or_else(|_| try_parsing(parser::ChangeOwnerActionParser {}, i))
+            .or_else(|_| try_parsing(parser::FuturesActionParser {}, i))
+            .or_else(|_| try_parsing(parser::HistoryActionParser {}, i))
+            .or_else(|_| try_parsing(parser::RevertActionParser {}, i))
     }
 }
 
@@ -79,12 +90,16 @@ impl ActionSource for SaveActionSource {
             actions::SaveQuickEditAction,
             actions::SaveEntityJsonAction,
             actions::DuplicateAction,
+            actions::InstantiateAction,
             actions::LimboAction,
             actions::MakeItemAction,
             actions::BuildAreaAction,
             actions::AddScopeAction,
             actions::ObliterateAction,
-            actions::ChangeOwnerAction
+            actions::ChangeOwnerAction,
+            actions::FuturesAction,
+            actions::HistoryAction,
+            actions::RevertAction
         );
 
         Ok(None)
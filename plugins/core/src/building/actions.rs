@@ -6,10 +6,15 @@ use engine::prelude::HasWellKnownEntities;
 use crate::{
     building::model::{Constructed, QuickEdit},
     carrying::model::{Carryable, Containing},
+    fashion::model::Wearing,
     library::actions::*,
-    looking::{actions::LookAction, model::new_area_observation},
+    location::Location,
+    looking::{
+        actions::LookAction,
+        model::{new_area_observation, Observe},
+    },
     memory::model::{remember, EntityEvent, Memory},
-    moving::model::Occupyable,
+    moving::model::{Occupyable, Occupying},
 };
 
 #[action]
@@ -28,7 +33,7 @@ impl Action for AddScopeAction {
         // TODO Right now this requires scopes to be functionable if all their
         // fields are ommitted. Look into `#[serde(default)]` to make this work
         // w/o a bunch of Option's?
-        let Some(item) = tools::holding_one_item(surroundings.actor())? else {
+        let Some(item) = tools::holding_one_item(surroundings.actor()?)? else {
             return Ok(SimpleReply::NotFound.try_into()?);
         };
 
@@ -91,7 +96,7 @@ impl Action for EditRawAction {
                 let editing = editing.one()?;
                 let json = {
                     let editing = editing.borrow();
-                    editing.to_json_value()?
+                    editing.to_json_value_for(&surroundings.actor()?.key())?
                 };
                 let key = editing.key().clone();
                 Ok(EditorReply::new(
@@ -115,7 +120,7 @@ impl Action for LimboAction {
     }
 
     fn perform(&self, session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
-        let (world, actor, _) = surroundings.unpack();
+        let (world, actor, _) = surroundings.unpack()?;
 
         let limbo = session
             .entity(&LookupBy::Key(&world.get_limbo()?.unwrap()))?
@@ -143,7 +148,49 @@ impl Action for DuplicateAction {
         match session.find_item(surroundings, &self.item)? {
             Some(duplicating) => {
                 info!("duplicating {:?}", duplicating);
-                _ = tools::duplicate(&duplicating.one()?)?;
+                _ = tools::duplicate(&duplicating)?;
+                Ok(SimpleReply::Done.try_into()?)
+            }
+            None => Ok(SimpleReply::NotFound.try_into()?),
+        }
+    }
+}
+
+#[action]
+pub struct InstantiateAction {
+    pub item: Item,
+}
+
+impl Action for InstantiateAction {
+    fn is_read_only(&self) -> bool {
+        false
+    }
+
+    fn perform(&self, session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
+        info!("instantiate {:?}!", self.item);
+
+        let creator = surroundings.actor()?;
+
+        match session.find_item(surroundings, &self.item)? {
+            Some(prototype) => {
+                let instance = tools::new_entity_from_prototype_ptr(
+                    prototype.entity()?,
+                    creator,
+                    &prototype.quantity(),
+                )?;
+
+                tools::set_container(creator, &vec![instance.clone()])?;
+
+                remember(
+                    creator,
+                    Utc::now(),
+                    Memory::Created(EntityEvent {
+                        key: instance.key().clone(),
+                        gid: instance.gid(),
+                        name: instance.name()?,
+                    }),
+                )?;
+
                 Ok(SimpleReply::Done.try_into()?)
             }
             None => Ok(SimpleReply::NotFound.try_into()?),
@@ -167,8 +214,10 @@ impl Action for ObliterateAction {
         match session.find_item(surroundings, &self.item)? {
             Some(obliterating) => {
                 info!("obliterate {:?}", obliterating);
-                tools::obliterate(&obliterating.one()?)?;
-                Ok(SimpleReply::Done.try_into()?)
+                match tools::obliterate(obliterating)? {
+                    true => Ok(SimpleReply::Done.try_into()?),
+                    false => Ok(SimpleReply::NotFound.try_into()?),
+                }
             }
             None => Ok(SimpleReply::NotFound.try_into()?),
         }
@@ -188,7 +237,7 @@ impl Action for MakeItemAction {
     fn perform(&self, session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
         info!("make-item {:?}", self.name);
 
-        let creator = surroundings.actor();
+        let creator = surroundings.actor()?;
 
         let new_item: Entity = build_entity()
             .default_scope::<Carryable>()?
@@ -233,7 +282,7 @@ impl Action for BidirectionalDigAction {
             self.outgoing, self.returning, self.new_area
         );
 
-        let (_, actor, area) = surroundings.unpack();
+        let (_, actor, area) = surroundings.unpack()?;
 
         let new_area: Entity = build_entity()
             .area()
@@ -257,7 +306,7 @@ impl Action for BidirectionalDigAction {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, ToTaggedJson, DeserializeTagged)]
+#[action]
 pub struct SaveQuickEditAction {
     pub key: EntityKey,
     pub copy: WorkingCopy,
@@ -305,7 +354,7 @@ impl Action for SaveQuickEditAction {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, ToTaggedJson, DeserializeTagged)]
+#[action]
 pub struct SaveEntityJsonAction {
     pub key: EntityKey,
     pub copy: WorkingCopy,
@@ -329,13 +378,22 @@ impl Action for SaveEntityJsonAction {
         false
     }
 
-    fn perform(&self, session: SessionRef, _surroundings: &Surroundings) -> ReplyResult {
+    fn perform(&self, session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
         info!("save:entity-json {:?}", self.key);
 
         match session.entity(&LookupBy::Key(&self.key))? {
             Some(entity) => match &self.copy {
                 WorkingCopy::Json(value) => {
-                    let replacing = Entity::from_value(value.clone())?;
+                    // The working copy was redacted for the actor, so put back
+                    // anything they were unable to see before saving.
+                    let mut value = value.clone();
+                    let existing = entity.borrow().to_json_value()?;
+                    restore_redacted_entity_json(
+                        &surroundings.actor()?.key(),
+                        &existing,
+                        &mut value,
+                    );
+                    let replacing = Entity::from_value(value)?;
                     entity.replace(replacing);
 
                     Ok(SimpleReply::Done.try_into()?)
@@ -360,7 +418,7 @@ impl Action for BuildAreaAction {
     fn perform(&self, session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
         info!("build-area {:?}", self.name);
 
-        let creator = surroundings.actor();
+        let creator = surroundings.actor()?;
 
         let new_area: Entity = build_entity()
             .area()
@@ -433,3 +491,142 @@ impl Action for ChangeOwnerAction {
         }
     }
 }
+
+#[action]
+pub struct FuturesAction {
+    pub item: Item,
+    pub cancel: Option<String>,
+}
+
+impl Action for FuturesAction {
+    fn is_read_only(&self) -> bool {
+        self.cancel.is_none()
+    }
+
+    fn perform(&self, session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
+        info!("futures {:?} cancel={:?}", self.item, self.cancel);
+
+        let actor = surroundings.actor()?;
+
+        match session.find_item(surroundings, &self.item)? {
+            Some(item) => {
+                let item = item.one()?;
+
+                // Only futures scheduled for this entity may be cancelled here.
+                if let Some(key) = &self.cancel {
+                    let scheduled = session.futures(&item.key())?;
+                    if !scheduled.iter().any(|f| &f.key == key) || !session.cancel(key)? {
+                        return Ok(SimpleReply::NotFound.try_into()?);
+                    }
+                }
+
+                match (&item).observe(actor)? {
+                    Some(entity) => Ok(FuturesReply {
+                        entity,
+                        futures: session.futures(&item.key())?,
+                    }
+                    .try_into()?),
+                    None => Ok(SimpleReply::NotFound.try_into()?),
+                }
+            }
+            None => Ok(SimpleReply::NotFound.try_into()?),
+        }
+    }
+}
+
+#[action]
+pub struct HistoryAction {
+    pub item: Item,
+}
+
+impl Action for HistoryAction {
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
+    fn perform(&self, session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
+        info!("history {:?}", self.item);
+
+        let actor = surroundings.actor()?;
+
+        match session.find_item(surroundings, &self.item)? {
+            Some(item) => {
+                let item = item.one()?;
+                let Some(entity) = (&item).observe(actor)? else {
+                    return Ok(SimpleReply::NotFound.try_into()?);
+                };
+
+                let current = item.to_json_value()?;
+                let versions = session
+                    .versions(&item.key())?
+                    .into_iter()
+                    .map(|v| {
+                        Ok(ObservedVersion {
+                            version: v.version,
+                            differences: entity_json_differences(&v.value, &current)?,
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+
+                Ok(HistoryReply { entity, versions }.try_into()?)
+            }
+            None => Ok(SimpleReply::NotFound.try_into()?),
+        }
+    }
+}
+
+#[action]
+pub struct RevertAction {
+    pub item: Item,
+    pub version: u64,
+}
+
+impl Action for RevertAction {
+    fn is_read_only(&self) -> bool {
+        false
+    }
+
+    fn perform(&self, session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
+        info!("revert {:?} to {}", self.item, self.version);
+
+        match session.find_item(surroundings, &self.item)? {
+            Some(item) => {
+                let item = item.one()?;
+                let Some(reverting) = session
+                    .versions(&item.key())?
+                    .into_iter()
+                    .find(|v| v.version == self.version)
+                else {
+                    return Ok(SimpleReply::NotFound.try_into()?);
+                };
+
+                // Where the entity is and what's with it is also kept on the
+                // entities related to it, so those scopes are left alone.
+                let mut reverted = Entity::from_value(reverting.value)?;
+                {
+                    let current = item.borrow();
+                    for scope_key in [
+                        Containing::scope_key(),
+                        Location::scope_key(),
+                        Occupying::scope_key(),
+                        Occupyable::scope_key(),
+                        Wearing::scope_key(),
+                    ] {
+                        match current.load_scope(scope_key) {
+                            Some(value) => reverted.store_scope(scope_key, value.clone()),
+                            None => {
+                                reverted.remove_scope(scope_key);
+                            }
+                        }
+                    }
+                }
+
+                // Saved as a new version, so reverting can be undone too.
+                item.replace(reverted);
+
+                Ok(SimpleReply::Done.try_into()?)
+            }
+            None => Ok(SimpleReply::NotFound.try_into()?),
+        }
+    }
+}
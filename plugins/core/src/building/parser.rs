@@ -2,7 +2,8 @@ use crate::library::parser::*;
 
 use super::actions::{
     AddScopeAction, BidirectionalDigAction, BuildAreaAction, ChangeOwnerAction, DuplicateAction,
-    EditAction, EditRawAction, LimboAction, MakeItemAction, ObliterateAction,
+    EditAction, EditRawAction, FuturesAction, HistoryAction, InstantiateAction, LimboAction,
+    MakeItemAction, ObliterateAction, RevertAction,
 };
 
 pub struct EditActionParser {}
@@ -53,6 +54,19 @@ impl ParsesActions for DuplicateActionParser {
     }
 }
 
+pub struct InstantiateActionParser {}
+
+impl ParsesActions for InstantiateActionParser {
+    fn try_parse_action(&self, i: &str) -> EvaluationResult {
+        let (_, action) = map(
+            preceded(pair(tag("@instantiate"), spaces), noun_or_specific),
+            |item| InstantiateAction { item },
+        )(i)?;
+
+        Ok(Some(Box::new(action)))
+    }
+}
+
 pub struct LimboActionParser {}
 
 impl ParsesActions for LimboActionParser {
@@ -140,3 +154,54 @@ impl ParsesActions for ChangeOwnerActionParser {
         Ok(Some(Box::new(action)))
     }
 }
+
+pub struct FuturesActionParser {}
+
+impl ParsesActions for FuturesActionParser {
+    fn try_parse_action(&self, i: &str) -> EvaluationResult {
+        let (_, action) = map(
+            pair(
+                preceded(pair(tag("@futures"), spaces), noun_or_specific),
+                opt(preceded(
+                    tuple((spaces, tag("cancel"), spaces)),
+                    text_to_end_of_line,
+                )),
+            ),
+            |(item, cancel)| FuturesAction {
+                item,
+                cancel: cancel.map(|key| key.to_owned()),
+            },
+        )(i)?;
+
+        Ok(Some(Box::new(action)))
+    }
+}
+
+pub struct HistoryActionParser {}
+
+impl ParsesActions for HistoryActionParser {
+    fn try_parse_action(&self, i: &str) -> EvaluationResult {
+        let (_, action) = map(
+            preceded(pair(tag("@history"), spaces), noun_or_specific),
+            |item| HistoryAction { item },
+        )(i)?;
+
+        Ok(Some(Box::new(action)))
+    }
+}
+
+pub struct RevertActionParser {}
+
+impl ParsesActions for RevertActionParser {
+    fn try_parse_action(&self, i: &str) -> EvaluationResult {
+        let (_, action) = map(
+            pair(
+                preceded(pair(tag("@revert"), spaces), noun_or_specific),
+                preceded(spaces, map_res(digit1, |s: &str| s.parse::<u64>())),
+            ),
+            |(item, version)| RevertAction { item, version },
+        )(i)?;
+
+        Ok(Some(Box::new(action)))
+    }
+}
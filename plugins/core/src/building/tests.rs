@@ -10,6 +10,7 @@ use crate::{
     {carrying::model::Containing, looking::model::new_area_observation, tools},
     {BuildSurroundings, QuickThing},
 };
+use engine::prelude::{DevNullNotifier, SessionOpener};
 
 #[test]
 fn it_fails_to_edit_unknown_items() -> Result<()> {
@@ -137,7 +138,7 @@ fn it_duplicates_items_named() -> Result<()> {
     let action = try_parsing(DuplicateActionParser {}, "@duplicate broom")?;
     let action = action.unwrap();
     let reply = action.perform(session.clone(), &surroundings)?;
-    let (_world, person, _area) = surroundings.unpack();
+    let (_world, person, _area) = surroundings.unpack()?;
 
     let reply: SimpleReply = reply.json_as()?;
     assert_eq!(reply, SimpleReply::Done);
@@ -150,6 +151,123 @@ fn it_duplicates_items_named() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn it_duplicates_specified_quantity() -> Result<()> {
+    let mut build = BuildSurroundings::new()?;
+    let (session, surroundings) = build
+        .hands(vec![QuickThing::Multiple("Coin", 2.0)])
+        .build()?;
+
+    let action = try_parsing(DuplicateActionParser {}, "@duplicate 3 coin")?;
+    let action = action.unwrap();
+    let reply = action.perform(session.clone(), &surroundings)?;
+    let (_world, person, _area) = surroundings.unpack()?;
+
+    let reply: SimpleReply = reply.json_as()?;
+    assert_eq!(reply, SimpleReply::Done);
+    assert_eq!(person.scope::<Containing>()?.unwrap().holding.len(), 1);
+    assert_eq!(
+        tools::quantity(&person.scope::<Containing>()?.unwrap().holding[0].to_entity()?)?,
+        5.0
+    );
+
+    Ok(())
+}
+
+#[test]
+fn it_instantiates_prototypes() -> Result<()> {
+    let mut build = BuildSurroundings::new()?;
+    let sword = build
+        .entity()?
+        .named("Iron Sword")?
+        .save()?
+        .carryable()?
+        .into_entity()?;
+    let (session, surroundings) = build
+        .hands(vec![QuickThing::Actual(sword.clone())])
+        .build()?;
+
+    let action = try_parsing(InstantiateActionParser {}, "@instantiate sword")?;
+    let action = action.unwrap();
+    let reply = action.perform(session.clone(), &surroundings)?;
+    let (_world, person, _area) = surroundings.unpack()?;
+
+    let reply: SimpleReply = reply.json_as()?;
+    assert_eq!(reply, SimpleReply::Done);
+
+    let holding = person.scope::<Containing>()?.unwrap().holding.clone();
+    assert_eq!(holding.len(), 2);
+
+    let instance = holding[1].to_entity()?;
+    assert_eq!(instance.name()?, "Iron Sword");
+    assert_eq!(
+        instance.borrow().parent().map(|p| p.key().clone()),
+        Some(sword.key())
+    );
+
+    // Editing the prototype reaches instances once they're loaded again.
+    sword.borrow_mut().set_desc("Heavy and sharp.")?;
+    let instance = instance.key();
+    build.close()?;
+
+    let domain = build.domain().unwrap();
+    let session = domain.open_session()?;
+    let instance = session.entity(&LookupBy::Key(&instance))?.unwrap();
+    assert_eq!(instance.desc()?, Some("Heavy and sharp.".to_owned()));
+
+    // Local changes only override what was changed.
+    instance.borrow_mut().set_name("Rusty Sword")?;
+    let json = instance.borrow().to_json_value()?;
+    assert_eq!(
+        json["scopes"]["props"]["core"]["name"]["value"],
+        "Rusty Sword"
+    );
+    assert!(json["scopes"]["props"]["core"].get("desc").is_none());
+
+    session.close(&DevNullNotifier {})?;
+
+    Ok(())
+}
+
+#[test]
+fn it_only_inherits_inheritable_scopes_from_prototypes() -> Result<()> {
+    let mut build = BuildSurroundings::new()?;
+    let coin = build.make(QuickThing::Object("Coin"))?;
+    let chest = build
+        .entity()?
+        .named("Wooden Chest")?
+        .save()?
+        .carryable()?
+        .holding(&vec![coin])?
+        .into_entity()?;
+    let (session, surroundings) = build
+        .hands(vec![QuickThing::Actual(chest.clone())])
+        .build()?;
+
+    let action = try_parsing(InstantiateActionParser {}, "@instantiate chest")?;
+    let action = action.unwrap();
+    action.perform(session.clone(), &surroundings)?;
+    let (_world, person, _area) = surroundings.unpack()?;
+
+    let holding = person.scope::<Containing>()?.unwrap().holding.clone();
+    let instance = holding[1].to_entity()?.key();
+    build.close()?;
+
+    let domain = build.domain().unwrap();
+    let session = domain.open_session()?;
+    let instance = session.entity(&LookupBy::Key(&instance))?.unwrap();
+    assert_eq!(instance.name()?, "Wooden Chest");
+    assert!(instance.scope::<Containing>()?.is_none());
+    assert!(instance
+        .borrow()
+        .scope_json_for(&person.key(), Containing::scope_key())?
+        .is_none());
+
+    session.close(&DevNullNotifier {})?;
+
+    Ok(())
+}
+
 #[test]
 fn it_fails_to_obliterate_unknown_items() -> Result<()> {
     let mut build = BuildSurroundings::new()?;
@@ -177,7 +295,7 @@ fn it_obliterates_items_named() -> Result<()> {
     let action = try_parsing(ObliterateActionParser {}, "@obliterate broom")?;
     let action = action.unwrap();
     let reply = action.perform(session.clone(), &surroundings)?;
-    let (_world, person, area) = surroundings.unpack();
+    let (_world, person, area) = surroundings.unpack()?;
 
     let reply: SimpleReply = reply.json_as()?;
     assert_eq!(reply, SimpleReply::Done);
@@ -191,6 +309,98 @@ fn it_obliterates_items_named() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn it_obliterates_specified_quantity() -> Result<()> {
+    let mut build = BuildSurroundings::new()?;
+    let (session, surroundings) = build
+        .hands(vec![QuickThing::Multiple("Coin", 5.0)])
+        .build()?;
+
+    let action = try_parsing(ObliterateActionParser {}, "@obliterate 2 coin")?;
+    let action = action.unwrap();
+    let reply = action.perform(session.clone(), &surroundings)?;
+    let (_world, person, _area) = surroundings.unpack()?;
+
+    let reply: SimpleReply = reply.json_as()?;
+    assert_eq!(reply, SimpleReply::Done);
+    assert_eq!(person.scope::<Containing>()?.unwrap().holding.len(), 1);
+    assert_eq!(
+        tools::quantity(&person.scope::<Containing>()?.unwrap().holding[0].to_entity()?)?,
+        3.0
+    );
+
+    build.flush()?;
+
+    Ok(())
+}
+
+#[test]
+fn it_fails_to_obliterate_more_than_available() -> Result<()> {
+    let mut build = BuildSurroundings::new()?;
+    let (session, surroundings) = build
+        .hands(vec![QuickThing::Multiple("Coin", 2.0)])
+        .build()?;
+
+    let action = try_parsing(ObliterateActionParser {}, "@obliterate 3 coin")?;
+    let action = action.unwrap();
+    let reply = action.perform(session.clone(), &surroundings)?;
+    let (_world, person, _area) = surroundings.unpack()?;
+
+    let reply: SimpleReply = reply.json_as()?;
+    assert_eq!(reply, SimpleReply::NotFound);
+    assert_eq!(
+        tools::quantity(&person.scope::<Containing>()?.unwrap().holding[0].to_entity()?)?,
+        2.0
+    );
+
+    Ok(())
+}
+
+#[test]
+fn it_obliterates_items_without_an_actor() -> Result<()> {
+    let mut build = BuildSurroundings::new()?;
+    let (session, surroundings) = build
+        .ground(vec![QuickThing::Object("Cool Broom")])
+        .build()?;
+    let (world, _person, area) = surroundings.unpack()?;
+    let surroundings = Surroundings::Area {
+        world,
+        area: area.clone(),
+    };
+
+    let action = try_parsing(ObliterateActionParser {}, "@obliterate broom")?;
+    let action = action.unwrap();
+    let reply = action.perform(session.clone(), &surroundings)?;
+
+    let reply: SimpleReply = reply.json_as()?;
+    assert_eq!(reply, SimpleReply::Done);
+    assert_eq!(area.scope::<Containing>()?.unwrap().holding.len(), 0);
+
+    build.flush()?;
+
+    Ok(())
+}
+
+#[test]
+fn it_requires_an_actor_to_make_items() -> Result<()> {
+    let mut build = BuildSurroundings::new()?;
+    let (session, surroundings) = build.plain().build()?;
+    let surroundings = Surroundings::World {
+        world: surroundings.world().clone(),
+    };
+
+    let action = try_parsing(MakeItemParser {}, r#"@make item "Blue Rake""#)?;
+    let action = action.unwrap();
+    let reply = action.perform(session.clone(), &surroundings);
+
+    assert!(matches!(
+        reply.unwrap_err().downcast_ref::<DomainError>(),
+        Some(DomainError::ActorRequired)
+    ));
+
+    Ok(())
+}
+
 #[test]
 fn it_digs_bidirectionally() -> Result<()> {
     let mut build = BuildSurroundings::new()?;
@@ -202,7 +412,7 @@ fn it_digs_bidirectionally() -> Result<()> {
     )?;
     let action = action.unwrap();
     let reply = action.perform(session.clone(), &surroundings)?;
-    let (_, actor, _area) = surroundings.unpack();
+    let (_, actor, _area) = surroundings.unpack()?;
 
     // Not the best way of finding the constructed area.
     let destination = session
@@ -223,7 +433,7 @@ fn it_makes_items() -> Result<()> {
     let action = try_parsing(MakeItemParser {}, r#"@make item "Blue Rake""#)?;
     let action = action.unwrap();
     let reply = action.perform(session.clone(), &surroundings)?;
-    let (_, actor, _area) = surroundings.unpack();
+    let (_, actor, _area) = surroundings.unpack()?;
 
     let reply: SimpleReply = reply.json_as()?;
     assert_eq!(reply, SimpleReply::Done);
@@ -237,7 +447,7 @@ fn it_makes_items() -> Result<()> {
 fn it_saves_changes_to_description() -> Result<()> {
     let mut build = BuildSurroundings::new()?;
     let (session, surroundings) = build.plain().build()?;
-    let (_world, _actor, _area) = surroundings.unpack();
+    let (_world, _actor, _area) = surroundings.unpack()?;
 
     let description = "Would be really weird if this was the original description".to_owned();
     let mut quick_edit = QuickEdit::default();
@@ -249,7 +459,7 @@ fn it_saves_changes_to_description() -> Result<()> {
         copy: WorkingCopy::Markdown(quick_edit.to_string()),
     });
     let reply = action.perform(session.clone(), &surroundings)?;
-    let (world, _actor, _area) = surroundings.unpack();
+    let (world, _actor, _area) = surroundings.unpack()?;
 
     let reply: SimpleReply = reply.json_as()?;
     assert_eq!(reply, SimpleReply::Done);
@@ -263,7 +473,7 @@ fn it_saves_changes_to_description() -> Result<()> {
 fn it_saves_changes_to_whole_entities() -> Result<()> {
     let mut build = BuildSurroundings::new()?;
     let (session, surroundings) = build.plain().build()?;
-    let (_, actor, area) = surroundings.unpack();
+    let (_, actor, area) = surroundings.unpack()?;
 
     let original = actor.borrow().to_json_value()?;
 
@@ -272,7 +482,7 @@ fn it_saves_changes_to_whole_entities() -> Result<()> {
         copy: WorkingCopy::Json(original),
     });
     let reply = action.perform(session.clone(), &surroundings)?;
-    let (_, _actor, _area) = surroundings.unpack();
+    let (_, _actor, _area) = surroundings.unpack()?;
 
     let reply: SimpleReply = reply.json_as()?;
     assert_eq!(reply, SimpleReply::Done);
@@ -302,7 +512,7 @@ fn it_adds_scopes_to_solo_held_items() -> Result<()> {
 
     let action = try_parsing(ScopeActionParser {}, r#"@scope wearable"#)?.unwrap();
     let reply = action.perform(session.clone(), &surroundings)?;
-    let (_, actor, _area) = surroundings.unpack();
+    let (_, actor, _area) = surroundings.unpack()?;
 
     let reply: SimpleReply = reply.json_as()?;
     assert_eq!(reply, SimpleReply::Done);
@@ -322,9 +532,155 @@ fn it_builds_areas() -> Result<()> {
     let action = try_parsing(BuildAreaParser {}, r#"@build "Green Room""#)?;
     let action = action.unwrap();
     let reply = action.perform(session.clone(), &surroundings)?;
-    let (_, _actor, _area) = surroundings.unpack();
+    let (_, _actor, _area) = surroundings.unpack()?;
 
     insta::assert_json_snapshot!(reply.to_debug_json()?);
 
     Ok(())
 }
+
+#[test]
+fn it_lists_and_cancels_futures_of_items() -> Result<()> {
+    let mut build = BuildSurroundings::new()?;
+    let (session, surroundings) = build
+        .ground(vec![QuickThing::Object("Cool Broom")])
+        .build()?;
+
+    let (_, _, area) = surroundings.unpack()?;
+    let broom = area.scope::<Containing>()?.unwrap().holding[0].to_entity()?;
+    let now = chrono::Utc::now();
+    for key in ["sweep-1", "sweep-2"] {
+        session.schedule(FutureAction::new(
+            key.to_owned(),
+            broom.key().clone(),
+            FutureSchedule::Utc(now),
+            TaggedJson::new("sweep".to_owned(), serde_json::json!({}).into()),
+        ))?;
+    }
+
+    build.flush()?;
+
+    let action = try_parsing(FuturesActionParser {}, "@futures broom")?;
+    let reply = action.unwrap().perform(session.clone(), &surroundings)?;
+    let reply: FuturesReply = reply.json_as()?;
+    assert_eq!(
+        reply
+            .futures
+            .iter()
+            .map(|f| f.key.as_str())
+            .collect::<Vec<_>>(),
+        vec!["sweep-1", "sweep-2"]
+    );
+
+    let action = try_parsing(FuturesActionParser {}, "@futures broom cancel sweep-1")?;
+    let reply = action.unwrap().perform(session.clone(), &surroundings)?;
+    let reply: FuturesReply = reply.json_as()?;
+    assert_eq!(
+        reply
+            .futures
+            .iter()
+            .map(|f| f.key.as_str())
+            .collect::<Vec<_>>(),
+        vec!["sweep-2"]
+    );
+
+    let action = try_parsing(FuturesActionParser {}, "@futures broom cancel sweep-1")?;
+    let reply = action.unwrap().perform(session, &surroundings)?;
+    let reply: SimpleReply = reply.json_as()?;
+    assert_eq!(reply, SimpleReply::NotFound);
+
+    build.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn it_reverts_items_to_previous_versions() -> Result<()> {
+    let mut build = BuildSurroundings::new()?;
+    let (session, surroundings) = build
+        .ground(vec![QuickThing::Object("Cool Broom")])
+        .build()?;
+
+    let (_, _, area) = surroundings.unpack()?;
+    let broom = area.scope::<Containing>()?.unwrap().holding[0].to_entity()?;
+    broom.borrow_mut().set_desc("Sweeps well.")?;
+    build.flush()?;
+
+    broom.borrow_mut().set_desc("Oops.")?;
+    build.flush()?;
+
+    let action = try_parsing(HistoryActionParser {}, "@history broom")?;
+    let reply = action.unwrap().perform(session.clone(), &surroundings)?;
+    let reply: HistoryReply = reply.json_as()?;
+    let previous = reply.versions.first().unwrap();
+    assert!(previous
+        .differences
+        .iter()
+        .any(|p| p.ends_with("desc.value")));
+
+    let revert = format!("@revert broom {}", previous.version);
+    let action = try_parsing(RevertActionParser {}, &revert)?;
+    let reply = action.unwrap().perform(session.clone(), &surroundings)?;
+    let reply: SimpleReply = reply.json_as()?;
+    assert_eq!(reply, SimpleReply::Done);
+
+    let broom = session.entity(&LookupBy::Key(&broom.key()))?.unwrap();
+    assert_eq!(broom.desc()?, Some("Sweeps well.".to_owned()));
+
+    build.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn it_keeps_what_reverted_items_contain() -> Result<()> {
+    let mut build = BuildSurroundings::new()?;
+    let key = build
+        .entity()?
+        .named("Key")?
+        .save()?
+        .carryable()?
+        .into_entity()?;
+    let vessel = build
+        .entity()?
+        .named("Vessel")?
+        .save()?
+        .carryable()?
+        .holding(&vec![])?
+        .into_entity()?;
+    let (session, surroundings) = build
+        .hands(vec![
+            QuickThing::Actual(key.clone()),
+            QuickThing::Actual(vessel.clone()),
+        ])
+        .build()?;
+
+    vessel.borrow_mut().set_desc("Sturdy.")?;
+    build.flush()?;
+
+    let (_, person, _) = surroundings.unpack()?;
+    vessel.borrow_mut().set_desc("Oops.")?;
+    tools::move_between(&person, &vessel, key.clone().into())?;
+    build.flush()?;
+
+    let action = try_parsing(HistoryActionParser {}, "@history vessel")?;
+    let reply = action.unwrap().perform(session.clone(), &surroundings)?;
+    let reply: HistoryReply = reply.json_as()?;
+    let previous = reply.versions.first().unwrap();
+
+    let revert = format!("@revert vessel {}", previous.version);
+    let action = try_parsing(RevertActionParser {}, &revert)?;
+    let reply = action.unwrap().perform(session.clone(), &surroundings)?;
+    let reply: SimpleReply = reply.json_as()?;
+    assert_eq!(reply, SimpleReply::Done);
+
+    let vessel = session.entity(&LookupBy::Key(&vessel.key()))?.unwrap();
+    assert_eq!(vessel.desc()?, Some("Sturdy.".to_owned()));
+    let holding = &vessel.scope::<Containing>()?.unwrap().holding;
+    assert_eq!(holding.len(), 1);
+    assert_eq!(holding[0].key(), &key.key());
+
+    build.close()?;
+
+    Ok(())
+}
@@ -17,6 +17,10 @@ impl PluginFactory for CarryingPluginFactory {
     fn stop(&self) -> Result<()> {
         Ok(())
     }
+
+    fn register_upgrades(&self, upgrades: &mut ScopeUpgrades) {
+        upgrades.register::<model::Carryable>();
+    }
 }
 
 #[derive(Default)]
@@ -1,6 +1,9 @@
-use crate::{carrying::model::Carrying, library::actions::*, looking::model::Observe};
+use crate::{
+    carrying::model::Carrying, finding::Referenced, library::actions::*,
+    locking::model::container_preventing, looking::model::Observe,
+};
 
-#[action]
+#[action("HOLD #unheld")]
 pub struct HoldAction {
     pub item: Item,
 }
@@ -13,29 +16,43 @@ impl Action for HoldAction {
     fn perform(&self, session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
         info!("hold {:?}!", self.item);
 
-        let (_, actor, area) = surroundings.unpack();
+        let (_, actor, area) = surroundings.unpack()?;
 
         match session.find_item(surroundings, &self.item)? {
-            Some(holding) => match tools::move_between(&area, &actor, holding.clone())? {
-                true => Ok(reply_ok(
-                    actor.clone(),
-                    Audience::Area(area.key().clone()),
-                    Carrying::Held {
-                        actor: (&actor).observe(&actor)?.expect("No observed entity"),
-                        item: (&holding.entity()?)
-                            .observe(&actor)?
-                            .expect("No observed entity"),
-                        area: (&area).observe(&actor)?.expect("No observed entity"),
-                    },
-                )?),
-                false => Ok(SimpleReply::NotFound.try_into()?),
-            },
+            Some(found) => {
+                let mut held = false;
+                for holding in found.clone().many() {
+                    if tools::move_between(&area, &actor, holding.clone())? {
+                        reply_ok(
+                            actor.clone(),
+                            Audience::Area(area.key().clone()),
+                            Carrying::Held {
+                                actor: (&actor).observe(&actor)?.expect("No observed entity"),
+                                item: (&holding.entity()?)
+                                    .observe(&actor)?
+                                    .expect("No observed entity"),
+                                area: (&area).observe(&actor)?.expect("No observed entity"),
+                            },
+                        )?;
+                        held = true;
+                    }
+                }
+
+                match held {
+                    true => {
+                        Referenced::remember(&actor, &found)?;
+
+                        Ok(Effect::Ok)
+                    }
+                    false => Ok(SimpleReply::NotFound.try_into()?),
+                }
+            }
             None => Ok(SimpleReply::NotFound.try_into()?),
         }
     }
 }
 
-#[action]
+#[action("DROP (#held)?")]
 pub struct DropAction {
     pub maybe_item: Option<Item>,
 }
@@ -48,24 +65,38 @@ impl Action for DropAction {
     fn perform(&self, session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
         info!("drop {:?}!", self.maybe_item);
 
-        let (_, actor, area) = surroundings.unpack();
+        let (_, actor, area) = surroundings.unpack()?;
 
         match &self.maybe_item {
             Some(item) => match session.find_item(surroundings, item)? {
-                Some(dropping) => match tools::move_between(&actor, &area, dropping.clone())? {
-                    true => Ok(reply_ok(
-                        actor.clone(),
-                        Audience::Area(area.key().clone()),
-                        Carrying::Dropped {
-                            actor: (&actor).observe(&actor)?.expect("No observed entity"),
-                            item: (&dropping.entity()?)
-                                .observe(&actor)?
-                                .expect("No observed entity"),
-                            area: (&area).observe(&actor)?.expect("No observed entity"),
-                        },
-                    )?),
-                    false => Ok(SimpleReply::NotFound.try_into()?),
-                },
+                Some(found) => {
+                    let mut dropped = false;
+                    for dropping in found.clone().many() {
+                        if tools::move_between(&actor, &area, dropping.clone())? {
+                            reply_ok(
+                                actor.clone(),
+                                Audience::Area(area.key().clone()),
+                                Carrying::Dropped {
+                                    actor: (&actor).observe(&actor)?.expect("No observed entity"),
+                                    item: (&dropping.entity()?)
+                                        .observe(&actor)?
+                                        .expect("No observed entity"),
+                                    area: (&area).observe(&actor)?.expect("No observed entity"),
+                                },
+                            )?;
+                            dropped = true;
+                        }
+                    }
+
+                    match dropped {
+                        true => {
+                            Referenced::remember(&actor, &found)?;
+
+                            Ok(Effect::Ok)
+                        }
+                        false => Ok(SimpleReply::NotFound.try_into()?),
+                    }
+                }
                 None => Ok(SimpleReply::NotFound.try_into()?),
             },
             None => Ok(SimpleReply::NotFound.try_into()?),
@@ -73,7 +104,7 @@ impl Action for DropAction {
     }
 }
 
-#[action]
+#[action("PUT #held (INSIDE (OF)?|IN) #unheld")]
 pub struct PutInsideAction {
     pub item: Item,
     pub vessel: Item,
@@ -87,16 +118,31 @@ impl Action for PutInsideAction {
     fn perform(&self, session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
         info!("put-inside {:?} -> {:?}", self.item, self.vessel);
 
-        let (_, _user, _area) = surroundings.unpack();
+        let (_, user, _area) = surroundings.unpack()?;
 
         match session.find_item(surroundings, &self.item)? {
-            Some(item) => match session.find_item(surroundings, &self.vessel)? {
+            Some(found) => match session.find_item(surroundings, &self.vessel)? {
                 Some(vessel) => {
                     let vessel = vessel.one()?;
                     if tools::is_container(&vessel)? {
-                        let from = tools::container_of(&item.clone().one()?)?;
-                        match tools::move_between(&from, &vessel, item)? {
-                            true => Ok(SimpleReply::Done.try_into()?),
+                        if let Some(reason) = container_preventing(&vessel)? {
+                            return Ok(SimpleReply::Prevented(Some(reason)).try_into()?);
+                        }
+
+                        let mut moved = false;
+                        for item in found.clone().many() {
+                            let from = tools::container_of(item.entity()?)?;
+                            if tools::move_between(&from, &vessel, item)? {
+                                moved = true;
+                            }
+                        }
+
+                        match moved {
+                            true => {
+                                Referenced::remember(&user, &found)?;
+
+                                Ok(SimpleReply::Done.try_into()?)
+                            }
                             false => Ok(SimpleReply::NotFound.try_into()?),
                         }
                     } else {
@@ -110,7 +156,7 @@ impl Action for PutInsideAction {
     }
 }
 
-#[action]
+#[action("TAKE #contained (OUT OF|FROM) #unheld")]
 pub struct TakeOutAction {
     pub item: Item,
     pub vessel: Item,
@@ -124,15 +170,44 @@ impl Action for TakeOutAction {
     fn perform(&self, session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
         info!("take-out {:?} -> {:?}", self.item, self.vessel);
 
-        let (_, user, _area) = surroundings.unpack();
+        let (_, user, _area) = surroundings.unpack()?;
 
         match session.find_item(surroundings, &self.vessel)? {
             Some(vessel) => {
                 let vessel = vessel.one()?;
                 if tools::is_container(&vessel)? {
+                    if let Some(reason) = container_preventing(&vessel)? {
+                        return Ok(SimpleReply::Prevented(Some(reason)).try_into()?);
+                    }
+
                     match session.find_item(surroundings, &self.item)? {
-                        Some(item) => match tools::move_between(&vessel, &user, item)? {
-                            true => Ok(SimpleReply::Done.try_into()?),
+                        Some(Found::Many(many)) => {
+                            let mut taken = Vec::new();
+                            for item in many {
+                                // Everything contained nearby is found, so
+                                // only take what's inside of this vessel.
+                                if tools::container_of(&item)?.key() == vessel.key()
+                                    && tools::move_between(&vessel, &user, item.clone().into())?
+                                {
+                                    taken.push(item);
+                                }
+                            }
+
+                            match taken.is_empty() {
+                                false => {
+                                    Referenced::remember(&user, &Found::Many(taken))?;
+
+                                    Ok(SimpleReply::Done.try_into()?)
+                                }
+                                true => Ok(SimpleReply::NotFound.try_into()?),
+                            }
+                        }
+                        Some(item) => match tools::move_between(&vessel, &user, item.clone())? {
+                            true => {
+                                Referenced::remember(&user, &item)?;
+
+                                Ok(SimpleReply::Done.try_into()?)
+                            }
                             false => Ok(SimpleReply::NotFound.try_into()?),
                         },
                         None => Ok(SimpleReply::NotFound.try_into()?),
@@ -146,7 +221,7 @@ impl Action for TakeOutAction {
     }
 }
 
-#[action]
+#[action("GIVE #held TO #unheld")]
 pub struct GiveToAction {
     pub item: Item,
     pub receiver: Item,
@@ -160,17 +235,44 @@ impl Action for GiveToAction {
     fn perform(&self, session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
         info!("give-to {:?} -> {:?}", self.item, self.receiver);
 
-        let (_, user, _area) = surroundings.unpack();
+        let (_, user, _area) = surroundings.unpack()?;
 
         // I think there are very interesting permission related implications
         // here. For example, limiting third party access to your hands except
         // for key individuals.
         match session.find_item(surroundings, &self.item)? {
-            Some(item) => match session.find_item(surroundings, &self.receiver)? {
-                Some(receiver) => match tools::move_between(&user, &receiver.one()?, item)? {
-                    true => Ok(SimpleReply::Done.try_into()?),
-                    false => Ok(SimpleReply::NotFound.try_into()?),
-                },
+            Some(found) => match session.find_item(surroundings, &self.receiver)? {
+                Some(receiver) => {
+                    let receiver = receiver.one()?;
+                    let mut given = false;
+                    for item in found.clone().many() {
+                        if tools::move_between(&user, &receiver, item.clone())? {
+                            reply_ok(
+                                user.clone(),
+                                Audience::Individuals(vec![receiver.key().clone()]),
+                                Carrying::Given {
+                                    actor: (&user).observe(&receiver)?.expect("No observed entity"),
+                                    item: (&item.entity()?)
+                                        .observe(&receiver)?
+                                        .expect("No observed entity"),
+                                    receiver: (&receiver)
+                                        .observe(&receiver)?
+                                        .expect("No observed entity"),
+                                },
+                            )?;
+                            given = true;
+                        }
+                    }
+
+                    match given {
+                        true => {
+                            Referenced::remember(&user, &found)?;
+
+                            Ok(SimpleReply::Done.try_into()?)
+                        }
+                        false => Ok(SimpleReply::NotFound.try_into()?),
+                    }
+                }
                 None => Ok(SimpleReply::NotFound.try_into()?),
             },
             None => Ok(SimpleReply::NotFound.try_into()?),
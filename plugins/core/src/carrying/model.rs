@@ -74,33 +74,14 @@ impl Containing {
             return Ok(None);
         }
 
-        if let Some(carryable) = item.scope::<Carryable>()? {
-            match &found {
-                Found::One(item) => {
-                    if carryable.quantity > 1.0 {
-                        let (_original, separated) = tools::separate(item, &1.0.into())?;
-
-                        Ok(Some(separated))
-                    } else {
-                        self.remove_item(item)?;
+        match tools::portion(&found)? {
+            Some(tools::Portion::Whole(item)) => {
+                self.remove_item(&item)?;
 
-                        Ok(Some(item.clone()))
-                    }
-                }
-                Found::Quantified(q, entity) => {
-                    if carryable.quantity > q.as_f32() {
-                        let (_original, separated) = tools::separate(entity, q)?;
-
-                        Ok(Some(separated))
-                    } else {
-                        Ok(None)
-                    }
-                }
+                Ok(Some(item))
             }
-        } else {
-            self.remove_item(item)?;
-
-            Ok(Some(item.clone()))
+            Some(tools::Portion::Separated(separated)) => Ok(Some(separated)),
+            None => Ok(None),
         }
     }
 }
@@ -180,4 +161,8 @@ impl Scope for Carryable {
     fn scope_key() -> &'static str {
         "carryable"
     }
+
+    fn inherited() -> bool {
+        true
+    }
 }
@@ -11,7 +11,7 @@ fn it_holds_unheld_items() -> Result<()> {
         .ground(vec![QuickThing::Object("Cool Rake")])
         .build()?;
 
-    let (_, person, area) = surroundings.unpack();
+    let (_, person, area) = surroundings.unpack()?;
     assert_eq!(person.scope::<Containing>()?.unwrap().holding.len(), 0);
     assert_eq!(area.scope::<Containing>()?.unwrap().holding.len(), 1);
 
@@ -36,7 +36,7 @@ fn it_separates_multiple_ground_items_when_held() -> Result<()> {
         .ground(vec![QuickThing::Multiple("Cool Rake", 2.0)])
         .build()?;
 
-    let (_, person, area) = surroundings.unpack();
+    let (_, person, area) = surroundings.unpack()?;
     assert_eq!(person.scope::<Containing>()?.unwrap().holding.len(), 0);
     assert_eq!(area.scope::<Containing>()?.unwrap().holding.len(), 1);
 
@@ -72,7 +72,7 @@ fn it_combines_multiple_items_when_together_on_ground() -> Result<()> {
         .hands(vec![QuickThing::Actual(second)])
         .build()?;
 
-    let (_, person, area) = surroundings.unpack();
+    let (_, person, area) = surroundings.unpack()?;
     assert_eq!(person.scope::<Containing>()?.unwrap().holding.len(), 1);
     assert_eq!(area.scope::<Containing>()?.unwrap().holding.len(), 1);
 
@@ -100,7 +100,7 @@ fn it_fails_to_hold_unknown_items() -> Result<()> {
     let action = try_parsing(HoldActionParser {}, "hold rake")?;
     let action = action.unwrap();
     let reply = action.perform(session.clone(), &surroundings)?;
-    let (_, person, area) = surroundings.unpack();
+    let (_, person, area) = surroundings.unpack()?;
 
     let reply: SimpleReply = reply.json_as()?;
     assert_eq!(reply, SimpleReply::NotFound);
@@ -124,7 +124,7 @@ fn it_drops_held_items() -> Result<()> {
 
     assert_eq!(effect, Effect::Ok);
 
-    let (_, person, area) = surroundings.unpack();
+    let (_, person, area) = surroundings.unpack()?;
 
     assert_eq!(person.scope::<Containing>()?.unwrap().holding.len(), 0);
     assert_eq!(area.scope::<Containing>()?.unwrap().holding.len(), 1);
@@ -144,7 +144,7 @@ fn it_fails_to_drop_unknown_items() -> Result<()> {
     let action = try_parsing(DropActionParser {}, "drop rake")?;
     let action = action.unwrap();
     let reply = action.perform(session.clone(), &surroundings)?;
-    let (_, person, area) = surroundings.unpack();
+    let (_, person, area) = surroundings.unpack()?;
 
     let reply: SimpleReply = reply.json_as()?;
     assert_eq!(reply, SimpleReply::NotFound);
@@ -167,7 +167,7 @@ fn it_fails_to_drop_unheld_items() -> Result<()> {
     let action = try_parsing(DropActionParser {}, "drop rake")?;
     let action = action.unwrap();
     let reply = action.perform(session.clone(), &surroundings)?;
-    let (_, person, area) = surroundings.unpack();
+    let (_, person, area) = surroundings.unpack()?;
 
     let reply: SimpleReply = reply.json_as()?;
     assert_eq!(reply, SimpleReply::NotFound);
@@ -199,7 +199,7 @@ fn it_fails_to_put_item_in_non_containers() -> Result<()> {
     let action = try_parsing(PutInsideActionParser {}, "put key inside vessel")?;
     let action = action.unwrap();
     let reply = action.perform(session.clone(), &surroundings)?;
-    let (_world, person, _area) = surroundings.unpack();
+    let (_world, person, _area) = surroundings.unpack()?;
 
     insta::assert_json_snapshot!(reply.to_debug_json()?);
 
@@ -231,7 +231,7 @@ fn it_puts_items_in_containers() -> Result<()> {
     let action = try_parsing(PutInsideActionParser {}, "put key inside vessel")?;
     let action = action.unwrap();
     let reply = action.perform(session.clone(), &surroundings)?;
-    let (_world, person, _area) = surroundings.unpack();
+    let (_world, person, _area) = surroundings.unpack()?;
 
     insta::assert_json_snapshot!(reply.to_debug_json()?);
 
@@ -243,6 +243,38 @@ fn it_puts_items_in_containers() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn it_puts_items_named_by_several_words_in_containers() -> Result<()> {
+    let mut build = BuildSurroundings::new()?;
+    let vessel = build
+        .entity()?
+        .named("Wooden Box")?
+        .save()?
+        .carryable()?
+        .holding(&vec![])?
+        .into_entity()?;
+    let (session, surroundings) = build
+        .hands(vec![
+            QuickThing::Object("Brass Key"),
+            QuickThing::Actual(vessel.clone()),
+        ])
+        .build()?;
+
+    let action = try_parsing(PutInsideActionParser {}, "put brass key in wooden box")?;
+    let action = action.unwrap();
+    let reply = action.perform(session.clone(), &surroundings)?;
+    let (_world, person, _area) = surroundings.unpack()?;
+
+    assert_eq!(reply, SimpleReply::Done.try_into()?);
+
+    assert_eq!(person.scope::<Containing>()?.unwrap().holding.len(), 1);
+    assert_eq!(vessel.scope::<Containing>()?.unwrap().holding.len(), 1);
+
+    build.close()?;
+
+    Ok(())
+}
+
 #[test]
 fn it_takes_items_out_of_containers() -> Result<()> {
     let mut build = BuildSurroundings::new()?;
@@ -266,7 +298,7 @@ fn it_takes_items_out_of_containers() -> Result<()> {
     let action = try_parsing(TakeOutActionParser {}, "take key out of vessel")?;
     let action = action.unwrap();
     let reply = action.perform(session.clone(), &surroundings)?;
-    let (_world, person, _area) = surroundings.unpack();
+    let (_world, person, _area) = surroundings.unpack()?;
 
     insta::assert_json_snapshot!(reply.to_debug_json()?);
 
@@ -299,7 +331,7 @@ fn it_gives_items_to_others() -> Result<()> {
     let action = try_parsing(GiveToActionParser {}, "give key to Carla")?;
     let action = action.unwrap();
     let reply = action.perform(session.clone(), &surroundings)?;
-    let (_world, person, _area) = surroundings.unpack();
+    let (_world, person, _area) = surroundings.unpack()?;
 
     assert_eq!(person.scope::<Containing>()?.unwrap().holding.len(), 0);
     assert_eq!(carla.scope::<Containing>()?.unwrap().holding.len(), 1);
@@ -324,7 +356,7 @@ fn it_drops_quantified_items() -> Result<()> {
 
     assert_eq!(effect, Effect::Ok);
 
-    let (_, person, area) = surroundings.unpack();
+    let (_, person, area) = surroundings.unpack()?;
 
     assert_eq!(person.scope::<Containing>()?.unwrap().holding.len(), 1);
     assert_eq!(area.scope::<Containing>()?.unwrap().holding.len(), 1);
@@ -348,7 +380,7 @@ fn it_fails_to_drop_when_available_quantity_insufficient() -> Result<()> {
     let reply: SimpleReply = effect.json_as()?;
     assert_eq!(reply, SimpleReply::NotFound);
 
-    let (_, person, area) = surroundings.unpack();
+    let (_, person, area) = surroundings.unpack()?;
 
     assert_eq!(person.scope::<Containing>()?.unwrap().holding.len(), 1);
     assert_eq!(area.scope::<Containing>()?.unwrap().holding.len(), 0);
@@ -365,7 +397,7 @@ fn it_drops_specified_quantity() -> Result<()> {
         .hands(vec![QuickThing::Multiple("Coin", 4.0)])
         .build()?;
 
-    let (_, person, _) = surroundings.unpack();
+    let (_, person, _) = surroundings.unpack()?;
 
     assert_eq!(
         person.scope::<Containing>()?.unwrap().holding[0]
@@ -382,7 +414,7 @@ fn it_drops_specified_quantity() -> Result<()> {
 
     assert_eq!(effect, Effect::Ok);
 
-    let (_, person, area) = surroundings.unpack();
+    let (_, person, area) = surroundings.unpack()?;
 
     assert_eq!(person.scope::<Containing>()?.unwrap().holding.len(), 1);
     assert_eq!(area.scope::<Containing>()?.unwrap().holding.len(), 1);
@@ -423,7 +455,7 @@ fn it_fails_to_hold_when_available_quantity_insufficient() -> Result<()> {
     let reply: SimpleReply = effect.json_as()?;
     assert_eq!(reply, SimpleReply::NotFound);
 
-    let (_, person, area) = surroundings.unpack();
+    let (_, person, area) = surroundings.unpack()?;
 
     assert_eq!(person.scope::<Containing>()?.unwrap().holding.len(), 0);
     assert_eq!(area.scope::<Containing>()?.unwrap().holding.len(), 1);
@@ -440,7 +472,7 @@ fn it_holds_specified_quantity() -> Result<()> {
         .ground(vec![QuickThing::Multiple("Coin", 4.0)])
         .build()?;
 
-    let (_, _, area) = surroundings.unpack();
+    let (_, _, area) = surroundings.unpack()?;
 
     assert_eq!(
         area.scope::<Containing>()?.unwrap().holding[0]
@@ -457,7 +489,7 @@ fn it_holds_specified_quantity() -> Result<()> {
 
     assert_eq!(effect, Effect::Ok);
 
-    let (_, person, area) = surroundings.unpack();
+    let (_, person, area) = surroundings.unpack()?;
 
     assert_eq!(person.scope::<Containing>()?.unwrap().holding.len(), 1);
     assert_eq!(area.scope::<Containing>()?.unwrap().holding.len(), 1);
@@ -484,6 +516,139 @@ fn it_holds_specified_quantity() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn it_holds_entire_quantity() -> Result<()> {
+    let mut build = BuildSurroundings::new()?;
+    let (session, surroundings) = build
+        .ground(vec![QuickThing::Multiple("Coin", 4.0)])
+        .build()?;
+
+    let action = try_parsing(HoldActionParser {}, "hold 4 coin")?;
+    let action = action.unwrap();
+    let effect = action.perform(session.clone(), &surroundings)?;
+
+    assert_eq!(effect, Effect::Ok);
+
+    let (_, person, area) = surroundings.unpack()?;
+
+    assert_eq!(person.scope::<Containing>()?.unwrap().holding.len(), 1);
+    assert_eq!(area.scope::<Containing>()?.unwrap().holding.len(), 0);
+
+    assert_eq!(
+        tools::quantity(&person.scope::<Containing>()?.unwrap().holding[0].to_entity()?)?,
+        4.0
+    );
+
+    build.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn it_gives_specified_quantity_to_others() -> Result<()> {
+    let mut build = BuildSurroundings::new()?;
+    let carla = build.with(build_entity().living().name("Carla").try_into()?)?;
+    let (session, surroundings) = build
+        .hands(vec![QuickThing::Multiple("Coin", 4.0)])
+        .occupying(vec![QuickThing::Actual(carla.clone())])
+        .build()?;
+
+    let action = try_parsing(GiveToActionParser {}, "give 3 coin to Carla")?;
+    let action = action.unwrap();
+    let reply = action.perform(session.clone(), &surroundings)?;
+    let (_world, person, _area) = surroundings.unpack()?;
+
+    let reply: SimpleReply = reply.json_as()?;
+    assert_eq!(reply, SimpleReply::Done);
+
+    assert_eq!(
+        tools::quantity(&person.scope::<Containing>()?.unwrap().holding[0].to_entity()?)?,
+        1.0
+    );
+    assert_eq!(
+        tools::quantity(&carla.scope::<Containing>()?.unwrap().holding[0].to_entity()?)?,
+        3.0
+    );
+
+    build.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn it_puts_specified_quantity_in_containers() -> Result<()> {
+    let mut build = BuildSurroundings::new()?;
+    let vessel = build
+        .entity()?
+        .named("Vessel")?
+        .save()?
+        .carryable()?
+        .holding(&vec![])?
+        .into_entity()?;
+    let (session, surroundings) = build
+        .hands(vec![
+            QuickThing::Multiple("Coin", 4.0),
+            QuickThing::Actual(vessel.clone()),
+        ])
+        .build()?;
+
+    let action = try_parsing(PutInsideActionParser {}, "put 2 coin inside vessel")?;
+    let action = action.unwrap();
+    let reply = action.perform(session.clone(), &surroundings)?;
+    let (_world, person, _area) = surroundings.unpack()?;
+
+    let reply: SimpleReply = reply.json_as()?;
+    assert_eq!(reply, SimpleReply::Done);
+
+    assert_eq!(person.scope::<Containing>()?.unwrap().holding.len(), 2);
+    assert_eq!(vessel.scope::<Containing>()?.unwrap().holding.len(), 1);
+    assert_eq!(
+        tools::quantity(&vessel.scope::<Containing>()?.unwrap().holding[0].to_entity()?)?,
+        2.0
+    );
+
+    build.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn it_takes_specified_quantity_out_of_containers() -> Result<()> {
+    let mut build = BuildSurroundings::new()?;
+    let coins = build
+        .entity()?
+        .named("Coin")?
+        .save()?
+        .of_quantity(&5.0.into())?
+        .into_entity()?;
+    let vessel = build
+        .entity()?
+        .named("Vessel")?
+        .save()?
+        .carryable()?
+        .holding(&vec![coins.clone()])?
+        .into_entity()?;
+    let (session, surroundings) = build
+        .hands(vec![QuickThing::Actual(vessel.clone())])
+        .build()?;
+
+    let action = try_parsing(TakeOutActionParser {}, "take 2 coin out of vessel")?;
+    let action = action.unwrap();
+    let reply = action.perform(session.clone(), &surroundings)?;
+    let (_world, person, _area) = surroundings.unpack()?;
+
+    let reply: SimpleReply = reply.json_as()?;
+    assert_eq!(reply, SimpleReply::Done);
+
+    assert_eq!(person.scope::<Containing>()?.unwrap().holding.len(), 2);
+    assert_eq!(vessel.scope::<Containing>()?.unwrap().holding.len(), 1);
+    assert_eq!(tools::quantity(&coins)?, 3.0);
+
+    build.close()?;
+
+    Ok(())
+}
+
 #[test]
 fn it_trades_one_for_one() -> Result<()> {
     let mut build = BuildSurroundings::new()?;
@@ -506,7 +671,7 @@ fn it_trades_one_for_one() -> Result<()> {
 
     assert_eq!(effect, Effect::Ok);
 
-    let (_, person, _) = surroundings.unpack();
+    let (_, person, _) = surroundings.unpack()?;
 
     assert_eq!(person.scope::<Containing>()?.unwrap().holding.len(), 1);
     assert_eq!(carla.scope::<Containing>()?.unwrap().holding.len(), 1);
@@ -524,3 +689,187 @@ fn it_trades_one_for_one() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn it_holds_the_nth_matching_item() -> Result<()> {
+    let mut build = BuildSurroundings::new()?;
+    let (session, surroundings) = build
+        .ground(vec![
+            QuickThing::Object("Brass Key"),
+            QuickThing::Object("Iron Key"),
+        ])
+        .build()?;
+
+    let action = try_parsing(HoldActionParser {}, "hold second key")?;
+    let action = action.unwrap();
+    let effect = action.perform(session.clone(), &surroundings)?;
+
+    assert_eq!(effect, Effect::Ok);
+
+    let (_, person, area) = surroundings.unpack()?;
+    let held = &person.scope::<Containing>()?.unwrap().holding;
+    assert_eq!(held.len(), 1);
+    assert_eq!(held[0].to_entity()?.name()?, "Iron Key");
+    assert_eq!(area.scope::<Containing>()?.unwrap().holding.len(), 1);
+
+    build.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn it_drops_items_referred_to_as_it() -> Result<()> {
+    let mut build = BuildSurroundings::new()?;
+    let (session, surroundings) = build
+        .ground(vec![QuickThing::Object("Cool Rake")])
+        .hands(vec![QuickThing::Object("Key")])
+        .build()?;
+
+    let action = try_parsing(HoldActionParser {}, "hold rake")?;
+    let effect = action.unwrap().perform(session.clone(), &surroundings)?;
+    assert_eq!(effect, Effect::Ok);
+
+    let action = try_parsing(DropActionParser {}, "drop it")?;
+    let effect = action.unwrap().perform(session.clone(), &surroundings)?;
+    assert_eq!(effect, Effect::Ok);
+
+    let (_, person, area) = surroundings.unpack()?;
+    let held = &person.scope::<Containing>()?.unwrap().holding;
+    let ground = &area.scope::<Containing>()?.unwrap().holding;
+    assert_eq!(held.len(), 1);
+    assert_eq!(held[0].to_entity()?.name()?, "Key");
+    assert_eq!(ground.len(), 1);
+    assert_eq!(ground[0].to_entity()?.name()?, "Cool Rake");
+
+    build.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn it_refers_to_items_put_inside_as_it() -> Result<()> {
+    let mut build = BuildSurroundings::new()?;
+    let vessel = build
+        .entity()?
+        .named("Vessel")?
+        .save()?
+        .carryable()?
+        .holding(&vec![])?
+        .into_entity()?;
+    let (session, surroundings) = build
+        .hands(vec![
+            QuickThing::Object("Key"),
+            QuickThing::Actual(vessel.clone()),
+        ])
+        .build()?;
+
+    let action = try_parsing(PutInsideActionParser {}, "put key inside vessel")?;
+    let reply = action.unwrap().perform(session.clone(), &surroundings)?;
+    assert_eq!(reply, SimpleReply::Done.try_into()?);
+    assert_eq!(vessel.scope::<Containing>()?.unwrap().holding.len(), 1);
+
+    let action = try_parsing(TakeOutActionParser {}, "take it out of vessel")?;
+    let reply = action.unwrap().perform(session.clone(), &surroundings)?;
+    assert_eq!(reply, SimpleReply::Done.try_into()?);
+
+    let (_, person, _area) = surroundings.unpack()?;
+    let held = &person.scope::<Containing>()?.unwrap().holding;
+    assert_eq!(held.len(), 2);
+    assert_eq!(held[1].to_entity()?.name()?, "Key");
+    assert_eq!(vessel.scope::<Containing>()?.unwrap().holding.len(), 0);
+
+    build.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn it_drops_all_held_items() -> Result<()> {
+    let mut build = BuildSurroundings::new()?;
+    let (session, surroundings) = build
+        .hands(vec![
+            QuickThing::Object("Key"),
+            QuickThing::Object("Cool Rake"),
+        ])
+        .build()?;
+
+    let action = try_parsing(DropActionParser {}, "drop all")?;
+    let action = action.unwrap();
+    let effect = action.perform(session.clone(), &surroundings)?;
+
+    assert_eq!(effect, Effect::Ok);
+
+    let (_, person, area) = surroundings.unpack()?;
+    assert_eq!(person.scope::<Containing>()?.unwrap().holding.len(), 0);
+    assert_eq!(area.scope::<Containing>()?.unwrap().holding.len(), 2);
+
+    build.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn it_takes_all_items_out_of_containers() -> Result<()> {
+    let mut build = BuildSurroundings::new()?;
+    let key = build
+        .entity()?
+        .named("Key")?
+        .save()?
+        .carryable()?
+        .into_entity()?;
+    let coin = build
+        .entity()?
+        .named("Coin")?
+        .save()?
+        .carryable()?
+        .into_entity()?;
+    let vessel = build
+        .entity()?
+        .named("Vessel")?
+        .save()?
+        .carryable()?
+        .holding(&vec![key.clone(), coin.clone()])?
+        .into_entity()?;
+    let (session, surroundings) = build
+        .hands(vec![QuickThing::Actual(vessel.clone())])
+        .build()?;
+
+    let action = try_parsing(TakeOutActionParser {}, "take all from vessel")?;
+    let action = action.unwrap();
+    let reply = action.perform(session.clone(), &surroundings)?;
+    let (_world, person, _area) = surroundings.unpack()?;
+
+    assert_eq!(reply, SimpleReply::Done.try_into()?);
+
+    assert_eq!(person.scope::<Containing>()?.unwrap().holding.len(), 3);
+    assert_eq!(vessel.scope::<Containing>()?.unwrap().holding.len(), 0);
+
+    build.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn it_suggests_corrections_for_mistyped_commands() -> Result<()> {
+    let mut plugins = RegisteredPlugins::default();
+    plugins.register(CarryingPluginFactory::default());
+    let mut build = BuildSurroundings::new_with_plugins(plugins)?;
+    let (_session, _surroundings) = build
+        .ground(vec![QuickThing::Object("Brass Key")])
+        .build()?;
+
+    assert_eq!(
+        build.evaluate("hlod brss key")?,
+        Some(
+            SuggestionsReply {
+                suggestions: vec!["hold brass key".to_owned()]
+            }
+            .try_into()?
+        )
+    );
+    assert_eq!(build.evaluate("xyzzy")?, None);
+
+    build.close()?;
+
+    Ok(())
+}
@@ -28,7 +28,9 @@ impl Plugin for ChatPlugin {
     }
 
     fn schema(&self) -> Schema {
-        Schema::empty().action::<actions::SpeakAction>()
+        Schema::empty()
+            .action::<actions::SpeakAction>()
+            .action::<actions::ShoutAction>()
     }
 
     fn key(&self) -> &'static str {
@@ -43,6 +45,7 @@ impl Plugin for ChatPlugin {
 impl ParsesActions for ChatPlugin {
     fn try_parse_action(&self, i: &str) -> EvaluationResult {
         try_parsing(parser::SpeakActionParser {}, i)
+            .or_else(|_| try_parsing(parser::ShoutActionParser {}, i))
     }
 }
 
@@ -54,7 +57,7 @@ impl ActionSource for ActionSources {
         &self,
         tagged: &TaggedJson,
     ) -> Result<Option<Box<dyn Action>>, serde_json::Error> {
-        try_deserialize_all!(tagged, actions::SpeakAction);
+        try_deserialize_all!(tagged, actions::SpeakAction, actions::ShoutAction);
 
         Ok(None)
     }
@@ -106,7 +109,7 @@ pub mod actions {
 
         fn perform(&self, session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
             if let Some(message) = &self.here {
-                let (_, actor, _) = surroundings.unpack();
+                let (_, actor, _) = surroundings.unpack()?;
 
                 let actor = match &self.actor {
                     Some(actor) => match session.find_item(&surroundings, &actor)? {
@@ -137,6 +140,41 @@ pub mod actions {
             Ok(Effect::Ok)
         }
     }
+
+    /// How many routes away shouting can be heard from.
+    pub const SHOUTING_DEPTH: u32 = 1;
+
+    #[action]
+    pub struct ShoutAction {
+        pub(crate) here: String,
+    }
+
+    impl Action for ShoutAction {
+        fn is_read_only(&self) -> bool {
+            true
+        }
+
+        fn perform(&self, session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
+            let (_, actor, area) = surroundings.unpack()?;
+
+            session.raise(
+                Some(actor.clone()),
+                Audience::Nearby {
+                    area: area.key().clone(),
+                    depth: SHOUTING_DEPTH,
+                },
+                Raising::TaggedJson(
+                    Talking::Shouting(Spoken::new(
+                        (&actor).observe(&actor)?.expect("No observed entity"),
+                        &self.here,
+                    ))
+                    .to_tagged_json()?,
+                ),
+            )?;
+
+            Ok(Effect::Ok)
+        }
+    }
 }
 
 pub mod parser {
@@ -164,4 +202,21 @@ pub mod parser {
             Ok(Some(action))
         }
     }
+
+    pub struct ShoutActionParser {}
+
+    impl ParsesActions for ShoutActionParser {
+        fn try_parse_action(&self, i: &str) -> EvaluationResult {
+            let (_, action) = map(
+                preceded(pair(tag("shout"), spaces), text_to_end_of_line),
+                |text| {
+                    Box::new(ShoutAction {
+                        here: text.to_owned(),
+                    }) as Box<dyn Action>
+                },
+            )(i)?;
+
+            Ok(Some(action))
+        }
+    }
 }
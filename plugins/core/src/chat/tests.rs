@@ -1,5 +1,11 @@
 use super::parser::*;
-use crate::{chat::actions::SpeakAction, library::tests::*};
+use crate::{
+    chat::{actions::SpeakAction, ChatPluginFactory},
+    library::tests::*,
+    moving::model::{Occupyable, Route, SimpleRoute},
+    tools,
+};
+use engine::prelude::{DevNullNotifier, HasUsernames};
 
 #[test]
 fn it_raises_conversation_events() -> Result<()> {
@@ -14,7 +20,7 @@ fn it_raises_conversation_events() -> Result<()> {
 fn it_raises_conversation_events_for_actor_area() -> Result<()> {
     let mut build = BuildSurroundings::new()?;
     let (_session, surroundings) = build.plain().encyclopedia()?.build()?;
-    let (_world, _, area) = surroundings.unpack();
+    let (_world, _, area) = surroundings.unpack()?;
 
     let (_, effect) = perform_directly(SpeakAction {
         area: Some(Item::Key(area.key())),
@@ -26,3 +32,63 @@ fn it_raises_conversation_events_for_actor_area() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn it_raises_shouting_events_heard_from_nearby_areas() -> Result<()> {
+    let mut plugins = RegisteredPlugins::default();
+    plugins.register(ChatPluginFactory::default());
+    let mut build = BuildSurroundings::new_with_plugins(plugins)?;
+    let north = build.make(QuickThing::Place("North Place"))?;
+    let far = build.make(QuickThing::Place("Far Place"))?;
+    let carla = build.with(build_entity().living().name("Carla").try_into()?)?;
+    let dora = build.with(build_entity().living().name("Dora").try_into()?)?;
+    let (_session, surroundings) = build
+        .route("North", QuickThing::Actual(north.clone()))
+        .build()?;
+    let (world, person, area) = surroundings.unpack()?;
+    world.add_username_to_key("carla", &carla.key())?;
+    world.add_username_to_key("dora", &dora.key())?;
+
+    tools::set_occupying(&north, &vec![carla.clone()])?;
+    tools::set_occupying(&far, &vec![dora.clone()])?;
+    for (from, name, to) in [(&north, "South", &area), (&north, "North", &far)] {
+        let mut occupyable = from.scope_mut::<Occupyable>()?;
+        occupyable
+            .routes
+            .get_or_insert_with(Vec::new)
+            .push(Route::Simple(SimpleRoute::new(name, to.entity_ref())));
+        occupyable.save()?;
+    }
+
+    build.close()?;
+
+    let domain = build.domain().unwrap();
+    domain.evaluate_and_perform_as(
+        engine::prelude::EvaluateAs::Key(&person.key()),
+        "shout help!",
+        &DevNullNotifier {},
+    )?;
+
+    let now = chrono::Utc::now();
+    let heard = |e: &EntityPtr| -> Result<Vec<JsonValue>> {
+        domain
+            .query_mailbox(&e.key(), now)?
+            .into_iter()
+            .map(|m| Ok(serde_json::from_str(&m.serialized)?))
+            .collect()
+    };
+
+    let shouter = heard(&person)?;
+    assert_eq!(shouter.len(), 1);
+    assert!(shouter[0]["talking"]["shouting"].is_object());
+
+    let nearby = heard(&carla)?;
+    assert_eq!(nearby.len(), 1);
+    assert_eq!(nearby[0]["distant"]["distance"], 1);
+    assert_eq!(nearby[0]["distant"]["direction"], "South");
+    assert!(nearby[0]["distant"]["event"]["talking"]["shouting"].is_object());
+
+    assert!(heard(&dora)?.is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,320 @@
+use std::rc::Rc;
+
+use crate::library::plugin::*;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Default)]
+pub struct ChoosingPluginFactory {}
+
+impl PluginFactory for ChoosingPluginFactory {
+    fn create_plugin(&self) -> Result<Box<dyn Plugin>> {
+        Ok(Box::new(ChoosingPlugin {}))
+    }
+
+    fn stop(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct ChoosingPlugin {}
+
+impl Plugin for ChoosingPlugin {
+    fn plugin_key() -> &'static str
+    where
+        Self: Sized,
+    {
+        "choosing"
+    }
+
+    fn key(&self) -> &'static str {
+        Self::plugin_key()
+    }
+
+    fn schema(&self) -> Schema {
+        Schema::empty().action::<actions::ChooseAction>()
+    }
+
+    fn sources(&self) -> Vec<Box<dyn ActionSource>> {
+        vec![Box::new(ActionSources::default())]
+    }
+
+    fn middleware(&mut self) -> Result<Vec<Rc<dyn Middleware>>> {
+        Ok(vec![Rc::new(ChoosingMiddleware {})])
+    }
+}
+
+impl ParsesActions for ChoosingPlugin {
+    fn try_parse_action_in_surroundings(
+        &self,
+        surroundings: &Surroundings,
+        i: &str,
+    ) -> EvaluationResult {
+        let Ok(actor) = surroundings.actor() else {
+            return Err(EvaluationError::ParseFailed);
+        };
+
+        let choosing = actor
+            .scope::<model::Choosing>()
+            .map_err(|e| EvaluationError::Other(e.into()))?;
+
+        match choosing.and_then(|c| c.into().pending) {
+            Some(pending) => try_parsing(
+                parser::ChooseActionParser {
+                    candidates: pending.candidates,
+                },
+                i,
+            ),
+            None => Err(EvaluationError::ParseFailed),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct ActionSources {}
+
+impl ActionSource for ActionSources {
+    fn try_deserialize_action(
+        &self,
+        tagged: &TaggedJson,
+    ) -> Result<Option<Box<dyn Action>>, serde_json::Error> {
+        try_deserialize_all!(tagged, actions::ChooseAction);
+
+        Ok(None)
+    }
+}
+
+/// Turns failures to find an entity because several matched into a reply
+/// asking the actor to choose, remembering the action so that it can be
+/// performed again once they have. Doing anything else instead of choosing
+/// forgets about that action.
+pub struct ChoosingMiddleware {}
+
+impl Middleware for ChoosingMiddleware {
+    fn handle(&self, value: Perform, next: MiddlewareNext) -> Result<Effect, anyhow::Error> {
+        let waiting = match &value {
+            Perform::Surroundings {
+                surroundings,
+                action,
+            } => match surroundings.actor() {
+                Ok(actor) => Some((
+                    actor.clone(),
+                    match action {
+                        PerformAction::Instance(action) => action.to_tagged_json()?,
+                        PerformAction::TaggedJson(tagged) => tagged.clone(),
+                    },
+                )),
+                Err(_) => None,
+            },
+            _ => None,
+        };
+
+        if let Some((actor, action)) = &waiting {
+            if action.tag() != actions::ChooseAction::tag() {
+                model::abandon_choice(actor)?;
+            }
+        }
+
+        match next.handle(value) {
+            Err(e) => match (ambiguous(&e), waiting) {
+                (Some(keys), Some((actor, action))) => {
+                    info!("ambiguous {:?}", keys);
+
+                    Ok(model::wait_for_choice(&actor, action, &keys)?.try_into()?)
+                }
+                _ => Err(e),
+            },
+            effect => effect,
+        }
+    }
+}
+
+fn ambiguous(e: &anyhow::Error) -> Option<Vec<EntityKey>> {
+    match e.downcast_ref::<DomainError>() {
+        Some(DomainError::Ambiguous(keys)) => Some(keys.clone()),
+        Some(DomainError::Anyhow(e)) => ambiguous(e),
+        _ => None,
+    }
+}
+
+pub mod model {
+    use crate::{library::model::*, looking::model::Observe};
+
+    #[derive(Debug, Serialize, Deserialize, Clone)]
+    pub struct Candidate {
+        pub key: EntityKey,
+        pub name: String,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, Clone)]
+    pub struct Pending {
+        pub action: TaggedJson,
+        pub candidates: Vec<Candidate>,
+    }
+
+    /// An action waiting for the actor to choose between several entities,
+    /// and the choices they've made so far while performing it.
+    #[derive(Debug, Serialize, Deserialize, Default)]
+    pub struct Choosing {
+        pub pending: Option<Pending>,
+        pub chosen: Vec<EntityKey>,
+    }
+
+    impl Scope for Choosing {
+        fn scope_key() -> &'static str {
+            "choosing"
+        }
+    }
+
+    pub fn wait_for_choice(
+        actor: &EntityPtr,
+        action: TaggedJson,
+        keys: &[EntityKey],
+    ) -> Result<AmbiguousReply, DomainError> {
+        let session = get_my_session()?;
+        let mut candidates = Vec::new();
+        let mut observed = Vec::new();
+        for key in keys {
+            let Some(entity) = session.entity(&LookupBy::Key(key))? else {
+                continue;
+            };
+
+            if let Some(observation) = (&entity).observe(actor)? {
+                candidates.push(Candidate {
+                    key: key.clone(),
+                    name: entity.name()?,
+                });
+                observed.push(observation);
+            }
+        }
+
+        let mut choosing = actor.scope_mut::<Choosing>()?;
+        choosing.pending = Some(Pending { action, candidates });
+        choosing.save()?;
+
+        Ok(AmbiguousReply {
+            candidates: observed,
+        })
+    }
+
+    /// Forgets about the action waiting for the actor to choose, if there is
+    /// one, along with any choices they made while performing it.
+    pub fn abandon_choice(actor: &EntityPtr) -> Result<(), DomainError> {
+        match actor.scope::<Choosing>()? {
+            Some(choosing) if choosing.pending.is_some() => {}
+            _ => return Ok(()),
+        }
+
+        let mut choosing = actor.scope_mut::<Choosing>()?;
+        choosing.pending = None;
+        choosing.chosen.clear();
+        choosing.save()
+    }
+}
+
+pub mod actions {
+    use super::model::*;
+    use crate::library::actions::*;
+
+    #[action]
+    pub struct ChooseAction {
+        pub key: EntityKey,
+    }
+
+    impl Action for ChooseAction {
+        fn is_read_only(&self) -> bool {
+            false
+        }
+
+        fn perform(&self, session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
+            info!("choose {:?}", self.key);
+
+            let (_, actor, _) = surroundings.unpack()?;
+
+            let pending = {
+                let mut choosing = actor.scope_mut::<Choosing>()?;
+                let Some(pending) = choosing.pending.take() else {
+                    return Ok(SimpleReply::What.try_into()?);
+                };
+
+                if !pending.candidates.iter().any(|c| c.key == self.key) {
+                    return Ok(SimpleReply::NotFound.try_into()?);
+                }
+
+                choosing.chosen.push(self.key.clone());
+                choosing.save()?;
+
+                pending
+            };
+
+            let effect = session.perform(Perform::Actor {
+                actor: actor.clone(),
+                action: PerformAction::TaggedJson(pending.action),
+            });
+
+            // Choosing may have uncovered another ambiguity, in which case the
+            // choices made so far are still needed. Otherwise they're done
+            // with, whether or not the action succeeded.
+            let mut choosing = actor.scope_mut::<Choosing>()?;
+            if choosing.pending.is_none() {
+                choosing.chosen.clear();
+                choosing.save()?;
+            }
+
+            Ok(effect?)
+        }
+    }
+}
+
+pub mod parser {
+    use super::{actions::*, model::Candidate};
+    use crate::{finding::rank_description, library::parser::*};
+
+    pub struct ChooseActionParser {
+        pub candidates: Vec<Candidate>,
+    }
+
+    impl ChooseActionParser {
+        fn numbered(&self, n: usize) -> Option<&Candidate> {
+            n.checked_sub(1).and_then(|i| self.candidates.get(i))
+        }
+
+        fn described(&self, desc: &str) -> Option<&Candidate> {
+            let ranked = self
+                .candidates
+                .iter()
+                .map(|c| (rank_description(&c.name, desc), c))
+                .filter(|(rank, _)| *rank > 0)
+                .collect::<Vec<_>>();
+
+            let best = ranked.iter().map(|(rank, _)| *rank).max()?;
+            match ranked
+                .into_iter()
+                .filter(|(rank, _)| *rank == best)
+                .collect::<Vec<_>>()
+                .as_slice()
+            {
+                [(_, candidate)] => Some(candidate),
+                _ => None,
+            }
+        }
+    }
+
+    impl ParsesActions for ChooseActionParser {
+        fn try_parse_action(&self, i: &str) -> EvaluationResult {
+            let numbered = map_opt(map_res(digit1, str::parse), |n: usize| self.numbered(n));
+            let described = map_opt(word, |desc: &str| self.described(desc));
+
+            let (rest, candidate) = alt((numbered, described))(i)?;
+            if !rest.trim().is_empty() {
+                return Err(EvaluationError::ParseFailed);
+            }
+
+            Ok(Some(Box::new(ChooseAction {
+                key: candidate.key.clone(),
+            })))
+        }
+    }
+}
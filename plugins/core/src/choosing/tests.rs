@@ -0,0 +1,159 @@
+use super::model::*;
+use super::*;
+use crate::carrying::model::Containing;
+use crate::carrying::CarryingPluginFactory;
+use crate::library::tests::*;
+
+fn plugins() -> RegisteredPlugins {
+    let mut plugins = RegisteredPlugins::default();
+    plugins.register(CarryingPluginFactory::default());
+    plugins.register(ChoosingPluginFactory::default());
+    plugins
+}
+
+fn held_names(actor: &EntityPtr) -> Result<Vec<String>> {
+    actor
+        .scope::<Containing>()?
+        .unwrap()
+        .holding
+        .iter()
+        .map(|h| Ok(h.to_entity()?.name()?))
+        .collect()
+}
+
+#[test]
+fn it_replies_with_candidates_when_ambiguous() -> Result<()> {
+    let mut build = BuildSurroundings::new_with_plugins(plugins())?;
+    let (_session, surroundings) = build
+        .ground(vec![
+            QuickThing::Object("Brass Key"),
+            QuickThing::Object("Iron Key"),
+        ])
+        .build()?;
+
+    let effect = build.evaluate("hold key")?.unwrap();
+
+    insta::assert_json_snapshot!(effect.to_debug_json()?);
+
+    let (_, actor, _) = surroundings.unpack()?;
+    let choosing = actor.scope::<Choosing>()?.unwrap();
+    assert_eq!(choosing.pending.as_ref().unwrap().candidates.len(), 2);
+    assert_eq!(held_names(&actor)?.len(), 0);
+
+    build.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn it_prefers_matching_adjectives_to_ambiguity() -> Result<()> {
+    let mut build = BuildSurroundings::new_with_plugins(plugins())?;
+    let (_session, surroundings) = build
+        .ground(vec![
+            QuickThing::Object("Brass Key"),
+            QuickThing::Object("Iron Key"),
+        ])
+        .build()?;
+
+    let effect = build.evaluate("hold iron")?.unwrap();
+
+    assert_eq!(effect, Effect::Ok);
+
+    let (_, actor, _) = surroundings.unpack()?;
+    assert_eq!(held_names(&actor)?, vec!["Iron Key".to_owned()]);
+
+    build.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn it_performs_pending_action_when_numbered_candidate_chosen() -> Result<()> {
+    let mut build = BuildSurroundings::new_with_plugins(plugins())?;
+    let (_session, surroundings) = build
+        .ground(vec![
+            QuickThing::Object("Brass Key"),
+            QuickThing::Object("Iron Key"),
+        ])
+        .build()?;
+
+    build.evaluate("hold key")?.unwrap();
+    let effect = build.evaluate("2")?.unwrap();
+
+    assert_eq!(effect, Effect::Ok);
+
+    let (_, actor, _) = surroundings.unpack()?;
+    assert_eq!(held_names(&actor)?, vec!["Iron Key".to_owned()]);
+
+    let choosing = actor.scope::<Choosing>()?.unwrap();
+    assert!(choosing.pending.is_none());
+    assert!(choosing.chosen.is_empty());
+
+    build.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn it_performs_pending_action_when_described_candidate_chosen() -> Result<()> {
+    let mut build = BuildSurroundings::new_with_plugins(plugins())?;
+    let (_session, surroundings) = build
+        .ground(vec![
+            QuickThing::Object("Brass Key"),
+            QuickThing::Object("Iron Key"),
+        ])
+        .build()?;
+
+    build.evaluate("hold key")?.unwrap();
+    let effect = build.evaluate("brass")?.unwrap();
+
+    assert_eq!(effect, Effect::Ok);
+
+    let (_, actor, _) = surroundings.unpack()?;
+    assert_eq!(held_names(&actor)?, vec!["Brass Key".to_owned()]);
+
+    build.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn it_fails_to_parse_choices_when_nothing_pending() -> Result<()> {
+    let mut build = BuildSurroundings::new_with_plugins(plugins())?;
+    let (_session, _surroundings) = build
+        .ground(vec![QuickThing::Object("Brass Key")])
+        .build()?;
+
+    assert_eq!(build.evaluate("1")?, None);
+
+    build.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn it_forgets_pending_actions_when_doing_something_else() -> Result<()> {
+    let mut build = BuildSurroundings::new_with_plugins(plugins())?;
+    let (_session, surroundings) = build
+        .ground(vec![
+            QuickThing::Object("Brass Key"),
+            QuickThing::Object("Iron Key"),
+        ])
+        .hands(vec![QuickThing::Object("Cool Rake")])
+        .build()?;
+
+    build.evaluate("hold key")?.unwrap();
+    assert_eq!(build.evaluate("drop rake")?, Some(Effect::Ok));
+
+    let (_, actor, _) = surroundings.unpack()?;
+    let choosing = actor.scope::<Choosing>()?.unwrap();
+    assert!(choosing.pending.is_none());
+    assert!(choosing.chosen.is_empty());
+
+    assert_eq!(build.evaluate("1")?, None);
+    assert!(held_names(&actor)?.is_empty());
+
+    build.close()?;
+
+    Ok(())
+}
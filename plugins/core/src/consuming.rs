@@ -0,0 +1,66 @@
+use crate::library::plugin::*;
+
+pub mod actions;
+pub mod model;
+#[cfg(test)]
+mod tests;
+
+#[derive(Default)]
+pub struct ConsumingPluginFactory {}
+
+impl PluginFactory for ConsumingPluginFactory {
+    fn create_plugin(&self) -> Result<Box<dyn Plugin>> {
+        Ok(Box::new(ConsumingPlugin {}))
+    }
+
+    fn stop(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct ConsumingPlugin {}
+
+impl Plugin for ConsumingPlugin {
+    fn plugin_key() -> &'static str
+    where
+        Self: Sized,
+    {
+        "consuming"
+    }
+
+    fn schema(&self) -> Schema {
+        Schema::empty()
+            .action::<actions::EatAction>()
+            .action::<actions::DrinkAction>()
+    }
+
+    fn key(&self) -> &'static str {
+        Self::plugin_key()
+    }
+
+    fn sources(&self) -> Vec<Box<dyn ActionSource>> {
+        vec![Box::new(ActionSources::default())]
+    }
+}
+
+impl ParsesActions for ConsumingPlugin {
+    fn try_parse_action(&self, i: &str) -> EvaluationResult {
+        try_parsing(actions::EatActionParser {}, i)
+            .or_else(|_| try_parsing(actions::DrinkActionParser {}, i))
+    }
+}
+
+#[derive(Default)]
+pub struct ActionSources {}
+
+impl ActionSource for ActionSources {
+    fn try_deserialize_action(
+        &self,
+        tagged: &TaggedJson,
+    ) -> Result<Option<Box<dyn Action>>, serde_json::Error> {
+        try_deserialize_all!(tagged, actions::EatAction, actions::DrinkAction);
+
+        Ok(None)
+    }
+}
@@ -0,0 +1,131 @@
+use crate::{
+    carrying::model::{Carryable, Containing},
+    consuming::model::{Consumable, Consuming, Drinkable, Edible},
+    library::actions::*,
+    location::Location,
+    looking::model::Observe,
+};
+
+/// Separates one of a stack into its own entity beside the stack, with its
+/// own kind so the two aren't combined again while it's partly consumed.
+fn separate_one(item: &EntityPtr) -> Result<EntityPtr> {
+    let (_, separated) = tools::separate(item, &1.0.into())?;
+
+    {
+        let mut carryable = separated.scope_mut::<Carryable>()?;
+        carryable.set_kind(&Kind::new(get_my_session()?.new_identity()));
+        carryable.save()?;
+    }
+
+    if let Some(container) = Location::get(item)? {
+        let container = container.to_entity()?;
+        let mut containing = container.scope_mut::<Containing>()?;
+        containing.start_carrying(&separated)?;
+        containing.save()?;
+        Location::set(&separated, container.entity_ref())?;
+    }
+
+    Ok(separated)
+}
+
+/// Consumes a portion of the item, obliterating the item when that was the
+/// last portion. Returns false when the item can't be consumed this way.
+fn consume<T: Consumable>(item: &EntityPtr) -> Result<bool> {
+    let Some(each) = item.scope::<T>()?.map(|c| c.portions().each) else {
+        return Ok(false);
+    };
+
+    let item = if tools::quantity(item)? > 1.0 {
+        if each <= 1 {
+            let mut carryable = item.scope_mut::<Carryable>()?;
+            carryable.decrease_quantity(&1.0.into())?;
+            carryable.save()?;
+
+            return Ok(true);
+        }
+
+        // Portions are kept for the whole entity, so the one being consumed
+        // is separated from the rest of the stack first.
+        separate_one(item)?
+    } else {
+        item.clone()
+    };
+
+    let mut consumable = item.scope_mut::<T>()?;
+    let finished = consumable.portions_mut().consume();
+    consumable.save()?;
+
+    if finished {
+        tools::obliterate(item.clone().into())?;
+    }
+
+    Ok(true)
+}
+
+fn consume_and_raise<T: Consumable>(
+    session: SessionRef,
+    surroundings: &Surroundings,
+    item: &Item,
+    event: impl FnOnce(ObservedEntity, ObservedEntity, ObservedEntity) -> Consuming,
+) -> ReplyResult {
+    let (_, actor, area) = surroundings.unpack()?;
+
+    let Some(item) = session.find_item(surroundings, item)? else {
+        return Ok(SimpleReply::NotFound.try_into()?);
+    };
+    let item = item.one()?;
+
+    // Observed before consuming, because the item may not be around after.
+    let observed = (&item).observe(&actor)?.expect("No observed entity");
+
+    match consume::<T>(&item)? {
+        true => reply_ok(
+            actor.clone(),
+            Audience::Area(area.key().clone()),
+            event(
+                (&actor).observe(&actor)?.expect("No observed entity"),
+                observed,
+                (&area).observe(&actor)?.expect("No observed entity"),
+            ),
+        ),
+        false => Ok(SimpleReply::Impossible.try_into()?),
+    }
+}
+
+#[action("EAT #unheld")]
+pub struct EatAction {
+    pub item: Item,
+}
+
+impl Action for EatAction {
+    fn is_read_only(&self) -> bool {
+        false
+    }
+
+    fn perform(&self, session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
+        info!("eat {:?}!", self.item);
+
+        consume_and_raise::<Edible>(session, surroundings, &self.item, |actor, item, area| {
+            Consuming::Ate { actor, item, area }
+        })
+    }
+}
+
+#[action("DRINK #unheld")]
+pub struct DrinkAction {
+    pub item: Item,
+}
+
+impl Action for DrinkAction {
+    fn is_read_only(&self) -> bool {
+        false
+    }
+
+    fn perform(&self, session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
+        info!("drink {:?}!", self.item);
+
+        consume_and_raise::<Drinkable>(session, surroundings, &self.item, |actor, item, area| {
+            Consuming::Drank { actor, item, area }
+        })
+    }
+}
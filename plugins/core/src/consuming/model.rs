@@ -0,0 +1,81 @@
+use crate::library::model::*;
+
+pub use kernel::common::Consuming;
+
+/// How many portions each of a carryable's quantity is consumed in, and how
+/// many are left of the one being consumed, which is separated from the rest
+/// of its stack.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Portions {
+    pub each: u32,
+    pub remaining: u32,
+}
+
+impl Default for Portions {
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+impl Portions {
+    pub fn new(each: u32) -> Self {
+        Self {
+            each,
+            remaining: each,
+        }
+    }
+
+    /// Consumes a portion, returning true when that was the last portion.
+    pub fn consume(&mut self) -> bool {
+        self.remaining = self.remaining.saturating_sub(1);
+        self.remaining == 0
+    }
+}
+
+pub trait Consumable: Scope {
+    fn portions(&self) -> &Portions;
+
+    fn portions_mut(&mut self) -> &mut Portions;
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct Edible {
+    pub portions: Portions,
+}
+
+impl Scope for Edible {
+    fn scope_key() -> &'static str {
+        "edible"
+    }
+}
+
+impl Consumable for Edible {
+    fn portions(&self) -> &Portions {
+        &self.portions
+    }
+
+    fn portions_mut(&mut self) -> &mut Portions {
+        &mut self.portions
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct Drinkable {
+    pub portions: Portions,
+}
+
+impl Scope for Drinkable {
+    fn scope_key() -> &'static str {
+        "drinkable"
+    }
+}
+
+impl Consumable for Drinkable {
+    fn portions(&self) -> &Portions {
+        &self.portions
+    }
+
+    fn portions_mut(&mut self) -> &mut Portions {
+        &mut self.portions
+    }
+}
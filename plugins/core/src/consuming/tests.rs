@@ -0,0 +1,95 @@
+use super::actions::*;
+use super::model::*;
+use crate::carrying::model::Containing;
+use crate::library::tests::*;
+
+fn consumable<T: Consumable>(entity: &EntityPtr, portions: u32) -> Result<()> {
+    let mut consumable = entity.scope_mut::<T>()?;
+    *consumable.portions_mut() = Portions::new(portions);
+    consumable.save()?;
+
+    Ok(())
+}
+
+#[test]
+fn it_eats_portions_of_food_until_its_gone() -> Result<()> {
+    let mut build = BuildSurroundings::new()?;
+    let bread = build.make(QuickThing::Object("Bread"))?;
+    consumable::<Edible>(&bread, 2)?;
+    let (session, surroundings) = build.hands(vec![QuickThing::Actual(bread)]).build()?;
+    let (_, person, _) = surroundings.unpack()?;
+
+    let action = try_parsing(EatActionParser {}, "eat bread")?.unwrap();
+    assert_eq!(action.perform(session.clone(), &surroundings)?, Effect::Ok);
+    assert_eq!(person.scope::<Containing>()?.unwrap().holding.len(), 1);
+
+    let action = try_parsing(EatActionParser {}, "eat bread")?.unwrap();
+    assert_eq!(action.perform(session.clone(), &surroundings)?, Effect::Ok);
+    assert_eq!(person.scope::<Containing>()?.unwrap().holding.len(), 0);
+
+    build.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn it_drinks_one_of_several_potions() -> Result<()> {
+    let mut build = BuildSurroundings::new()?;
+    let potion = build.make(QuickThing::Multiple("Potion", 2.0))?;
+    consumable::<Drinkable>(&potion, 1)?;
+    let (session, surroundings) = build
+        .hands(vec![QuickThing::Actual(potion.clone())])
+        .build()?;
+    let (_, person, _) = surroundings.unpack()?;
+
+    let action = try_parsing(DrinkActionParser {}, "drink potion")?.unwrap();
+    assert_eq!(action.perform(session.clone(), &surroundings)?, Effect::Ok);
+    assert_eq!(person.scope::<Containing>()?.unwrap().holding.len(), 1);
+    assert_eq!(tools::quantity(&potion)?, 1.0);
+
+    build.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn it_eats_portions_of_one_from_a_stack() -> Result<()> {
+    let mut build = BuildSurroundings::new()?;
+    let bread = build.make(QuickThing::Multiple("Bread", 3.0))?;
+    consumable::<Edible>(&bread, 2)?;
+    let (session, surroundings) = build
+        .hands(vec![QuickThing::Actual(bread.clone())])
+        .build()?;
+    let (_, person, _) = surroundings.unpack()?;
+
+    let action = try_parsing(EatActionParser {}, "eat bread")?.unwrap();
+    assert_eq!(action.perform(session.clone(), &surroundings)?, Effect::Ok);
+    assert_eq!(tools::quantity(&bread)?, 2.0);
+    assert_eq!(bread.scope::<Edible>()?.unwrap().portions, Portions::new(2));
+
+    let holding = tools::contained_by(&person)?;
+    assert_eq!(holding.len(), 2);
+    let eating = holding.iter().find(|h| h.key() != bread.key()).unwrap();
+    assert_eq!(tools::quantity(eating)?, 1.0);
+    assert_eq!(eating.scope::<Edible>()?.unwrap().portions.remaining, 1);
+
+    build.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn it_fails_to_eat_things_that_arent_edible() -> Result<()> {
+    let mut build = BuildSurroundings::new()?;
+    let (session, surroundings) = build.hands(vec![QuickThing::Object("Rock")]).build()?;
+    let (_, person, _) = surroundings.unpack()?;
+
+    let action = try_parsing(EatActionParser {}, "eat rock")?.unwrap();
+    let reply: SimpleReply = action.perform(session.clone(), &surroundings)?.json_as()?;
+    assert_eq!(reply, SimpleReply::Impossible);
+    assert_eq!(person.scope::<Containing>()?.unwrap().holding.len(), 1);
+
+    build.close()?;
+
+    Ok(())
+}
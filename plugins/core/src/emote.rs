@@ -73,7 +73,7 @@ pub mod actions {
         }
 
         fn perform(&self, session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
-            let (_, actor, area) = surroundings.unpack();
+            let (_, actor, area) = surroundings.unpack()?;
 
             session.raise(
                 Some(actor.clone()),
@@ -14,6 +14,10 @@ impl PluginFactory for FashionPluginFactory {
     fn stop(&self) -> Result<()> {
         Ok(())
     }
+
+    fn register_upgrades(&self, upgrades: &mut ScopeUpgrades) {
+        upgrades.register::<model::Wearable>();
+    }
 }
 
 #[derive(Default)]
@@ -64,7 +68,7 @@ impl ActionSource for ActionSources {
 }
 
 pub mod model {
-    use crate::library::model::*;
+    use crate::{carrying::model::Carryable, library::model::*, tools};
 
     #[derive(Debug, Serialize, ToTaggedJson)]
     #[serde(rename_all = "camelCase")]
@@ -129,6 +133,16 @@ pub mod model {
 
             for held in wearing {
                 if is_kind(&held, &wearable.kind)? {
+                    if let Some(carryable) = item.scope::<Carryable>()? {
+                        let mut combining = held.scope_mut::<Carryable>()?;
+
+                        combining.increase_quantity(&carryable.quantity().into())?;
+
+                        combining.save()?;
+
+                        get_my_session()?.obliterate(item)?;
+                    }
+
                     return Ok(true);
                 }
             }
@@ -162,16 +176,20 @@ pub mod model {
             Ok(true)
         }
 
-        pub fn stop_wearing(&mut self, item: Found) -> Result<Option<EntityPtr>, DomainError> {
-            let item = &item.one()?;
-
-            if !self.is_wearing(item) {
+        pub fn stop_wearing(&mut self, found: Found) -> Result<Option<EntityPtr>, DomainError> {
+            if !self.is_wearing(found.entity()?) {
                 return Ok(None);
             }
 
-            self.remove_item(item)?;
+            match tools::portion(&found)? {
+                Some(tools::Portion::Whole(item)) => {
+                    self.remove_item(&item)?;
 
-            Ok(Some(item.clone()))
+                    Ok(Some(item))
+                }
+                Some(tools::Portion::Separated(separated)) => Ok(Some(separated)),
+                None => Ok(None),
+            }
         }
     }
 
@@ -212,6 +230,10 @@ pub mod model {
         fn scope_key() -> &'static str {
             "wearable"
         }
+
+        fn inherited() -> bool {
+            true
+        }
     }
 }
 
@@ -232,19 +254,22 @@ pub mod actions {
         fn perform(&self, session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
             info!("wear {:?}!", self.item);
 
-            let (_, actor, area) = surroundings.unpack();
+            let (_, actor, area) = surroundings.unpack()?;
 
             match session.find_item(surroundings, &self.item)? {
                 Some(wearing) => {
-                    let wearing = wearing.one()?;
-                    let location = Location::get(&wearing)?.expect("No location").to_entity()?;
-                    match tools::wear_article(&location, &actor, &wearing)? {
+                    let location = Location::get(wearing.entity()?)?
+                        .expect("No location")
+                        .to_entity()?;
+                    match tools::wear_article(&location, &actor, wearing.clone())? {
                         true => Ok(reply_ok(
                             actor.clone(),
                             Audience::Area(area.key().clone()),
                             Fashion::Worn {
                                 actor: actor.entity_ref(),
-                                item: (&wearing).observe(&actor)?.expect("No observed entity"),
+                                item: (&wearing.entity()?)
+                                    .observe(&actor)?
+                                    .expect("No observed entity"),
                                 area: area.entity_ref(),
                             },
                         )?),
@@ -269,19 +294,20 @@ pub mod actions {
         fn perform(&self, session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
             info!("remove {:?}!", self.maybe_item);
 
-            let (_, actor, area) = surroundings.unpack();
+            let (_, actor, area) = surroundings.unpack()?;
 
             match &self.maybe_item {
                 Some(item) => match session.find_item(surroundings, item)? {
                     Some(removing) => {
-                        let removing = removing.one()?;
-                        match tools::remove_article(&actor, &actor, &removing)? {
+                        match tools::remove_article(&actor, &actor, removing.clone())? {
                             true => Ok(reply_ok(
                                 actor.clone(),
                                 Audience::Area(area.key().clone()),
                                 Fashion::Removed {
                                     actor: actor.entity_ref(),
-                                    item: (&removing).observe(&actor)?.expect("No observed entity"),
+                                    item: (&removing.entity()?)
+                                        .observe(&actor)?
+                                        .expect("No observed entity"),
                                     area: area.entity_ref(),
                                 },
                             )?),
@@ -304,9 +330,10 @@ pub mod parser {
 
     impl ParsesActions for WearActionParser {
         fn try_parse_action(&self, i: &str) -> EvaluationResult {
-            let (_, action) = map(separated_pair(tag("wear"), spaces, noun), |(_, item)| {
-                WearAction { item }
-            })(i)?;
+            let (_, action) = map(
+                separated_pair(tag("wear"), spaces, alt((quantified, noun))),
+                |(_, item)| WearAction { item },
+            )(i)?;
 
             Ok(Some(Box::new(action)))
         }
@@ -316,11 +343,12 @@ pub mod parser {
 
     impl ParsesActions for RemoveActionParser {
         fn try_parse_action(&self, i: &str) -> EvaluationResult {
-            let specific = map(separated_pair(tag("remove"), spaces, noun), |(_, item)| {
-                RemoveAction {
+            let specific = map(
+                separated_pair(tag("remove"), spaces, alt((quantified, noun))),
+                |(_, item)| RemoveAction {
                     maybe_item: Some(Item::Held(Box::new(item))),
-                }
-            });
+                },
+            );
 
             let everything = map(tag("remove"), |_| RemoveAction { maybe_item: None });
 
@@ -11,7 +11,7 @@ fn it_wears_unworn_items() -> Result<()> {
         .hands(vec![QuickThing::Wearable("Cool Jacket")])
         .build()?;
 
-    let (_, person, _area) = surroundings.unpack();
+    let (_, person, _area) = surroundings.unpack()?;
     assert_eq!(person.scope::<Wearing>()?.unwrap().wearing.len(), 0);
     assert_eq!(person.scope::<Containing>()?.unwrap().holding.len(), 1);
 
@@ -40,7 +40,7 @@ fn it_removes_worn_items() -> Result<()> {
     let effect = action.perform(session.clone(), &surroundings)?;
     assert_eq!(effect, Effect::Ok);
 
-    let (_, person, _area) = surroundings.unpack();
+    let (_, person, _area) = surroundings.unpack()?;
     assert_eq!(person.scope::<Wearing>()?.unwrap().wearing.len(), 0);
     assert_eq!(person.scope::<Containing>()?.unwrap().holding.len(), 1);
 
@@ -48,3 +48,36 @@ fn it_removes_worn_items() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn it_wears_specified_quantity() -> Result<()> {
+    let mut build = BuildSurroundings::new()?;
+    let rings = build
+        .entity()?
+        .named("Ring")?
+        .save()?
+        .wearable()?
+        .of_quantity(&3.0.into())?
+        .into_entity()?;
+    let (session, surroundings) = build
+        .hands(vec![QuickThing::Actual(rings.clone())])
+        .build()?;
+
+    let action = try_parsing(WearActionParser {}, "wear 2 ring")?;
+    let action = action.unwrap();
+    let effect = action.perform(session.clone(), &surroundings)?;
+    assert_eq!(effect, Effect::Ok);
+
+    let (_, person, _area) = surroundings.unpack()?;
+    assert_eq!(person.scope::<Wearing>()?.unwrap().wearing.len(), 1);
+    assert_eq!(person.scope::<Containing>()?.unwrap().holding.len(), 1);
+    assert_eq!(tools::quantity(&rings)?, 1.0);
+    assert_eq!(
+        tools::quantity(&person.scope::<Wearing>()?.unwrap().wearing[0].to_entity()?)?,
+        2.0
+    );
+
+    build.close()?;
+
+    Ok(())
+}
@@ -1,11 +1,18 @@
 use anyhow::Result;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use tracing::debug;
 
-use crate::{location::Location, moving::model::Occupying, tools};
+use std::cmp::Reverse;
+
+use crate::{
+    choosing::model::Choosing,
+    location::Location,
+    moving::model::{Occupyable, Occupying},
+    tools,
+};
 use kernel::prelude::{
-    get_my_session, here, Audience, DomainError, EntityPtr, Finder, Found, IntoEntityPtr, Item,
-    OpenScope, Surroundings,
+    get_my_session, here, Audience, Distance, DomainError, EntityKey, EntityPtr, Finder, Found,
+    IntoEntityPtr, Item, Listener, LookupBy, OpenScope, OpenScopeRefMut, Scope, Surroundings,
 };
 
 /// Determines if an entity matches a user's description of that entity, given
@@ -19,6 +26,23 @@ pub fn matches_string(haystack: &str, desc: &str) -> bool {
     haystack.to_lowercase().contains(&desc.to_lowercase())
 }
 
+/// How well a description matches a name, higher is better and 0 means no
+/// match. Matching a whole word, like the adjective in "brass key", beats
+/// matching part of one and matching the whole name beats both.
+pub fn rank_description(name: &str, desc: &str) -> u32 {
+    let name = name.to_lowercase();
+    let desc = desc.to_lowercase();
+    if name == desc {
+        3
+    } else if name.split_whitespace().any(|word| word == desc) {
+        2
+    } else if name.contains(&desc) {
+        1
+    } else {
+        0
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub enum EntityRelationship {
     World(EntityPtr),
@@ -46,6 +70,33 @@ impl EntityRelationship {
     }
 }
 
+/// The entities an actor last did something to, so that they can be referred
+/// to again as "it" or "them". Actions remember their direct object, finding
+/// entities alone doesn't change this.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct Referenced {
+    entities: Vec<EntityKey>,
+}
+
+impl Scope for Referenced {
+    fn scope_key() -> &'static str {
+        "referenced"
+    }
+}
+
+impl Referenced {
+    pub fn remember(actor: &EntityPtr, found: &Found) -> Result<(), DomainError> {
+        let entities = found.entities().iter().map(|e| e.key()).collect();
+        let mut referenced = actor.scope_mut::<Referenced>()?;
+        if referenced.entities != entities {
+            referenced.entities = entities;
+            referenced.save()?;
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Debug)]
 pub struct EntityRelationshipSet {
     entities: Vec<EntityRelationship>,
@@ -65,6 +116,15 @@ impl EntityRelationshipSet {
                     EntityRelationship::Actor(actor.clone()),
                 ],
             },
+            Surroundings::World { world } => Self {
+                entities: vec![EntityRelationship::World(world.clone())],
+            },
+            Surroundings::Area { world, area } => Self {
+                entities: vec![
+                    EntityRelationship::World(world.clone()),
+                    EntityRelationship::Area(area.clone()),
+                ],
+            },
         }
     }
 
@@ -140,23 +200,48 @@ impl EntityRelationshipSet {
                 Ok(None)
             }
             Item::Named(name) => {
-                for entity in &self.entities {
-                    match entity {
-                        EntityRelationship::Contained(e)
-                        | EntityRelationship::Ground(e)
-                        | EntityRelationship::Holding(e)
-                        | EntityRelationship::Occupying(e)
-                        | EntityRelationship::Wearing(e) => {
-                            if matches_description(e, name)? {
-                                return Ok(Some(e.clone().into()));
-                            }
-                        }
-                        _ => {}
-                    }
+                let best = self.best_matches(name)?;
+                if best.len() < 2 {
+                    return Ok(best.into_iter().next().map(|e| e.into()));
                 }
 
-                Ok(None)
+                if let Some(chosen) = self.chosen(&best)? {
+                    return Ok(Some(chosen.into()));
+                }
+
+                Err(DomainError::Ambiguous(best.iter().map(|e| e.key()).collect()).into())
+            }
+            Item::Ordinal(nth, item) => Ok(self
+                .find_all(item)?
+                .into_iter()
+                .nth(nth.saturating_sub(1))
+                .map(|e| e.into())),
+            Item::Pronoun => {
+                let Some(referenced) = self.referenced()? else {
+                    return Ok(None);
+                };
+
+                let found = self
+                    .candidates()
+                    .into_iter()
+                    .filter(|e| referenced.entities.contains(&e.key()))
+                    .collect::<Vec<_>>();
+
+                Ok(many(found))
             }
+            Item::All => Ok(many(
+                self.filter(|e| matches!(e, EntityRelationship::Ground(_)))
+                    .candidates(),
+            )),
+            Item::Contained(contained) if **contained == Item::All => Ok(many(
+                self.expand()?
+                    .filter(|e| matches!(e, EntityRelationship::Contained(_)))
+                    .candidates(),
+            )),
+            Item::Held(held) if **held == Item::All => Ok(many(
+                self.filter(|e| matches!(e, EntityRelationship::Holding(_)))
+                    .candidates(),
+            )),
             Item::Contained(contained) => self.expand()?.find_item(contained),
             Item::Held(held) => self
                 .prioritize(|e| match e {
@@ -173,7 +258,117 @@ impl EntityRelationshipSet {
         }
     }
 
-    #[allow(dead_code)]
+    fn find_all(&self, item: &Item) -> Result<Vec<EntityPtr>> {
+        match item {
+            Item::Named(name) => {
+                let mut matching = Vec::new();
+                for entity in self.candidates() {
+                    if matches_description(&entity, name)? {
+                        matching.push(entity);
+                    }
+                }
+
+                Ok(matching)
+            }
+            _ => Ok(self
+                .find_item(item)?
+                .map(|f| f.entities())
+                .unwrap_or_default()),
+        }
+    }
+
+    /// Entities tied for the best match of the description. Candidates are
+    /// ranked by how well their name matches and then by how near they are,
+    /// which is the order their relationship appears in this set.
+    fn best_matches(&self, desc: &str) -> Result<Vec<EntityPtr>> {
+        let mut ranked: Vec<((Reverse<u32>, usize), EntityPtr)> = Vec::new();
+        for entity in &self.entities {
+            let e = match entity {
+                EntityRelationship::Contained(e)
+                | EntityRelationship::Ground(e)
+                | EntityRelationship::Holding(e)
+                | EntityRelationship::Occupying(e)
+                | EntityRelationship::Wearing(e) => e,
+                _ => continue,
+            };
+
+            let rank = rank_description(&e.name()?, desc);
+            if rank == 0 || ranked.iter().any(|(_, r)| r.key() == e.key()) {
+                continue;
+            }
+
+            let nearness = self
+                .entities
+                .iter()
+                .position(|o| std::mem::discriminant(o) == std::mem::discriminant(entity))
+                .unwrap_or_default();
+
+            ranked.push(((Reverse(rank), nearness), e.clone()));
+        }
+
+        ranked.sort_by_key(|(score, _)| *score);
+
+        let Some((best, _)) = ranked.first().cloned() else {
+            return Ok(Vec::new());
+        };
+
+        Ok(ranked
+            .into_iter()
+            .take_while(|(score, _)| *score == best)
+            .map(|(_, e)| e)
+            .collect())
+    }
+
+    /// The entity the actor chose when this same ambiguity came up before.
+    fn chosen(&self, among: &[EntityPtr]) -> Result<Option<EntityPtr>> {
+        for entity in &self.entities {
+            if let EntityRelationship::Actor(actor) = entity {
+                let Some(choosing) = actor.scope::<Choosing>()? else {
+                    return Ok(None);
+                };
+
+                return Ok(among
+                    .iter()
+                    .find(|e| choosing.chosen.contains(&e.key()))
+                    .cloned());
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Entities an actor may be referring to, in order of priority and
+    /// without duplicates. This excludes the actor, the area and the world.
+    fn candidates(&self) -> Vec<EntityPtr> {
+        let mut candidates: Vec<EntityPtr> = Vec::new();
+        for entity in &self.entities {
+            match entity {
+                EntityRelationship::Contained(e)
+                | EntityRelationship::Ground(e)
+                | EntityRelationship::Holding(e)
+                | EntityRelationship::Occupying(e)
+                | EntityRelationship::Wearing(e)
+                    if !candidates.iter().any(|c| c.key() == e.key()) =>
+                {
+                    candidates.push(e.clone());
+                }
+                _ => {}
+            }
+        }
+
+        candidates
+    }
+
+    fn referenced(&self) -> Result<Option<Referenced>> {
+        for entity in &self.entities {
+            if let EntityRelationship::Actor(actor) = entity {
+                return Ok(actor.scope::<Referenced>()?.map(|r| r.into()));
+            }
+        }
+
+        Ok(None)
+    }
+
     fn filter<P>(&self, mut predicate: P) -> EntityRelationshipSet
     where
         P: FnMut(&EntityRelationship) -> bool,
@@ -197,6 +392,14 @@ impl EntityRelationshipSet {
     }
 }
 
+fn many(mut entities: Vec<EntityPtr>) -> Option<Found> {
+    match entities.len() {
+        0 => None,
+        1 => Some(entities.remove(0).into()),
+        _ => Some(Found::Many(entities)),
+    }
+}
+
 fn default_priority(e: &EntityRelationship) -> u32 {
     match e {
         EntityRelationship::Area(_) => 1,
@@ -247,17 +450,30 @@ impl Finder for DefaultFinder {
         item: &Item,
     ) -> Result<Option<Found>, DomainError> {
         let haystack = EntityRelationshipSet::new_from_surroundings(surroundings).expand()?;
-        Ok(haystack.find_item(item)?)
+        let found = haystack
+            .find_item(item)
+            .map_err(|e| match e.downcast::<DomainError>() {
+                Ok(e) => e,
+                Err(e) => e.into(),
+            })?;
+
+        Ok(found)
+    }
+
+    fn find_nearby(&self, surroundings: &Surroundings) -> Result<Vec<EntityPtr>, DomainError> {
+        Ok(EntityRelationshipSet::new_from_surroundings(surroundings)
+            .expand()?
+            .candidates())
     }
 
     fn find_audience(
         &self,
         audience: &kernel::prelude::Audience,
-    ) -> Result<Vec<kernel::prelude::EntityKey>, DomainError> {
+    ) -> Result<Vec<Listener>, DomainError> {
         match audience {
             Audience::Nobody => Ok(Vec::new()),
             Audience::Everybody => todo![],
-            Audience::Individuals(keys) => Ok(keys.to_vec()),
+            Audience::Individuals(keys) => Ok(keys.iter().cloned().map(Listener::here).collect()),
             Audience::Area(area) => {
                 // If you find yourself here in the future, consider doing this
                 // lookup when the event is raised rather than in here.
@@ -265,8 +481,62 @@ impl Finder for DefaultFinder {
                 let area = session
                     .entity(&kernel::prelude::LookupBy::Key(area))?
                     .ok_or(DomainError::EntityNotFound(here!().into()))?;
-                Ok(tools::get_occupant_keys(&area)?)
+                Ok(tools::get_occupant_keys(&area)?
+                    .into_iter()
+                    .map(Listener::here)
+                    .collect())
+            }
+            Audience::Nearby { area, depth } => find_nearby_listeners(area, *depth),
+        }
+    }
+}
+
+/// Occupants of the area and of those areas reachable from it by following
+/// active routes, up to depth routes away. Each area is only visited once, at
+/// the shortest distance it's reachable from.
+fn find_nearby_listeners(area: &EntityKey, depth: u32) -> Result<Vec<Listener>, DomainError> {
+    let session = get_my_session()?;
+    let mut listeners = Vec::new();
+    let mut visited = vec![area.clone()];
+    let mut visiting: Vec<(EntityKey, Option<EntityKey>)> = vec![(area.clone(), None)];
+
+    for areas in 0..=depth {
+        let mut following = Vec::new();
+
+        for (key, from) in visiting {
+            let Some(area) = session.entity(&LookupBy::Key(&key))? else {
+                continue;
+            };
+            let Some(occupyable) = area.scope::<Occupyable>()? else {
+                continue;
+            };
+            let routes = occupyable.routes.clone().unwrap_or_default();
+
+            let distance = from.map(|from| Distance {
+                areas,
+                direction: routes
+                    .iter()
+                    .find(|r| r.destination().map(|d| *d.key() == from).unwrap_or(false))
+                    .map(|r| r.name().to_owned()),
+            });
+
+            listeners.extend(occupyable.occupied.iter().map(|e| Listener {
+                key: e.key().clone(),
+                distance: distance.clone(),
+            }));
+
+            if areas < depth {
+                for destination in routes.iter().flat_map(|r| r.destination()) {
+                    if !visited.contains(destination.key()) {
+                        visited.push(destination.key().clone());
+                        following.push((destination.key().clone(), Some(key.clone())));
+                    }
+                }
             }
         }
+
+        visiting = following;
     }
+
+    Ok(listeners)
 }
@@ -31,6 +31,13 @@ impl Plugin for HelpingPlugin {
         Self::plugin_key()
     }
 
+    fn schema(&self) -> Schema {
+        Schema::empty()
+            .action::<actions::ReadHelpAction>()
+            .action_requiring::<actions::EditHelpAction>(Role::Builder)
+            .action_requiring::<actions::SaveHelpAction>(Role::Builder)
+    }
+
     fn sources(&self) -> Vec<Box<dyn ActionSource>> {
         vec![Box::new(SaveHelpActionSource::default())]
     }
@@ -159,7 +166,7 @@ pub mod actions {
         }
 
         fn perform(&self, session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
-            let (world, _, _) = surroundings.unpack();
+            let (world, _, _) = surroundings.unpack()?;
 
             let page = lookup_page_name(&session, &world, self.page_name.as_deref(), false)?;
             let Some(page) = page else {
@@ -185,7 +192,7 @@ pub mod actions {
         fn perform(&self, session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
             info!("editing {:?}", self.page_name);
 
-            let (world, _, _) = surroundings.unpack();
+            let (world, _, _) = surroundings.unpack()?;
             let page = lookup_page_name(&session, &world, self.page_name.as_deref(), true)?;
             let Some(page) = page else {
                 return Ok(SimpleReply::NotFound.try_into()?);
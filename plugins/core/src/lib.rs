@@ -2,14 +2,18 @@ pub mod build;
 pub mod building;
 pub mod carrying;
 pub mod chat;
+pub mod choosing;
+pub mod consuming;
 pub mod emote;
 pub mod fashion;
 pub mod finding;
 pub mod helping;
 pub mod library;
 pub mod location;
+pub mod locking;
 pub mod looking;
 pub mod memory;
+pub mod moderation;
 pub mod moving;
 pub mod sched;
 pub mod security;
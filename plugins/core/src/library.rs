@@ -17,7 +17,7 @@ pub mod parser {
         character::complete::digit1,
         character::complete::one_of,
         combinator::map,
-        combinator::{map_res, opt, recognize},
+        combinator::{map_opt, map_res, opt, recognize, verify},
         multi::{many0, many1},
         sequence::delimited,
         sequence::{pair, preceded, separated_pair, terminated, tuple},
@@ -69,9 +69,54 @@ pub mod parser {
     }
 
     pub fn noun(i: &str) -> IResult<&str, Item> {
+        alt((pronoun, ordinal_noun, named))(i)
+    }
+
+    fn named(i: &str) -> IResult<&str, Item> {
         map(word, |s: &str| Item::Named(s.to_owned()))(i)
     }
 
+    pub fn pronoun(i: &str) -> IResult<&str, Item> {
+        map(verify(word, |s: &str| s == "it" || s == "them"), |_| {
+            Item::Pronoun
+        })(i)
+    }
+
+    pub fn everything(i: &str) -> IResult<&str, Item> {
+        map(
+            verify(word, |s: &str| s == "all" || s == "everything"),
+            |_| Item::All,
+        )(i)
+    }
+
+    const ORDINAL_WORDS: [&str; 10] = [
+        "first", "second", "third", "fourth", "fifth", "sixth", "seventh", "eighth", "ninth",
+        "tenth",
+    ];
+
+    fn ordinal(i: &str) -> IResult<&str, usize> {
+        let spelled = map_opt(word, |s: &str| {
+            ORDINAL_WORDS.iter().position(|w| *w == s).map(|p| p + 1)
+        });
+
+        let numbered = map_res(
+            terminated(digit1, alt((tag("st"), tag("nd"), tag("rd"), tag("th")))),
+            str::parse,
+        );
+
+        verify(alt((spelled, numbered)), |n: &usize| *n > 0)(i)
+    }
+
+    fn ordinal_noun(i: &str) -> IResult<&str, Item> {
+        map(
+            preceded(
+                opt(pair(tag("the"), spaces)),
+                separated_pair(ordinal, spaces, named),
+            ),
+            |(n, item)| Item::Ordinal(n, item.into()),
+        )(i)
+    }
+
     pub fn person(i: &str) -> IResult<&str, Item> {
         map(word, |s: &str| Item::Named(s.to_owned()))(i)
     }
@@ -160,10 +205,108 @@ pub mod parser {
         parser.try_parse_action(i)
     }
 
+    /// Matches text against an action's grammar, returning the captured
+    /// phrases in the order they appear.
+    pub fn parse_grammar(grammar: &str, i: &str) -> Result<Vec<english::Node>, EvaluationError> {
+        let tongue = english::to_tongue(grammar).ok_or(EvaluationError::ParseFailed)?;
+
+        match english::try_parse(&tongue, i) {
+            Some(english::Node::Phrase(nodes)) => Ok(nodes),
+            _ => Err(EvaluationError::ParseFailed),
+        }
+    }
+
+    /// Interprets a captured phrase, falling back to naming the item by the
+    /// whole phrase when it isn't something more specific.
+    pub fn item_phrase(i: &str) -> Item {
+        match alt((everything, noun_or_specific))(i) {
+            Ok(("", item)) => item,
+            _ => Item::Named(i.to_owned()),
+        }
+    }
+
+    /// Converts the captures of an action's grammar into the values of that
+    /// action's fields.
+    pub trait FromCapture: Sized {
+        fn from_capture(node: Option<english::Node>) -> Result<Self, EvaluationError>;
+    }
+
+    impl FromCapture for Item {
+        fn from_capture(node: Option<english::Node>) -> Result<Self, EvaluationError> {
+            match node {
+                Some(english::Node::Unheld(s)) => Ok(item_phrase(&s)),
+                Some(english::Node::Held(s)) => Ok(Item::Held(item_phrase(&s).into())),
+                Some(english::Node::Contained(s)) => Ok(Item::Contained(item_phrase(&s).into())),
+                Some(english::Node::Text(s)) => Ok(Item::Named(s)),
+                Some(english::Node::Phrase(_)) | None => Err(EvaluationError::ParseFailed),
+            }
+        }
+    }
+
+    impl<T: FromCapture> FromCapture for Option<T> {
+        fn from_capture(node: Option<english::Node>) -> Result<Self, EvaluationError> {
+            match node {
+                Some(node) => Ok(Some(T::from_capture(Some(node))?)),
+                None => Ok(None),
+            }
+        }
+    }
+
+    impl FromCapture for String {
+        fn from_capture(node: Option<english::Node>) -> Result<Self, EvaluationError> {
+            match node {
+                Some(english::Node::Text(s)) => Ok(s),
+                _ => Err(EvaluationError::ParseFailed),
+            }
+        }
+    }
+
     #[cfg(test)]
     mod tests {
         use super::*;
 
+        #[test]
+        pub fn test_noun() {
+            assert_eq!(noun("key").unwrap(), ("", Item::Named("key".to_owned())));
+            assert_eq!(noun("item").unwrap(), ("", Item::Named("item".to_owned())));
+            assert_eq!(noun("it").unwrap(), ("", Item::Pronoun));
+            assert_eq!(noun("them").unwrap(), ("", Item::Pronoun));
+        }
+
+        #[test]
+        pub fn test_ordinals() {
+            let second_key = Item::Ordinal(2, Item::Named("key".to_owned()).into());
+            assert_eq!(noun("second key").unwrap(), ("", second_key.clone()));
+            assert_eq!(noun("the second key").unwrap(), ("", second_key.clone()));
+            assert_eq!(noun("2nd key").unwrap(), ("", second_key));
+            assert_eq!(
+                noun("first").unwrap(),
+                ("", Item::Named("first".to_owned()))
+            );
+            assert!(ordinal("0th").is_err());
+        }
+
+        #[test]
+        pub fn test_everything() {
+            assert_eq!(everything("all").unwrap(), ("", Item::All));
+            assert_eq!(everything("everything").unwrap(), ("", Item::All));
+            assert!(everything("allspice").is_err());
+        }
+
+        #[test]
+        pub fn test_item_phrase() {
+            assert_eq!(item_phrase("key"), Item::Named("key".to_owned()));
+            assert_eq!(
+                item_phrase("brass key"),
+                Item::Named("brass key".to_owned())
+            );
+            assert_eq!(item_phrase("all"), Item::All);
+            assert_eq!(
+                item_phrase("10 coins"),
+                Item::Quantified(Quantity::Whole(10), Item::Named("coins".to_owned()).into())
+            );
+        }
+
         #[test]
         pub fn test_camel_case() {
             assert_eq!(camel_case_word("hello").unwrap(), ("", "hello"));
@@ -209,6 +352,7 @@ pub mod model {
 }
 
 pub mod actions {
+    pub use crate::library::parser::{parse_grammar, FromCapture};
     pub use crate::tools;
     pub use anyhow::Result;
     pub use kernel::common::*;
@@ -33,7 +33,7 @@ impl Plugin for LocationPlugin {
     }
 
     fn schema(&self) -> Schema {
-        Schema::empty().action::<actions::RelocateAction>()
+        Schema::empty().action_requiring::<actions::RelocateAction>(Role::Builder)
     }
 
     fn key(&self) -> &'static str {
@@ -14,7 +14,7 @@ fn it_moves_items_from_ourselves_to_here() -> Result<()> {
     let action = action.unwrap();
     let reply = action.perform(session, &surroundings)?;
 
-    let (_, person, area) = surroundings.unpack();
+    let (_, person, area) = surroundings.unpack()?;
 
     let reply: SimpleReply = reply.json_as()?;
     assert_eq!(reply, SimpleReply::Done);
@@ -40,7 +40,7 @@ fn it_moves_items_from_ourselves_to_another_place_by_gid() -> Result<()> {
     let action = action.unwrap();
     let reply = action.perform(session, &surroundings)?;
 
-    let (_, person, _) = surroundings.unpack();
+    let (_, person, _) = surroundings.unpack()?;
 
     let reply: SimpleReply = reply.json_as()?;
     assert_eq!(reply, SimpleReply::Done);
@@ -66,7 +66,7 @@ fn it_moves_items_from_area_to_area() -> Result<()> {
     let action = action.unwrap();
     let reply = action.perform(session, &surroundings)?;
 
-    let (_, person, area) = surroundings.unpack();
+    let (_, person, area) = surroundings.unpack()?;
 
     let reply: SimpleReply = reply.json_as()?;
     assert_eq!(reply, SimpleReply::Done);
@@ -92,7 +92,7 @@ fn it_moves_areas_into_areas() -> Result<()> {
     let action = action.unwrap();
     let reply = action.perform(session, &surroundings)?;
 
-    let (_, person, area) = surroundings.unpack();
+    let (_, person, area) = surroundings.unpack()?;
 
     let reply: SimpleReply = reply.json_as()?;
     assert_eq!(reply, SimpleReply::Done);
@@ -0,0 +1,76 @@
+use crate::library::plugin::*;
+
+pub mod actions;
+pub mod model;
+#[cfg(test)]
+mod tests;
+
+#[derive(Default)]
+pub struct LockingPluginFactory {}
+
+impl PluginFactory for LockingPluginFactory {
+    fn create_plugin(&self) -> Result<Box<dyn Plugin>> {
+        Ok(Box::new(LockingPlugin {}))
+    }
+
+    fn stop(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct LockingPlugin {}
+
+impl Plugin for LockingPlugin {
+    fn plugin_key() -> &'static str
+    where
+        Self: Sized,
+    {
+        "locking"
+    }
+
+    fn schema(&self) -> Schema {
+        Schema::empty()
+            .action::<actions::LockAction>()
+            .action::<actions::UnlockAction>()
+            .action::<actions::OpenAction>()
+            .action::<actions::CloseAction>()
+    }
+
+    fn key(&self) -> &'static str {
+        Self::plugin_key()
+    }
+
+    fn sources(&self) -> Vec<Box<dyn ActionSource>> {
+        vec![Box::new(ActionSources::default())]
+    }
+}
+
+impl ParsesActions for LockingPlugin {
+    fn try_parse_action(&self, i: &str) -> EvaluationResult {
+        try_parsing(actions::LockActionParser {}, i)
+            .or_else(|_| try_parsing(actions::UnlockActionParser {}, i))
+            .or_else(|_| try_parsing(actions::OpenActionParser {}, i))
+            .or_else(|_| try_parsing(actions::CloseActionParser {}, i))
+    }
+}
+
+#[derive(Default)]
+pub struct ActionSources {}
+
+impl ActionSource for ActionSources {
+    fn try_deserialize_action(
+        &self,
+        tagged: &TaggedJson,
+    ) -> Result<Option<Box<dyn Action>>, serde_json::Error> {
+        try_deserialize_all!(
+            tagged,
+            actions::LockAction,
+            actions::UnlockAction,
+            actions::OpenAction,
+            actions::CloseAction
+        );
+
+        Ok(None)
+    }
+}
@@ -0,0 +1,207 @@
+use crate::{
+    library::actions::*,
+    locking::model::{held_kinds, Lock, Lockable, Locking},
+    looking::model::Observe,
+    moving::model::Occupyable,
+};
+
+/// What's being locked, either an entity or a route leaving the area.
+enum Target {
+    Entity(EntityPtr),
+    Route(EntityPtr, String),
+}
+
+fn find_target(
+    session: &SessionRef,
+    surroundings: &Surroundings,
+    item: &Item,
+) -> Result<Option<Target>> {
+    if let Some(found) = session.find_item(surroundings, item)? {
+        return Ok(Some(Target::Entity(found.one()?)));
+    }
+
+    // Routes aren't entities, so when nothing nearby matches look for a
+    // route by that name.
+    let (_, _, area) = surroundings.unpack()?;
+    if let Item::Named(name) = item {
+        if let Some(occupyable) = area.scope::<Occupyable>()? {
+            if let Some(route) = occupyable.find_route(name) {
+                return Ok(Some(Target::Route(area.clone(), route.name().to_owned())));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// Routes are locked from both sides, so the route leading back from the
+/// destination gets the same lock.
+fn lock_returning_route(area: &EntityPtr, route: &str, lock: &Lock) -> Result<()> {
+    let Some(destination) = area
+        .scope::<Occupyable>()?
+        .and_then(|o| o.find_route(route).and_then(|r| r.destination().cloned()))
+    else {
+        return Ok(());
+    };
+
+    let destination = destination.to_entity()?;
+    let Some(returning) = destination.scope::<Occupyable>()?.and_then(|o| {
+        o.routes
+            .iter()
+            .flatten()
+            .find(|r| {
+                r.destination()
+                    .map(|d| *d.key() == area.key())
+                    .unwrap_or(false)
+            })
+            .map(|r| r.name().to_owned())
+    }) else {
+        return Ok(());
+    };
+
+    let mut lockable = destination.scope_mut::<Lockable>()?;
+    lockable.routes.insert(returning, lock.clone());
+    lockable.save()?;
+
+    Ok(())
+}
+
+fn change_lock(
+    session: &SessionRef,
+    surroundings: &Surroundings,
+    item: &Item,
+    change: impl FnOnce(&mut Lock, &str, &[Kind]) -> Result<(), String>,
+    event: impl FnOnce(ObservedEntity, ObservedEntity, Option<String>) -> Locking,
+) -> ReplyResult {
+    let (_, actor, area) = surroundings.unpack()?;
+
+    let (entity, name, route) = match find_target(session, surroundings, item)? {
+        Some(Target::Entity(entity)) => {
+            let name = entity.name()?;
+            (entity, name, None)
+        }
+        Some(Target::Route(area, route)) => (area, route.clone(), Some(route)),
+        None => return Ok(SimpleReply::NotFound.try_into()?),
+    };
+
+    let changed = {
+        let mut lockable = entity.scope_mut::<Lockable>()?;
+        let Some(lock) = lockable.lock_mut(route.as_deref()) else {
+            return Ok(SimpleReply::Impossible.try_into()?);
+        };
+
+        if let Err(reason) = change(lock, &name, &held_kinds(&actor)?) {
+            return Ok(SimpleReply::Prevented(Some(reason)).try_into()?);
+        }
+
+        let changed = lock.clone();
+        lockable.save()?;
+        changed
+    };
+
+    if let Some(route) = &route {
+        lock_returning_route(&entity, route, &changed)?;
+    }
+
+    reply_ok(
+        actor.clone(),
+        Audience::Area(area.key().clone()),
+        event(
+            (&actor).observe(&actor)?.expect("No observed entity"),
+            (&entity).observe(&actor)?.expect("No observed entity"),
+            route,
+        ),
+    )
+}
+
+#[action("LOCK #unheld")]
+pub struct LockAction {
+    pub item: Item,
+}
+
+impl Action for LockAction {
+    fn is_read_only(&self) -> bool {
+        false
+    }
+
+    fn perform(&self, session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
+        info!("lock {:?}!", self.item);
+
+        change_lock(
+            &session,
+            surroundings,
+            &self.item,
+            |lock, name, keys| lock.lock(name, keys),
+            |actor, item, route| Locking::Locked { actor, item, route },
+        )
+    }
+}
+
+#[action("UNLOCK #unheld")]
+pub struct UnlockAction {
+    pub item: Item,
+}
+
+impl Action for UnlockAction {
+    fn is_read_only(&self) -> bool {
+        false
+    }
+
+    fn perform(&self, session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
+        info!("unlock {:?}!", self.item);
+
+        change_lock(
+            &session,
+            surroundings,
+            &self.item,
+            |lock, name, keys| lock.unlock(name, keys),
+            |actor, item, route| Locking::Unlocked { actor, item, route },
+        )
+    }
+}
+
+#[action("OPEN #unheld")]
+pub struct OpenAction {
+    pub item: Item,
+}
+
+impl Action for OpenAction {
+    fn is_read_only(&self) -> bool {
+        false
+    }
+
+    fn perform(&self, session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
+        info!("open {:?}!", self.item);
+
+        change_lock(
+            &session,
+            surroundings,
+            &self.item,
+            |lock, name, _| lock.open(name),
+            |actor, item, route| Locking::Opened { actor, item, route },
+        )
+    }
+}
+
+#[action("CLOSE #unheld")]
+pub struct CloseAction {
+    pub item: Item,
+}
+
+impl Action for CloseAction {
+    fn is_read_only(&self) -> bool {
+        false
+    }
+
+    fn perform(&self, session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
+        info!("close {:?}!", self.item);
+
+        change_lock(
+            &session,
+            surroundings,
+            &self.item,
+            |lock, name, _| lock.close(name),
+            |actor, item, route| Locking::Closed { actor, item, route },
+        )
+    }
+}
@@ -0,0 +1,162 @@
+use std::collections::BTreeMap;
+
+use crate::{carrying::model::Carryable, library::model::*, tools};
+
+pub use kernel::common::Locking;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum LockState {
+    #[default]
+    Open,
+    Closed,
+    Locked,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct Lock {
+    /// Kinds of the carryable entities that work as keys for this lock.
+    pub keys: Vec<Kind>,
+    pub state: LockState,
+}
+
+impl Lock {
+    pub fn new(keys: Vec<Kind>) -> Self {
+        Self {
+            keys,
+            state: LockState::default(),
+        }
+    }
+
+    pub fn fits(&self, key: &Kind) -> bool {
+        self.keys.iter().any(|k| k == key)
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.state == LockState::Open
+    }
+
+    /// Why nothing can get past this lock, if anything.
+    pub fn preventing(&self, name: &str) -> Option<String> {
+        match self.state {
+            LockState::Open => None,
+            LockState::Closed => Some(format!("{} is closed.", name)),
+            LockState::Locked => Some(format!("{} is locked.", name)),
+        }
+    }
+
+    pub fn open(&mut self, name: &str) -> Result<(), String> {
+        match self.state {
+            LockState::Open => Err(format!("{} is already open.", name)),
+            LockState::Closed => {
+                self.state = LockState::Open;
+                Ok(())
+            }
+            LockState::Locked => Err(format!("{} is locked.", name)),
+        }
+    }
+
+    pub fn close(&mut self, name: &str) -> Result<(), String> {
+        match self.state {
+            LockState::Open => {
+                self.state = LockState::Closed;
+                Ok(())
+            }
+            LockState::Closed | LockState::Locked => Err(format!("{} is already closed.", name)),
+        }
+    }
+
+    pub fn lock(&mut self, name: &str, keys: &[Kind]) -> Result<(), String> {
+        match self.state {
+            LockState::Open => Err(format!("{} needs to be closed first.", name)),
+            LockState::Closed => {
+                self.unlocking_with(name, keys)?;
+                self.state = LockState::Locked;
+                Ok(())
+            }
+            LockState::Locked => Err(format!("{} is already locked.", name)),
+        }
+    }
+
+    pub fn unlock(&mut self, name: &str, keys: &[Kind]) -> Result<(), String> {
+        match self.state {
+            LockState::Open | LockState::Closed => Err(format!("{} isn't locked.", name)),
+            LockState::Locked => {
+                self.unlocking_with(name, keys)?;
+                self.state = LockState::Closed;
+                Ok(())
+            }
+        }
+    }
+
+    fn unlocking_with(&self, name: &str, keys: &[Kind]) -> Result<(), String> {
+        if keys.iter().any(|key| self.fits(key)) {
+            Ok(())
+        } else {
+            Err(format!("You don't have a key that fits {}.", name))
+        }
+    }
+}
+
+/// Locks on an entity, either on the entity itself when it's a container or
+/// on the routes leading out of it when it's an area. Routes are keyed by
+/// their name.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct Lockable {
+    pub lock: Option<Lock>,
+    #[serde(default)]
+    pub routes: BTreeMap<String, Lock>,
+}
+
+impl Scope for Lockable {
+    fn scope_key() -> &'static str {
+        "lockable"
+    }
+}
+
+impl Lockable {
+    pub fn lock_mut(&mut self, route: Option<&str>) -> Option<&mut Lock> {
+        match route {
+            Some(route) => self.routes.get_mut(route),
+            None => self.lock.as_mut(),
+        }
+    }
+}
+
+/// Why the container can't be reached into, if it's closed or locked.
+pub fn container_preventing(container: &EntityPtr) -> Result<Option<String>, DomainError> {
+    let Some(lockable) = container.scope::<Lockable>()? else {
+        return Ok(None);
+    };
+
+    let name = container.name()?;
+
+    Ok(lockable
+        .lock
+        .as_ref()
+        .and_then(|lock| lock.preventing(&name)))
+}
+
+/// Why the route leaving the area can't be taken, if it's closed or locked.
+pub fn route_preventing(area: &EntityPtr, route: &str) -> Result<Option<String>, DomainError> {
+    let Some(lockable) = area.scope::<Lockable>()? else {
+        return Ok(None);
+    };
+
+    Ok(lockable
+        .routes
+        .get(route)
+        .and_then(|lock| lock.preventing(route)))
+}
+
+/// Kinds of everything the actor is holding, any of which may be a key.
+pub fn held_kinds(actor: &EntityPtr) -> Result<Vec<Kind>, DomainError> {
+    let mut kinds = Vec::new();
+    for held in tools::contained_by(actor)? {
+        if let Some(carryable) = held.scope::<Carryable>()? {
+            kinds.push(carryable.kind().clone());
+        }
+    }
+
+    Ok(kinds)
+}
@@ -0,0 +1,263 @@
+use super::actions::*;
+use super::model::*;
+use crate::carrying::actions::{PutInsideActionParser, TakeOutActionParser};
+use crate::carrying::model::Carryable;
+use crate::library::tests::*;
+use crate::moving::actions::GoAction;
+use crate::moving::model::{Occupyable, Route, SimpleRoute};
+
+fn kind_of(entity: &EntityPtr) -> Result<Kind> {
+    Ok(entity.scope::<Carryable>()?.unwrap().kind().clone())
+}
+
+fn add_lock(entity: &EntityPtr, route: Option<&str>, lock: Lock) -> Result<()> {
+    let mut lockable = entity.scope_mut::<Lockable>()?;
+    match route {
+        Some(route) => {
+            lockable.routes.insert(route.to_owned(), lock);
+        }
+        None => lockable.lock = Some(lock),
+    }
+    lockable.save()?;
+
+    Ok(())
+}
+
+fn state_of(entity: &EntityPtr, route: Option<&str>) -> Result<LockState> {
+    let mut lockable = entity.scope_mut::<Lockable>()?;
+    Ok(lockable.lock_mut(route).unwrap().state)
+}
+
+#[test]
+fn it_locks_and_unlocks_containers_with_fitting_keys() -> Result<()> {
+    let mut build = BuildSurroundings::new()?;
+    let key = build.make(QuickThing::Object("Brass Key"))?;
+    let chest = build
+        .entity()?
+        .named("Chest")?
+        .save()?
+        .carryable()?
+        .holding(&vec![])?
+        .into_entity()?;
+    add_lock(&chest, None, Lock::new(vec![kind_of(&key)?]))?;
+    let (session, surroundings) = build
+        .ground(vec![QuickThing::Actual(chest.clone())])
+        .hands(vec![QuickThing::Actual(key)])
+        .build()?;
+
+    let action = try_parsing(LockActionParser {}, "lock chest")?.unwrap();
+    let reply: SimpleReply = action.perform(session.clone(), &surroundings)?.json_as()?;
+    assert_eq!(
+        reply,
+        SimpleReply::Prevented(Some("Chest needs to be closed first.".to_owned()))
+    );
+
+    let action = try_parsing(CloseActionParser {}, "close chest")?.unwrap();
+    assert_eq!(action.perform(session.clone(), &surroundings)?, Effect::Ok);
+
+    let action = try_parsing(LockActionParser {}, "lock chest")?.unwrap();
+    assert_eq!(action.perform(session.clone(), &surroundings)?, Effect::Ok);
+    assert_eq!(state_of(&chest, None)?, LockState::Locked);
+
+    let action = try_parsing(OpenActionParser {}, "open chest")?.unwrap();
+    let reply: SimpleReply = action.perform(session.clone(), &surroundings)?.json_as()?;
+    assert_eq!(
+        reply,
+        SimpleReply::Prevented(Some("Chest is locked.".to_owned()))
+    );
+
+    let action = try_parsing(UnlockActionParser {}, "unlock chest")?.unwrap();
+    assert_eq!(action.perform(session.clone(), &surroundings)?, Effect::Ok);
+    assert_eq!(state_of(&chest, None)?, LockState::Closed);
+
+    build.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn it_prevents_locking_without_a_fitting_key() -> Result<()> {
+    let mut build = BuildSurroundings::new()?;
+    let key = build.make(QuickThing::Object("Brass Key"))?;
+    let chest = build
+        .entity()?
+        .named("Chest")?
+        .save()?
+        .carryable()?
+        .holding(&vec![])?
+        .into_entity()?;
+    let mut lock = Lock::new(vec![kind_of(&key)?]);
+    lock.state = LockState::Closed;
+    add_lock(&chest, None, lock)?;
+    let (session, surroundings) = build
+        .ground(vec![QuickThing::Actual(chest.clone())])
+        .hands(vec![QuickThing::Object("Iron Key")])
+        .build()?;
+
+    let action = try_parsing(LockActionParser {}, "lock chest")?.unwrap();
+    let reply: SimpleReply = action.perform(session.clone(), &surroundings)?.json_as()?;
+    assert_eq!(
+        reply,
+        SimpleReply::Prevented(Some("You don't have a key that fits Chest.".to_owned()))
+    );
+    assert_eq!(state_of(&chest, None)?, LockState::Closed);
+
+    build.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn it_prevents_putting_items_in_or_taking_them_out_of_closed_containers() -> Result<()> {
+    let mut build = BuildSurroundings::new()?;
+    let coin = build.make(QuickThing::Object("Coin"))?;
+    let chest = build
+        .entity()?
+        .named("Chest")?
+        .save()?
+        .carryable()?
+        .holding(&vec![coin])?
+        .into_entity()?;
+    let mut lock = Lock::new(vec![]);
+    lock.state = LockState::Closed;
+    add_lock(&chest, None, lock)?;
+    let (session, surroundings) = build
+        .hands(vec![
+            QuickThing::Object("Rock"),
+            QuickThing::Actual(chest.clone()),
+        ])
+        .build()?;
+
+    let prevented = SimpleReply::Prevented(Some("Chest is closed.".to_owned()));
+
+    let action = try_parsing(PutInsideActionParser {}, "put rock in chest")?.unwrap();
+    let reply: SimpleReply = action.perform(session.clone(), &surroundings)?.json_as()?;
+    assert_eq!(reply, prevented);
+
+    let action = try_parsing(TakeOutActionParser {}, "take coin out of chest")?.unwrap();
+    let reply: SimpleReply = action.perform(session.clone(), &surroundings)?.json_as()?;
+    assert_eq!(reply, prevented);
+
+    let action = try_parsing(OpenActionParser {}, "open chest")?.unwrap();
+    assert_eq!(action.perform(session.clone(), &surroundings)?, Effect::Ok);
+
+    let action = try_parsing(PutInsideActionParser {}, "put rock in chest")?.unwrap();
+    let reply: SimpleReply = action.perform(session.clone(), &surroundings)?.json_as()?;
+    assert_eq!(reply, SimpleReply::Done);
+
+    build.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn it_prevents_going_through_locked_routes() -> Result<()> {
+    let mut build = BuildSurroundings::new()?;
+    let key = build.make(QuickThing::Object("Brass Key"))?;
+    let east = build.make(QuickThing::Place("East Place"))?;
+    let (session, surroundings) = build
+        .route("East", QuickThing::Actual(east.clone()))
+        .hands(vec![QuickThing::Actual(key.clone())])
+        .build()?;
+    let (_, actor, area) = surroundings.unpack()?;
+    let mut occupyable = east.scope_mut::<Occupyable>()?;
+    occupyable.add_route(Route::Simple(SimpleRoute::new("West", area.entity_ref())));
+    occupyable.save()?;
+    let mut lock = Lock::new(vec![kind_of(&key)?]);
+    lock.state = LockState::Locked;
+    add_lock(&area, Some("East"), lock)?;
+
+    let action = GoAction {
+        item: Item::Route("east".to_owned()),
+    };
+    let reply: SimpleReply = action.perform(session.clone(), &surroundings)?.json_as()?;
+    assert_eq!(
+        reply,
+        SimpleReply::Prevented(Some("East is locked.".to_owned()))
+    );
+    assert_eq!(tools::area_of(&actor)?.key(), area.key());
+
+    let action = try_parsing(UnlockActionParser {}, "unlock east")?.unwrap();
+    assert_eq!(action.perform(session.clone(), &surroundings)?, Effect::Ok);
+    let action = try_parsing(OpenActionParser {}, "open east")?.unwrap();
+    assert_eq!(action.perform(session.clone(), &surroundings)?, Effect::Ok);
+    assert_eq!(state_of(&area, Some("East"))?, LockState::Open);
+    assert_eq!(state_of(&east, Some("West"))?, LockState::Open);
+
+    let action = GoAction {
+        item: Item::Route("east".to_owned()),
+    };
+    action.perform(session.clone(), &surroundings)?;
+    assert_eq!(tools::area_of(&actor)?.key(), east.key());
+
+    build.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn it_prevents_going_through_locked_deactivated_routes() -> Result<()> {
+    let mut build = BuildSurroundings::new()?;
+    let east = build.make(QuickThing::Place("East Place"))?;
+    let (session, surroundings) = build
+        .route("East", QuickThing::Actual(east.clone()))
+        .build()?;
+    let (_, actor, area) = surroundings.unpack()?;
+    let mut occupyable = area.scope_mut::<Occupyable>()?;
+    occupyable.add_route(Route::Deactivated(
+        "The bridge is out.".to_owned(),
+        Route::Simple(SimpleRoute::new("East", east.entity_ref())).into(),
+    ));
+    occupyable.save()?;
+    let mut lock = Lock::new(vec![]);
+    lock.state = LockState::Locked;
+    add_lock(&area, Some("East"), lock)?;
+
+    let action = GoAction {
+        item: Item::Route("east".to_owned()),
+    };
+    let reply: SimpleReply = action.perform(session.clone(), &surroundings)?.json_as()?;
+    assert_eq!(
+        reply,
+        SimpleReply::Prevented(Some("East is locked.".to_owned()))
+    );
+    assert_eq!(tools::area_of(&actor)?.key(), area.key());
+
+    build.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn it_locks_and_unlocks_held_containers() -> Result<()> {
+    let mut build = BuildSurroundings::new()?;
+    let key = build.make(QuickThing::Object("Brass Key"))?;
+    let chest = build
+        .entity()?
+        .named("Chest")?
+        .save()?
+        .carryable()?
+        .holding(&vec![])?
+        .into_entity()?;
+    let mut lock = Lock::new(vec![kind_of(&key)?]);
+    lock.state = LockState::Closed;
+    add_lock(&chest, None, lock)?;
+    let (session, surroundings) = build
+        .hands(vec![
+            QuickThing::Actual(chest.clone()),
+            QuickThing::Actual(key),
+        ])
+        .build()?;
+
+    let action = try_parsing(LockActionParser {}, "lock chest")?.unwrap();
+    assert_eq!(action.perform(session.clone(), &surroundings)?, Effect::Ok);
+    assert_eq!(state_of(&chest, None)?, LockState::Locked);
+
+    let action = try_parsing(UnlockActionParser {}, "unlock chest")?.unwrap();
+    assert_eq!(action.perform(session.clone(), &surroundings)?, Effect::Ok);
+    assert_eq!(state_of(&chest, None)?, LockState::Closed);
+
+    build.close()?;
+
+    Ok(())
+}
@@ -32,7 +32,10 @@ impl Plugin for LookingPlugin {
     }
 
     fn schema(&self) -> Schema {
-        Schema::empty().action::<actions::LookAction>()
+        Schema::empty()
+            .action::<actions::LookAction>()
+            .action::<actions::LookInsideAction>()
+            .action::<actions::LookAtAction>()
     }
 
     fn sources(&self) -> Vec<Box<dyn ActionSource>> {
@@ -119,18 +122,32 @@ pub mod model {
         Ok(Some(observed))
     }
 
+    const NAME_PATH: &str = "core.name";
+    const DESC_PATH: &str = "core.desc";
+
     pub trait Observe<T> {
         fn observe(&self, user: &EntityPtr) -> Result<Option<T>, DomainError>;
     }
 
     impl Observe<ObservedEntity> for &EntityPtr {
-        fn observe(&self, _user: &EntityPtr) -> Result<Option<ObservedEntity>, DomainError> {
+        fn observe(&self, user: &EntityPtr) -> Result<Option<ObservedEntity>, DomainError> {
             let quantity = self.scope::<Carryable>()?.map(|c| c.quantity());
             let key = self.key().to_string();
             let gid = self.gid().into();
             let observing = self.entity().borrow();
+            let props = observing.scope_json_for(&user.key(), Properties::scope_key())?;
+            let readable = |path: &str| {
+                props
+                    .as_ref()
+                    .and_then(|props| DottedPath::from(path).lookup(props))
+                    .is_some()
+            };
+            // Entities whose names can't be read aren't seen at all.
+            if !readable(NAME_PATH) {
+                return Ok(None);
+            }
             let name = observing.name();
-            let desc = observing.desc();
+            let desc = observing.desc().filter(|_| readable(DESC_PATH));
             let qualified = match quantity {
                 Some(quantity) => Unqualified::Quantity(quantity, &name),
                 None => Unqualified::Living(&name),
@@ -184,18 +201,19 @@ pub mod model {
                 }
             }
             if let Some(outgoing) = &occupyable.routes {
-                routes.extend(outgoing.iter().map(|r| {
+                for r in outgoing.iter() {
                     match r {
-                        crate::moving::model::Route::Simple(r) => ObservedRoute::Simple {
-                            name: r.name().to_owned(),
-                            to: (&r.destination().to_entity().unwrap())
-                                .observe(user)
-                                .unwrap()
-                                .unwrap(),
-                        },
+                        crate::moving::model::Route::Simple(r) => {
+                            if let Some(to) = (&r.destination().to_entity()?).observe(user)? {
+                                routes.push(ObservedRoute::Simple {
+                                    name: r.name().to_owned(),
+                                    to,
+                                });
+                            }
+                        }
                         crate::moving::model::Route::Deactivated(_, _) => todo!(),
                     }
-                }))
+                }
             }
         }
 
@@ -249,7 +267,7 @@ pub mod actions {
         }
 
         fn perform(&self, _session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
-            let (_, user, area) = surroundings.unpack();
+            let (_, user, area) = surroundings.unpack()?;
 
             Ok(new_area_observation(&user, &area)
                 .with_context(|| "Observing area")?
@@ -268,7 +286,7 @@ pub mod actions {
         }
 
         fn perform(&self, session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
-            let (_, user, _area) = surroundings.unpack();
+            let (_, user, _area) = surroundings.unpack()?;
 
             match session.find_item(surroundings, &self.item)? {
                 Some(actor) => {
@@ -298,7 +316,7 @@ pub mod actions {
         }
 
         fn perform(&self, session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
-            let (_, user, _area) = surroundings.unpack();
+            let (_, user, _area) = surroundings.unpack()?;
 
             match session.find_item(surroundings, &self.item)? {
                 Some(item) => match new_entity_observation(&user, &item.one()?)? {
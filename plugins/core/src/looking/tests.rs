@@ -12,7 +12,7 @@ fn it_looks_in_empty_area() -> Result<()> {
     let action = try_parsing(LookActionParser {}, "look")?;
     let action = action.unwrap();
     let reply = action.perform(session.clone(), &surroundings)?;
-    let (_, _person, _area) = surroundings.unpack();
+    let (_, _person, _area) = surroundings.unpack()?;
 
     insta::assert_json_snapshot!(reply.to_debug_json()?);
 
@@ -32,7 +32,7 @@ fn it_looks_in_area_with_items_on_ground() -> Result<()> {
     let action = try_parsing(LookActionParser {}, "look")?;
     let action = action.unwrap();
     let reply = action.perform(session.clone(), &surroundings)?;
-    let (_, _person, _area) = surroundings.unpack();
+    let (_, _person, _area) = surroundings.unpack()?;
 
     insta::assert_json_snapshot!(reply.to_debug_json()?);
 
@@ -54,7 +54,7 @@ fn it_looks_in_area_with_items_on_ground_and_holding_items() -> Result<()> {
     let action = try_parsing(LookActionParser {}, "look")?;
     let action = action.unwrap();
     let reply = action.perform(session.clone(), &surroundings)?;
-    let (_, _person, _area) = surroundings.unpack();
+    let (_, _person, _area) = surroundings.unpack()?;
 
     insta::assert_json_snapshot!(reply.to_debug_json()?);
 
@@ -71,7 +71,7 @@ fn it_fails_to_look_inside_non_containers() -> Result<()> {
     let action = try_parsing(LookActionParser {}, "look inside box")?;
     let action = action.unwrap();
     let reply = action.perform(session.clone(), &surroundings)?;
-    let (_, _person, _area) = surroundings.unpack();
+    let (_, _person, _area) = surroundings.unpack()?;
 
     insta::assert_json_snapshot!(reply.to_debug_json()?);
 
@@ -95,7 +95,7 @@ fn it_looks_inside_containers() -> Result<()> {
     let action = try_parsing(LookActionParser {}, "look inside vessel")?;
     let action = action.unwrap();
     let reply = action.perform(session.clone(), &surroundings)?;
-    let (_, _person, _area) = surroundings.unpack();
+    let (_, _person, _area) = surroundings.unpack()?;
 
     insta::assert_json_snapshot!(reply.to_debug_json()?);
 
@@ -118,7 +118,7 @@ fn it_fails_to_look_at_not_found_entities() -> Result<()> {
     let action = try_parsing(LookActionParser {}, "look at shovel")?;
     let action = action.unwrap();
     let reply = action.perform(session.clone(), &surroundings)?;
-    let (_, _person, _area) = surroundings.unpack();
+    let (_, _person, _area) = surroundings.unpack()?;
 
     insta::assert_json_snapshot!(reply.to_debug_json()?);
 
@@ -141,7 +141,7 @@ fn it_looks_at_entities() -> Result<()> {
     let action = try_parsing(LookActionParser {}, "look at hammer")?;
     let action = action.unwrap();
     let reply = action.perform(session.clone(), &surroundings)?;
-    let (_, _person, _area) = surroundings.unpack();
+    let (_, _person, _area) = surroundings.unpack()?;
 
     insta::assert_json_snapshot!(reply.to_debug_json()?);
 
@@ -165,7 +165,7 @@ fn it_sees_worm_items() -> Result<()> {
     let action = try_parsing(LookActionParser {}, "look at myself")?;
     let action = action.unwrap();
     let reply = action.perform(session.clone(), &surroundings)?;
-    let (_, _person, _area) = surroundings.unpack();
+    let (_, _person, _area) = surroundings.unpack()?;
 
     insta::assert_json_snapshot!(reply.to_debug_json()?);
 
@@ -190,3 +190,36 @@ fn qualify_name_basics() {
     );
     assert_eq!(Unqualified::Living("Jacob").qualify(), "Jacob");
 }
+
+#[test]
+fn it_leaves_out_entities_whose_names_are_unreadable() -> Result<()> {
+    let mut build = BuildSurroundings::new()?;
+    let shovel = build.make(QuickThing::Object("Secret Shovel"))?;
+    let mut json = shovel.borrow().to_json_value()?;
+    json["acls"] = serde_json::json!({ "rules": [{ "perm": "Read", "sub": ["Owner"] }] });
+    *shovel.borrow_mut() = Entity::from_value(json)?;
+    let (session, surroundings) = build
+        .ground(vec![
+            QuickThing::Object("Cool Rake"),
+            QuickThing::Actual(shovel),
+        ])
+        .build()?;
+
+    let action = try_parsing(LookActionParser {}, "look")?;
+    let action = action.unwrap();
+    let reply = action.perform(session.clone(), &surroundings)?;
+
+    let reply: AreaObservation = reply.json_as()?;
+    assert_eq!(
+        reply
+            .items
+            .iter()
+            .map(|i| i.name.as_str())
+            .collect::<Vec<_>>(),
+        vec!["Cool Rake"]
+    );
+
+    build.close()?;
+
+    Ok(())
+}
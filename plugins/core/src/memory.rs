@@ -28,6 +28,10 @@ impl Plugin for MemoryPlugin {
         Self::plugin_key()
     }
 
+    fn schema(&self) -> Schema {
+        Schema::empty().action::<actions::RecallAction>()
+    }
+
     fn sources(&self) -> Vec<Box<dyn ActionSource>> {
         vec![Box::new(ActionSources::default())]
     }
@@ -164,7 +168,7 @@ pub mod actions {
         }
 
         fn perform(&self, _session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
-            let (_world, actor, _area) = surroundings.unpack();
+            let (_world, actor, _area) = surroundings.unpack()?;
             let memories = memories_of(&actor)?;
             Ok(RecallReply {
                 memories: memories.into_iter().map(|m| m.into()).collect(),
@@ -205,7 +209,7 @@ mod tests {
         let action = try_parsing(RecallActionParser {}, "recall")?;
         let action = action.unwrap();
         let reply = action.perform(session.clone(), &surroundings)?;
-        let (_, _person, _area) = surroundings.unpack();
+        let (_, _person, _area) = surroundings.unpack()?;
 
         insta::assert_json_snapshot!(reply.to_debug_json()?);
 
@@ -219,7 +223,7 @@ mod tests {
         let mut build = BuildSurroundings::new()?;
         let (session, surroundings) = build.build()?;
 
-        let (_, actor, _) = surroundings.clone().unpack();
+        let (_, actor, _) = surroundings.clone().unpack()?;
         let time = Utc.with_ymd_and_hms(1982, 4, 23, 0, 0, 0).unwrap();
         remember(
             &actor,
@@ -234,7 +238,7 @@ mod tests {
         let action = try_parsing(RecallActionParser {}, "recall")?;
         let action = action.unwrap();
         let reply = action.perform(session.clone(), &surroundings)?;
-        let (_, _person, _area) = surroundings.unpack();
+        let (_, _person, _area) = surroundings.unpack()?;
 
         insta::assert_json_snapshot!(reply.to_debug_json()?);
 
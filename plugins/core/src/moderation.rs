@@ -0,0 +1,126 @@
+use chrono::Utc;
+use std::rc::Rc;
+
+use crate::library::plugin::*;
+use crate::{chat::actions::*, emote::actions::LaughAction, looking::actions::LookAction};
+use model::{Moderation, Restriction};
+
+pub mod actions;
+pub mod model;
+pub mod parser;
+#[cfg(test)]
+mod tests;
+
+#[derive(Default)]
+pub struct ModerationPluginFactory {}
+
+impl PluginFactory for ModerationPluginFactory {
+    fn create_plugin(&self) -> Result<Box<dyn Plugin>> {
+        Ok(Box::new(ModerationPlugin {}))
+    }
+
+    fn stop(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct ModerationPlugin {}
+
+impl Plugin for ModerationPlugin {
+    fn plugin_key() -> &'static str
+    where
+        Self: Sized,
+    {
+        "moderation"
+    }
+
+    fn schema(&self) -> Schema {
+        Schema::empty()
+            .action_requiring::<actions::ModerateAction>(Role::Admin)
+            .action_requiring::<actions::LiftModerationAction>(Role::Admin)
+            .action_requiring::<actions::ModerationLogAction>(Role::Admin)
+            .action_requiring::<actions::ExpireModerationAction>(Role::Admin)
+    }
+
+    fn key(&self) -> &'static str {
+        Self::plugin_key()
+    }
+
+    fn sources(&self) -> Vec<Box<dyn ActionSource>> {
+        vec![Box::new(ActionSources::default())]
+    }
+
+    fn middleware(&mut self) -> Result<Vec<Rc<dyn Middleware>>> {
+        Ok(vec![Rc::new(ModerationMiddleware {})])
+    }
+}
+
+impl ParsesActions for ModerationPlugin {
+    fn try_parse_action(&self, i: &str) -> EvaluationResult {
+        try_parsing(parser::ModerateActionParser {}, i)
+            .or_else(|_| try_parsing(parser::LiftModerationActionParser {}, i))
+            .or_else(|_| try_parsing(parser::ModerationLogActionParser {}, i))
+    }
+}
+
+#[derive(Default)]
+pub struct ActionSources {}
+
+impl ActionSource for ActionSources {
+    fn try_deserialize_action(
+        &self,
+        tagged: &TaggedJson,
+    ) -> Result<Option<Box<dyn Action>>, serde_json::Error> {
+        try_deserialize_all!(
+            tagged,
+            actions::ModerateAction,
+            actions::LiftModerationAction,
+            actions::ModerationLogAction,
+            actions::ExpireModerationAction
+        );
+
+        Ok(None)
+    }
+}
+
+/// Prevents frozen actors from doing anything besides looking around and
+/// muted actors from speaking, shouting or emoting.
+pub struct ModerationMiddleware {}
+
+impl Middleware for ModerationMiddleware {
+    fn handle(&self, value: Perform, next: MiddlewareNext) -> Result<Effect, anyhow::Error> {
+        if let Perform::Surroundings {
+            surroundings: Surroundings::Actor { actor, .. },
+            action,
+        } = &value
+        {
+            if let Some(moderation) = actor.scope::<Moderation>()? {
+                let tagged = match action {
+                    PerformAction::Instance(action) => action.to_tagged_json()?,
+                    PerformAction::TaggedJson(tagged) => tagged.clone(),
+                };
+                let tag = tagged.tag();
+                let now = Utc::now();
+
+                let prevented = if moderation.is(Restriction::Frozen, now) {
+                    tag != LookAction::tag()
+                } else if moderation.is(Restriction::Muted, now) {
+                    tag == SpeakAction::tag()
+                        || tag == ShoutAction::tag()
+                        || tag == LaughAction::tag()
+                } else {
+                    false
+                };
+
+                if prevented {
+                    info!(action = tag, "moderated");
+
+                    return Ok(Effect::Prevented);
+                }
+            }
+        }
+
+        next.handle(value)
+    }
+}
@@ -0,0 +1,207 @@
+use chrono::{DateTime, Duration, Utc};
+use engine::prelude::HasUsernames;
+
+use crate::{
+    library::actions::*,
+    looking::model::Observe,
+    moderation::model::{Moderation, Restriction, Sanction},
+};
+
+fn find_user(world: &EntityPtr, username: &str) -> Result<Option<EntityPtr>> {
+    let Some(key) = world.find_name_key(username)? else {
+        return Ok(None);
+    };
+
+    Ok(get_my_session()?.entity(&LookupBy::Key(&key))?)
+}
+
+/// Restricts a user, for the given number of seconds or until the
+/// restriction is lifted.
+#[action]
+pub struct ModerateAction {
+    pub username: String,
+    pub restriction: Restriction,
+    pub seconds: Option<u64>,
+    pub reason: Option<String>,
+}
+
+impl Action for ModerateAction {
+    fn is_read_only(&self) -> bool {
+        false
+    }
+
+    fn perform(&self, session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
+        let (world, actor, _area) = surroundings.unpack()?;
+        let Some(user) = find_user(&world, &self.username)? else {
+            return Ok(SimpleReply::NotFound.try_into()?);
+        };
+
+        info!(username = %self.username, restriction = %self.restriction, seconds = ?self.seconds, "moderating");
+
+        let now = Utc::now();
+        let expires = self
+            .seconds
+            .map(|seconds| now + Duration::seconds(seconds as i64));
+
+        let mut moderation = user.scope_mut::<Moderation>()?;
+        moderation.impose(
+            self.restriction,
+            Sanction {
+                since: now,
+                expires,
+                reason: self.reason.clone(),
+            },
+            Some(actor.key()),
+        );
+        moderation.save()?;
+
+        if let Some(expires) = expires {
+            session.schedule(FutureAction::new(
+                format!(
+                    "{}-{}-{}",
+                    user.key(),
+                    self.restriction,
+                    expires.timestamp_millis()
+                ),
+                world.key(),
+                FutureSchedule::Utc(expires),
+                ExpireModerationAction {
+                    user: user.key(),
+                    restriction: self.restriction,
+                    expires,
+                }
+                .to_tagged_json()?,
+            ))?;
+        }
+
+        Ok(SimpleReply::Done.try_into()?)
+    }
+}
+
+#[action]
+pub struct LiftModerationAction {
+    pub username: String,
+    pub restriction: Restriction,
+}
+
+impl Action for LiftModerationAction {
+    fn is_read_only(&self) -> bool {
+        false
+    }
+
+    fn perform(&self, _session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
+        let (world, actor, _area) = surroundings.unpack()?;
+        let Some(user) = find_user(&world, &self.username)? else {
+            return Ok(SimpleReply::NotFound.try_into()?);
+        };
+
+        info!(username = %self.username, restriction = %self.restriction, "lifting");
+
+        let mut moderation = user.scope_mut::<Moderation>()?;
+        if !moderation.lift(self.restriction, Utc::now(), Some(actor.key())) {
+            return Ok(SimpleReply::Impossible.try_into()?);
+        }
+        moderation.save()?;
+
+        Ok(SimpleReply::Done.try_into()?)
+    }
+}
+
+/// Scheduled on the world when a restriction is imposed for a while, to
+/// remove it once it's over. Actors performing this are refused, so it can
+/// only be delivered as a future.
+#[action]
+pub struct ExpireModerationAction {
+    pub user: EntityKey,
+    pub restriction: Restriction,
+    pub expires: DateTime<Utc>,
+}
+
+impl Action for ExpireModerationAction {
+    fn is_read_only(&self) -> bool {
+        false
+    }
+
+    fn perform(&self, session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
+        if matches!(surroundings, Surroundings::Actor { .. }) {
+            warn!(user = %self.user, "expiring moderation as an actor");
+
+            return Ok(Effect::Prevented);
+        }
+
+        let Some(user) = session.entity(&LookupBy::Key(&self.user))? else {
+            return Ok(SimpleReply::NotFound.try_into()?);
+        };
+
+        let mut moderation = user.scope_mut::<Moderation>()?;
+        if moderation.expire(self.restriction, self.expires, Utc::now()) {
+            info!(user = %self.user, restriction = %self.restriction, "expired");
+
+            moderation.save()?;
+        }
+
+        Ok(Effect::Ok)
+    }
+}
+
+#[action]
+pub struct ModerationLogAction {
+    pub username: String,
+}
+
+impl Action for ModerationLogAction {
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
+    fn perform(&self, session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
+        let (world, actor, _area) = surroundings.unpack()?;
+        let Some(user) = find_user(&world, &self.username)? else {
+            return Ok(SimpleReply::NotFound.try_into()?);
+        };
+
+        let Some(entity) = (&user).observe(&actor)? else {
+            return Ok(SimpleReply::NotFound.try_into()?);
+        };
+
+        let Some(moderation) = user.scope::<Moderation>()? else {
+            return Ok(ModerationReply {
+                entity,
+                frozen: false,
+                muted: false,
+                log: Vec::new(),
+            }
+            .try_into()?);
+        };
+
+        let now = Utc::now();
+        let log = moderation
+            .log()
+            .iter()
+            .map(|logged| {
+                let by = match &logged.by {
+                    Some(key) => session.entity(&LookupBy::Key(key))?.map(|e| e.name()),
+                    None => None,
+                }
+                .transpose()?;
+
+                Ok(ObservedModeration {
+                    time: logged.time.to_rfc3339(),
+                    restriction: logged.restriction.to_string(),
+                    change: logged.change.to_string(),
+                    reason: logged.reason.clone(),
+                    expires: logged.expires.as_ref().map(DateTime::to_rfc3339),
+                    by,
+                })
+            })
+            .collect::<Result<Vec<_>, DomainError>>()?;
+
+        Ok(ModerationReply {
+            entity,
+            frozen: moderation.is(Restriction::Frozen, now),
+            muted: moderation.is(Restriction::Muted, now),
+            log,
+        }
+        .try_into()?)
+    }
+}
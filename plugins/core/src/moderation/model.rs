@@ -0,0 +1,175 @@
+use crate::library::model::*;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Restriction {
+    /// Nothing but looking around is allowed.
+    Frozen,
+    /// Speaking, shouting and emoting aren't allowed.
+    Muted,
+}
+
+impl std::fmt::Display for Restriction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Restriction::Frozen => write!(f, "frozen"),
+            Restriction::Muted => write!(f, "muted"),
+        }
+    }
+}
+
+impl HasArgumentType for Restriction {
+    fn argument_type() -> ArgumentType {
+        ArgumentType::String
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Sanction {
+    pub since: DateTime<Utc>,
+    pub expires: Option<DateTime<Utc>>,
+    pub reason: Option<String>,
+}
+
+impl Sanction {
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.expires.map(|expires| now < expires).unwrap_or(true)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Change {
+    Imposed,
+    Lifted,
+    Expired,
+}
+
+impl std::fmt::Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Change::Imposed => write!(f, "imposed"),
+            Change::Lifted => write!(f, "lifted"),
+            Change::Expired => write!(f, "expired"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Logged {
+    pub time: DateTime<Utc>,
+    pub restriction: Restriction,
+    pub change: Change,
+    pub reason: Option<String>,
+    pub expires: Option<DateTime<Utc>>,
+    /// The moderator, None when the change was made by the world, like
+    /// when a sanction expires.
+    pub by: Option<EntityKey>,
+}
+
+/// Restrictions placed on an actor by moderators, and a log of every
+/// restriction imposed or lifted.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct Moderation {
+    frozen: Option<Sanction>,
+    muted: Option<Sanction>,
+    #[serde(default)]
+    log: Vec<Logged>,
+}
+
+impl Scope for Moderation {
+    fn scope_key() -> &'static str {
+        "moderation"
+    }
+}
+
+impl Moderation {
+    fn sanction_mut(&mut self, restriction: Restriction) -> &mut Option<Sanction> {
+        match restriction {
+            Restriction::Frozen => &mut self.frozen,
+            Restriction::Muted => &mut self.muted,
+        }
+    }
+
+    pub fn sanction(&self, restriction: Restriction) -> Option<&Sanction> {
+        match restriction {
+            Restriction::Frozen => self.frozen.as_ref(),
+            Restriction::Muted => self.muted.as_ref(),
+        }
+    }
+
+    /// Sanctions that have expired aren't active, even before they've been
+    /// removed by the future scheduled to expire them.
+    pub fn is(&self, restriction: Restriction, now: DateTime<Utc>) -> bool {
+        self.sanction(restriction)
+            .map(|s| s.is_active(now))
+            .unwrap_or(false)
+    }
+
+    pub fn log(&self) -> &[Logged] {
+        &self.log
+    }
+
+    pub fn impose(&mut self, restriction: Restriction, sanction: Sanction, by: Option<EntityKey>) {
+        self.log.push(Logged {
+            time: sanction.since,
+            restriction,
+            change: Change::Imposed,
+            reason: sanction.reason.clone(),
+            expires: sanction.expires,
+            by,
+        });
+
+        *self.sanction_mut(restriction) = Some(sanction);
+    }
+
+    /// Returns false if the actor wasn't restricted this way.
+    pub fn lift(
+        &mut self,
+        restriction: Restriction,
+        now: DateTime<Utc>,
+        by: Option<EntityKey>,
+    ) -> bool {
+        self.remove(restriction, Change::Lifted, now, by)
+    }
+
+    /// Removes the sanction expiring at the given time, returns false if
+    /// there's no such sanction, which happens when the sanction was lifted
+    /// or replaced after the expiry was scheduled, or if it hasn't expired.
+    pub fn expire(
+        &mut self,
+        restriction: Restriction,
+        expires: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> bool {
+        match self.sanction(restriction) {
+            Some(sanction) if sanction.expires == Some(expires) && now >= expires => {
+                self.remove(restriction, Change::Expired, now, None)
+            }
+            _ => false,
+        }
+    }
+
+    fn remove(
+        &mut self,
+        restriction: Restriction,
+        change: Change,
+        now: DateTime<Utc>,
+        by: Option<EntityKey>,
+    ) -> bool {
+        let Some(removed) = self.sanction_mut(restriction).take() else {
+            return false;
+        };
+
+        self.log.push(Logged {
+            time: now,
+            restriction,
+            change,
+            reason: removed.reason,
+            expires: removed.expires,
+            by,
+        });
+
+        true
+    }
+}
@@ -0,0 +1,84 @@
+use super::actions::*;
+use super::model::Restriction;
+use crate::library::parser::*;
+
+/// Durations like 30s, 10m, 2h or 7d, in seconds.
+fn duration(i: &str) -> IResult<&str, u64> {
+    map(
+        pair(map_res(digit1, str::parse::<u64>), one_of("smhd")),
+        |(n, unit)| match unit {
+            's' => n,
+            'm' => n * 60,
+            'h' => n * 60 * 60,
+            _ => n * 60 * 60 * 24,
+        },
+    )(i)
+}
+
+fn restriction(i: &str) -> IResult<&str, Restriction> {
+    alt((
+        map(tag("@freeze"), |_| Restriction::Frozen),
+        map(tag("@mute"), |_| Restriction::Muted),
+    ))(i)
+}
+
+fn lifting(i: &str) -> IResult<&str, Restriction> {
+    alt((
+        map(tag("@unfreeze"), |_| Restriction::Frozen),
+        map(tag("@unmute"), |_| Restriction::Muted),
+    ))(i)
+}
+
+pub struct ModerateActionParser {}
+
+impl ParsesActions for ModerateActionParser {
+    fn try_parse_action(&self, i: &str) -> EvaluationResult {
+        let (_, action) = map(
+            tuple((
+                restriction,
+                preceded(spaces, word),
+                opt(preceded(spaces, duration)),
+                opt(preceded(spaces, text_to_end_of_line)),
+            )),
+            |(restriction, username, seconds, reason)| ModerateAction {
+                username: username.to_owned(),
+                restriction,
+                seconds,
+                reason: reason.map(|r| r.trim().to_owned()),
+            },
+        )(i)?;
+
+        Ok(Some(Box::new(action)))
+    }
+}
+
+pub struct LiftModerationActionParser {}
+
+impl ParsesActions for LiftModerationActionParser {
+    fn try_parse_action(&self, i: &str) -> EvaluationResult {
+        let (_, action) = map(
+            pair(lifting, preceded(spaces, word)),
+            |(restriction, username)| LiftModerationAction {
+                username: username.to_owned(),
+                restriction,
+            },
+        )(i)?;
+
+        Ok(Some(Box::new(action)))
+    }
+}
+
+pub struct ModerationLogActionParser {}
+
+impl ParsesActions for ModerationLogActionParser {
+    fn try_parse_action(&self, i: &str) -> EvaluationResult {
+        let (_, action) = map(
+            preceded(pair(tag("@modlog"), spaces), word),
+            |username: &str| ModerationLogAction {
+                username: username.to_owned(),
+            },
+        )(i)?;
+
+        Ok(Some(Box::new(action)))
+    }
+}
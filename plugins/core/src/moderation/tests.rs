@@ -0,0 +1,180 @@
+use chrono::{DateTime, Duration};
+use engine::prelude::{DevNullNotifier, Domain, EvaluateAs, HasRoles, HasUsernames};
+
+use super::parser::*;
+use super::ModerationPluginFactory;
+use crate::chat::ChatPluginFactory;
+use crate::emote::EmotePluginFactory;
+use crate::library::tests::*;
+use crate::looking::LookingPluginFactory;
+use crate::moderation::actions::{ExpireModerationAction, ModerateAction};
+use crate::moderation::model::Restriction;
+use crate::security::SecurityPluginFactory;
+
+fn moderated_domain() -> Result<(BuildSurroundings, EntityKey, EntityKey)> {
+    let mut plugins = RegisteredPlugins::default();
+    plugins.register(LookingPluginFactory::default());
+    plugins.register(ChatPluginFactory::default());
+    plugins.register(EmotePluginFactory::default());
+    plugins.register(SecurityPluginFactory::default());
+    plugins.register(ModerationPluginFactory::default());
+    let mut build = BuildSurroundings::new_with_plugins(plugins)?;
+    let carla = build.with(build_entity().living().name("Carla"))?;
+    let (_session, surroundings) = build
+        .occupying(vec![QuickThing::Actual(carla.clone())])
+        .build()?;
+    let (world, person, _area) = surroundings.unpack()?;
+    world.add_username_to_key("carla", &carla.key())?;
+    person.grant_role(Role::Admin)?;
+
+    build.close()?;
+
+    Ok((build, person.key(), carla.key()))
+}
+
+fn perform(domain: &Domain, key: &EntityKey, text: &str) -> Result<Option<Effect>> {
+    domain.evaluate_and_perform_as(EvaluateAs::Key(key), text, &DevNullNotifier {})
+}
+
+fn moderation_log(domain: &Domain, admin: &EntityKey) -> Result<ModerationReply> {
+    match perform(domain, admin, "@modlog carla")? {
+        Some(effect) => Ok(effect.json_as()?),
+        None => panic!("No moderation log"),
+    }
+}
+
+#[test]
+fn it_parses_moderation_with_durations_and_reasons() -> Result<()> {
+    let action = try_parsing(ModerateActionParser {}, "@mute carla 2h being rude")?;
+    assert_eq!(
+        action.unwrap().to_tagged_json()?,
+        ModerateAction {
+            username: "carla".to_owned(),
+            restriction: Restriction::Muted,
+            seconds: Some(2 * 60 * 60),
+            reason: Some("being rude".to_owned()),
+        }
+        .to_tagged_json()?
+    );
+
+    let action = try_parsing(ModerateActionParser {}, "@freeze carla")?;
+    assert_eq!(
+        action.unwrap().to_tagged_json()?,
+        ModerateAction {
+            username: "carla".to_owned(),
+            restriction: Restriction::Frozen,
+            seconds: None,
+            reason: None,
+        }
+        .to_tagged_json()?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn it_only_lets_frozen_actors_look() -> Result<()> {
+    let (build, admin, carla) = moderated_domain()?;
+    let domain = build.domain().unwrap();
+
+    assert_eq!(
+        perform(domain, &carla, "@freeze carla")?,
+        Some(Effect::Prevented)
+    );
+
+    let done: Effect = SimpleReply::Done.try_into()?;
+    assert_eq!(
+        perform(domain, &admin, "@freeze carla spamming")?,
+        Some(done.clone())
+    );
+
+    assert_eq!(perform(domain, &carla, "laugh")?, Some(Effect::Prevented));
+    assert_ne!(perform(domain, &carla, "look")?, Some(Effect::Prevented));
+
+    let log = moderation_log(domain, &admin)?;
+    assert!(log.frozen);
+    assert_eq!(log.log.len(), 1);
+    assert_eq!(log.log[0].reason, Some("spamming".to_owned()));
+    assert_eq!(log.log[0].by, Some("Living".to_owned()));
+
+    assert_eq!(perform(domain, &admin, "@unfreeze carla")?, Some(done));
+    assert_eq!(perform(domain, &carla, "laugh")?, Some(Effect::Ok));
+
+    let log = moderation_log(domain, &admin)?;
+    assert!(!log.frozen);
+    assert_eq!(log.log.len(), 2);
+
+    Ok(())
+}
+
+#[test]
+fn it_expires_mutes_through_futures() -> Result<()> {
+    let (build, admin, _carla) = moderated_domain()?;
+    let domain = build.domain().unwrap();
+
+    let done: Effect = SimpleReply::Done.try_into()?;
+    assert_eq!(perform(domain, &admin, "@mute carla 0s")?, Some(done));
+
+    domain.tick(Utc::now() + Duration::minutes(1), &DevNullNotifier {})?;
+
+    let log = moderation_log(domain, &admin)?;
+    assert!(!log.muted);
+    assert_eq!(log.log.len(), 2);
+    assert_eq!(log.log[1].change, "expired");
+
+    Ok(())
+}
+
+#[test]
+fn it_keeps_mutes_until_they_expire() -> Result<()> {
+    let (build, admin, carla) = moderated_domain()?;
+    let domain = build.domain().unwrap();
+
+    let done: Effect = SimpleReply::Done.try_into()?;
+    assert_eq!(perform(domain, &admin, "@mute carla 1h")?, Some(done));
+
+    assert_eq!(
+        perform(domain, &carla, "say hello")?,
+        Some(Effect::Prevented)
+    );
+    assert_eq!(perform(domain, &carla, "laugh")?, Some(Effect::Prevented));
+    assert_ne!(perform(domain, &carla, "look")?, Some(Effect::Prevented));
+
+    // Delivered early, so the mute is kept.
+    domain.tick(Utc::now() + Duration::hours(2), &DevNullNotifier {})?;
+
+    let log = moderation_log(domain, &admin)?;
+    assert!(log.muted);
+    assert_eq!(log.log.len(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn it_refuses_to_let_actors_expire_moderation() -> Result<()> {
+    let (build, admin, carla) = moderated_domain()?;
+    let domain = build.domain().unwrap();
+
+    let done: Effect = SimpleReply::Done.try_into()?;
+    assert_eq!(perform(domain, &admin, "@freeze carla 1h")?, Some(done));
+
+    let log = moderation_log(domain, &admin)?;
+    let expires = DateTime::parse_from_rfc3339(log.log[0].expires.as_ref().unwrap())?;
+    let action = ExpireModerationAction {
+        user: carla.clone(),
+        restriction: Restriction::Frozen,
+        expires: expires.with_timezone(&Utc),
+    }
+    .to_tagged_json()?;
+
+    for key in [&carla, &admin] {
+        assert_eq!(
+            domain.perform_as(key, &action, &DevNullNotifier {})?,
+            Effect::Prevented
+        );
+    }
+
+    assert!(moderation_log(domain, &admin)?.frozen);
+
+    Ok(())
+}
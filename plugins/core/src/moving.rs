@@ -33,10 +33,14 @@ impl Plugin for MovingPlugin {
     fn schema(&self) -> Schema {
         Schema::empty()
             .action::<actions::GoAction>()
-            .action::<actions::AddRouteAction>()
-            .action::<actions::RemoveRouteAction>()
-            .action::<actions::ActivateRouteAction>()
-            .action::<actions::DeactivateRouteAction>()
+            .action::<actions::ShowRoutesAction>()
+            .action_requiring::<actions::AddRouteAction>(Role::Builder)
+            .action_requiring::<actions::RemoveRouteAction>(Role::Builder)
+            .action_requiring::<actions::ActivateRouteAction>(Role::Builder)
+            .action_requiring::<actions::DeactivateRouteAction>(Role::Builder)
+            .action::<actions::SetHomeAction>()
+            .action::<actions::HomeAction>()
+            .action_requiring::<actions::TeleportAction>(Role::Admin)
     }
 
     fn key(&self) -> &'static str {
@@ -52,6 +56,9 @@ impl ParsesActions for MovingPlugin {
     fn try_parse_action(&self, i: &str) -> EvaluationResult {
         try_parsing(parser::GoActionParser {}, i)
             .or_else(|_| try_parsing(parser::RouteActionParser {}, i))
+            .or_else(|_| try_parsing(parser::SetHomeActionParser {}, i))
+            .or_else(|_| try_parsing(parser::HomeActionParser {}, i))
+            .or_else(|_| try_parsing(parser::TeleportActionParser {}, i))
     }
 }
 
@@ -69,7 +76,10 @@ impl ActionSource for ActionSources {
             actions::AddRouteAction,
             actions::RemoveRouteAction,
             actions::ActivateRouteAction,
-            actions::DeactivateRouteAction
+            actions::DeactivateRouteAction,
+            actions::SetHomeAction,
+            actions::HomeAction,
+            actions::TeleportAction
         );
 
         Ok(None)
@@ -1,63 +1,77 @@
 use std::rc::Rc;
 
 use crate::library::actions::*;
+use crate::locking::model::route_preventing;
 use crate::looking::actions::*;
 use crate::looking::model::Observe;
 use crate::moving::model::Route;
+use engine::prelude::HasUsernames;
 
-use super::model::Occupyable;
+use super::model::{Home, Occupyable};
 
 #[action]
 pub struct GoAction {
     pub item: Item,
 }
 
-impl GoAction {
-    fn navigate(
-        &self,
-        session: SessionRef,
-        actor: EntityPtr,
-        area: EntityPtr,
-        to_area: EntityPtr,
-    ) -> ReplyResult {
-        match tools::navigate_between(&area, &to_area, &actor)? {
-            true => {
-                let excluding = actor.key();
-                let hearing_arrive: Vec<_> = tools::get_occupant_keys(&to_area)?
-                    .into_iter()
-                    .filter(|v| *v != excluding)
-                    .collect();
-
-                session.raise(
-                    Some(actor.clone()),
-                    Audience::Area(area.key().clone()),
-                    Raising::TaggedJson(
-                        Moving::Left {
-                            actor: (&actor).observe(&actor)?.expect("No observed entity"),
-                            area: (&area).observe(&actor)?.expect("No observed entity"),
-                        }
-                        .to_tagged_json()?,
-                    ),
-                )?;
-                session.raise(
-                    Some(actor.clone()),
-                    Audience::Individuals(hearing_arrive),
-                    Raising::TaggedJson(
-                        Moving::Arrived {
-                            actor: (&actor).observe(&actor)?.expect("No observed entity"),
-                            area: (&to_area).observe(&actor)?.expect("No observed entity"),
-                        }
-                        .to_tagged_json()?,
-                    ),
-                )?;
-
-                Ok(session.perform(Perform::Actor {
-                    actor,
-                    action: PerformAction::Instance(Rc::new(LookAction {})),
-                })?)
+/// Moves the actor between the two areas, letting those in the area being
+/// left and the area being arrived in know. Returns false if the actor
+/// wasn't in the area they're leaving.
+pub(crate) fn navigate(
+    session: &SessionRef,
+    actor: &EntityPtr,
+    area: &EntityPtr,
+    to_area: &EntityPtr,
+) -> Result<bool> {
+    if !tools::navigate_between(area, to_area, actor)? {
+        return Ok(false);
+    }
+
+    let excluding = actor.key();
+    let hearing_arrive: Vec<_> = tools::get_occupant_keys(to_area)?
+        .into_iter()
+        .filter(|v| *v != excluding)
+        .collect();
+
+    session.raise(
+        Some(actor.clone()),
+        Audience::Area(area.key().clone()),
+        Raising::TaggedJson(
+            Moving::Left {
+                actor: actor.observe(actor)?.expect("No observed entity"),
+                area: area.observe(actor)?.expect("No observed entity"),
             }
-            false => Ok(SimpleReply::NotFound.try_into()?),
-        }
+            .to_tagged_json()?,
+        ),
+    )?;
+    session.raise(
+        Some(actor.clone()),
+        Audience::Individuals(hearing_arrive),
+        Raising::TaggedJson(
+            Moving::Arrived {
+                actor: actor.observe(actor)?.expect("No observed entity"),
+                area: to_area.observe(actor)?.expect("No observed entity"),
+            }
+            .to_tagged_json()?,
+        ),
+    )?;
+
+    Ok(true)
+}
+
+/// Moves the actor and shows them where they've arrived.
+fn navigate_and_look(
+    session: SessionRef,
+    actor: EntityPtr,
+    area: EntityPtr,
+    to_area: EntityPtr,
+) -> ReplyResult {
+    match navigate(&session, &actor, &area, &to_area)? {
+        true => Ok(session.perform(Perform::Actor {
+            actor,
+            action: PerformAction::Instance(Rc::new(LookAction {})),
+        })?),
+        false => Ok(SimpleReply::NotFound.try_into()?),
     }
 }
 
@@ -69,26 +83,32 @@ impl Action for GoAction {
     fn perform(&self, session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
         info!("go {:?}!", self.item);
 
-        let (_, actor, area) = surroundings.unpack();
+        let (_, actor, area) = surroundings.unpack()?;
 
         if let Some(occupyable) = area.scope::<Occupyable>()? {
             match &self.item {
                 Item::Route(route) => match occupyable.find_route(&route) {
-                    Some(route) => match route {
-                        Route::Simple(to_area) => {
-                            let to_area = to_area.destination().to_entity()?;
-                            self.navigate(session, actor, area, to_area)
+                    Some(route) => {
+                        if let Some(reason) = route_preventing(&area, route.name())? {
+                            return Ok(SimpleReply::Prevented(Some(reason)).try_into()?);
                         }
-                        Route::Deactivated(reason, _) => {
-                            Ok(SimpleReply::Prevented(Some(reason.clone())).try_into()?)
+
+                        match route {
+                            Route::Simple(to_area) => {
+                                let to_area = to_area.destination().to_entity()?;
+                                navigate_and_look(session, actor, area, to_area)
+                            }
+                            Route::Deactivated(reason, _) => {
+                                Ok(SimpleReply::Prevented(Some(reason.clone())).try_into()?)
+                            }
                         }
-                    },
+                    }
                     None => {
                         match session.find_item(surroundings, &Item::Named(route.to_owned()))? {
                             Some(maybe) => {
                                 let maybe = maybe.one()?;
                                 if maybe.scope::<Occupyable>()?.is_some() {
-                                    self.navigate(session, actor, area, maybe)
+                                    navigate_and_look(session, actor, area, maybe)
                                 } else {
                                     Ok(SimpleReply::NotFound.try_into()?)
                                 }
@@ -98,7 +118,7 @@ impl Action for GoAction {
                     }
                 },
                 Item::Gid(_) => match session.find_item(surroundings, &self.item)? {
-                    Some(to_area) => self.navigate(session, actor, area, to_area.one()?),
+                    Some(to_area) => navigate_and_look(session, actor, area, to_area.one()?),
                     None => Ok(SimpleReply::NotFound.try_into()?),
                 },
                 _ => panic!("Occupyable::find_route expecting Item::Route or Item::Gid"),
@@ -118,7 +138,7 @@ impl Action for ShowRoutesAction {
     }
 
     fn perform(&self, _session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
-        let (_, _, area) = surroundings.unpack();
+        let (_, _, area) = surroundings.unpack()?;
         let Some(occupyable) = area.scope::<Occupyable>()? else {
             return Ok(SimpleReply::NotFound.try_into()?);
         };
@@ -203,7 +223,7 @@ impl Action for ActivateRouteAction {
     }
 
     fn perform(&self, _session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
-        let (_, _, area) = surroundings.unpack();
+        let (_, _, area) = surroundings.unpack()?;
 
         let mut occupyable = area.scope_mut::<Occupyable>()?;
         occupyable.activate(&self.name);
@@ -225,7 +245,7 @@ impl Action for DeactivateRouteAction {
     }
 
     fn perform(&self, _session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
-        let (_, _, area) = surroundings.unpack();
+        let (_, _, area) = surroundings.unpack()?;
 
         let mut occupyable = area.scope_mut::<Occupyable>()?;
         occupyable.deactivate(&self.name, &self.reason);
@@ -234,3 +254,97 @@ impl Action for DeactivateRouteAction {
         Ok(SimpleReply::Done.try_into()?)
     }
 }
+
+#[action]
+pub struct SetHomeAction {}
+
+impl Action for SetHomeAction {
+    fn is_read_only(&self) -> bool {
+        false
+    }
+
+    fn perform(&self, _session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
+        let (_, actor, area) = surroundings.unpack()?;
+
+        let mut home = actor.scope_mut::<Home>()?;
+        home.area = Some(area.entity_ref());
+        home.save()?;
+
+        Ok(SimpleReply::Done.try_into()?)
+    }
+}
+
+#[action]
+pub struct HomeAction {}
+
+impl Action for HomeAction {
+    fn is_read_only(&self) -> bool {
+        false
+    }
+
+    fn perform(&self, session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
+        let (_, actor, area) = surroundings.unpack()?;
+
+        let Some(home) = actor.scope::<Home>()?.and_then(|h| h.area.clone()) else {
+            return Ok(SimpleReply::Prevented(Some(
+                "You haven't set a home, use @sethome first.".to_owned(),
+            ))
+            .try_into()?);
+        };
+
+        let home = home.to_entity()?;
+        if home.key() == area.key() {
+            return Ok(SimpleReply::Impossible.try_into()?);
+        }
+
+        navigate_and_look(session, actor, area, home)
+    }
+}
+
+/// Moves somebody, found by their username or gid, to an area anywhere.
+#[action]
+pub struct TeleportAction {
+    pub who: Item,
+    pub area: Item,
+}
+
+impl Action for TeleportAction {
+    fn is_read_only(&self) -> bool {
+        false
+    }
+
+    fn perform(&self, session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
+        info!("teleport {:?} -> {:?}", self.who, self.area);
+
+        let (world, _, _) = surroundings.unpack()?;
+
+        let who = match &self.who {
+            Item::Named(username) => match world.find_name_key(username)? {
+                Some(key) => session.entity(&LookupBy::Key(&key))?,
+                None => None,
+            },
+            item => session
+                .find_item(surroundings, item)?
+                .map(|f| f.one())
+                .transpose()?,
+        };
+        let Some(who) = who else {
+            return Ok(SimpleReply::NotFound.try_into()?);
+        };
+
+        let Some(to_area) = session.find_item(surroundings, &self.area)? else {
+            return Ok(SimpleReply::NotFound.try_into()?);
+        };
+        let to_area = to_area.one()?;
+        if to_area.scope::<Occupyable>()?.is_none() {
+            return Ok(SimpleReply::Impossible.try_into()?);
+        }
+
+        let area = tools::area_of(&who)?;
+
+        match navigate(&session, &who, &area, &to_area)? {
+            true => Ok(SimpleReply::Done.try_into()?),
+            false => Ok(SimpleReply::NotFound.try_into()?),
+        }
+    }
+}
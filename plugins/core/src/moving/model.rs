@@ -11,6 +11,18 @@ impl Scope for Occupying {
     }
 }
 
+/// Where an actor returns to when they go home.
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct Home {
+    pub area: Option<EntityRef>,
+}
+
+impl Scope for Home {
+    fn scope_key() -> &'static str {
+        "home"
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct SimpleRoute {
@@ -43,7 +55,7 @@ pub enum Route {
 }
 
 impl Route {
-    fn name(&self) -> &str {
+    pub fn name(&self) -> &str {
         match self {
             Route::Simple(simple) => &simple.name,
             Route::Deactivated(_, route) => route.name(),
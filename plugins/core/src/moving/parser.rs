@@ -2,8 +2,11 @@ use crate::library::parser::*;
 
 use super::actions::AddRouteAction;
 use super::actions::GoAction;
+use super::actions::HomeAction;
 use super::actions::RemoveRouteAction;
+use super::actions::SetHomeAction;
 use super::actions::ShowRoutesAction;
+use super::actions::TeleportAction;
 
 pub struct GoActionParser {}
 
@@ -60,3 +63,41 @@ impl ParsesActions for RouteActionParser {
         Ok(Some(action))
     }
 }
+
+pub struct SetHomeActionParser {}
+
+impl ParsesActions for SetHomeActionParser {
+    fn try_parse_action(&self, i: &str) -> EvaluationResult {
+        let (_, action) = map(tag("@sethome"), |_| SetHomeAction {})(i)?;
+
+        Ok(Some(Box::new(action)))
+    }
+}
+
+pub struct HomeActionParser {}
+
+impl ParsesActions for HomeActionParser {
+    fn try_parse_action(&self, i: &str) -> EvaluationResult {
+        let (_, action) = map(tag("home"), |_| HomeAction {})(i)?;
+
+        Ok(Some(Box::new(action)))
+    }
+}
+
+pub struct TeleportActionParser {}
+
+impl ParsesActions for TeleportActionParser {
+    fn try_parse_action(&self, i: &str) -> EvaluationResult {
+        let who = alt((
+            gid_reference,
+            map(word, |s: &str| Item::Named(s.to_owned())),
+        ));
+
+        let (_, action) = map(
+            tuple((pair(tag("@teleport"), spaces), who, spaces, gid_reference)),
+            |(_, who, _, area)| TeleportAction { who, area },
+        )(i)?;
+
+        Ok(Some(Box::new(action)))
+    }
+}
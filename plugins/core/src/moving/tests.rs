@@ -3,7 +3,7 @@ use super::*;
 use crate::library::tests::*;
 use crate::looking::model::new_area_observation;
 use crate::moving::actions::{
-    AddRouteAction, DeactivateRouteAction, RemoveRouteAction, ShowRoutesAction,
+    AddRouteAction, DeactivateRouteAction, RemoveRouteAction, ShowRoutesAction, TeleportAction,
 };
 use crate::moving::model::{Occupyable, Route, SimpleRoute};
 
@@ -42,7 +42,7 @@ fn it_goes_through_correct_route_when_two_nearby() -> Result<()> {
     let action = try_parsing(GoActionParser {}, "go east")?;
     let action = action.unwrap();
     let reply = action.perform(session.clone(), &surroundings)?;
-    let (_, actor, area) = surroundings.unpack();
+    let (_, actor, area) = surroundings.unpack()?;
 
     let reply: AreaObservation = reply.json_as()?;
     assert_eq!(reply, new_area_observation(&actor, &east)?);
@@ -66,7 +66,7 @@ fn it_goes_through_routes_when_one_nearby() -> Result<()> {
     let action = try_parsing(GoActionParser {}, "go east")?;
     let action = action.unwrap();
     let reply = action.perform(session.clone(), &surroundings)?;
-    let (_, actor, area) = surroundings.unpack();
+    let (_, actor, area) = surroundings.unpack()?;
 
     let reply: AreaObservation = reply.json_as()?;
     assert_eq!(reply, new_area_observation(&actor, &destination)?);
@@ -87,7 +87,7 @@ fn it_fails_to_go_unknown_items() -> Result<()> {
     let action = try_parsing(GoActionParser {}, "go rake")?;
     let action = action.unwrap();
     let reply = action.perform(session.clone(), &surroundings)?;
-    let (_, _person, _area) = surroundings.unpack();
+    let (_, _person, _area) = surroundings.unpack()?;
 
     let reply: SimpleReply = reply.json_as()?;
     assert_eq!(reply, SimpleReply::NotFound);
@@ -107,7 +107,7 @@ fn it_fails_to_go_non_routes() -> Result<()> {
     let action = try_parsing(GoActionParser {}, "go rake")?;
     let action = action.unwrap();
     let reply = action.perform(session.clone(), &surroundings)?;
-    let (_, _person, _area) = surroundings.unpack();
+    let (_, _person, _area) = surroundings.unpack()?;
 
     let reply: SimpleReply = reply.json_as()?;
     assert_eq!(reply, SimpleReply::NotFound);
@@ -169,7 +169,7 @@ fn it_adds_simple_routes() -> Result<()> {
     let action = try_parsing(RouteActionParser {}, &format!("@route #{} north", gid))?.unwrap();
 
     let reply = action.perform(session.clone(), &surroundings)?;
-    let (_, _person, area) = surroundings.unpack();
+    let (_, _person, area) = surroundings.unpack()?;
 
     let reply: SimpleReply = reply.json_as()?;
     assert_eq!(reply, SimpleReply::Done);
@@ -204,7 +204,7 @@ fn it_replaces_simple_routes() -> Result<()> {
     let action = try_parsing(RouteActionParser {}, &format!("@route #{} north", new_gid))?.unwrap();
     action.perform(session.clone(), &surroundings)?;
 
-    let (_, _person, area) = surroundings.unpack();
+    let (_, _person, area) = surroundings.unpack()?;
 
     let occupyable = area.scope::<Occupyable>()?.unwrap();
     let routes: Vec<Route> = occupyable.routes.clone().unwrap();
@@ -230,7 +230,7 @@ fn it_removes_simple_routes() -> Result<()> {
 
     let action = try_parsing(RouteActionParser {}, &format!("@route #{} north", gid))?.unwrap();
     action.perform(session.clone(), &surroundings)?;
-    let (_, _person, area) = surroundings.unpack();
+    let (_, _person, area) = surroundings.unpack()?;
 
     let occupyable = area.scope::<Occupyable>()?.unwrap();
     let routes: Vec<Route> = occupyable.routes.clone().unwrap();
@@ -244,7 +244,7 @@ fn it_removes_simple_routes() -> Result<()> {
 
     let action = try_parsing(RouteActionParser {}, &format!("@route rm north"))?.unwrap();
     action.perform(session.clone(), &surroundings)?;
-    let (_, _person, area) = surroundings.unpack();
+    let (_, _person, area) = surroundings.unpack()?;
 
     let occupyable = area.scope::<Occupyable>()?.unwrap();
     let routes: Vec<Route> = occupyable.routes.clone().unwrap();
@@ -337,7 +337,7 @@ fn it_goes_into_nested_areas() -> Result<()> {
     let action = try_parsing(GoActionParser {}, "go place")?;
     let action = action.unwrap();
     let reply = action.perform(session.clone(), &surroundings)?;
-    let (_, actor, area) = surroundings.unpack();
+    let (_, actor, area) = surroundings.unpack()?;
 
     let reply: AreaObservation = reply.json_as()?;
     assert_eq!(reply, new_area_observation(&actor, &destination)?);
@@ -349,3 +349,67 @@ fn it_goes_into_nested_areas() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn it_goes_home_after_setting_one() -> Result<()> {
+    let mut build = BuildSurroundings::new()?;
+    let destination = build.make(QuickThing::Place("Place"))?;
+    let (session, surroundings) = build
+        .route("East", QuickThing::Actual(destination.clone()))
+        .build()?;
+    let (world, actor, area) = surroundings.unpack()?;
+
+    let action = try_parsing(HomeActionParser {}, "home")?.unwrap();
+    let reply: SimpleReply = action.perform(session.clone(), &surroundings)?.json_as()?;
+    assert!(matches!(reply, SimpleReply::Prevented(Some(_))));
+
+    let action = try_parsing(SetHomeActionParser {}, "@sethome")?.unwrap();
+    let reply: SimpleReply = action.perform(session.clone(), &surroundings)?.json_as()?;
+    assert_eq!(reply, SimpleReply::Done);
+
+    let action = try_parsing(GoActionParser {}, "go east")?.unwrap();
+    action.perform(session.clone(), &surroundings)?;
+    assert_eq!(tools::area_of(&actor)?.key(), destination.key());
+
+    let away = Surroundings::Actor {
+        world,
+        actor: actor.clone(),
+        area: destination,
+    };
+    let action = try_parsing(HomeActionParser {}, "home")?.unwrap();
+    let reply: AreaObservation = action.perform(session.clone(), &away)?.json_as()?;
+    assert_eq!(reply, new_area_observation(&actor, &area)?);
+    assert_eq!(tools::area_of(&actor)?.key(), area.key());
+
+    build.close()?;
+
+    Ok(())
+}
+
+#[test]
+fn it_teleports_by_gid_to_areas_anywhere() -> Result<()> {
+    let mut build = BuildSurroundings::new()?;
+    let faraway = build.make(QuickThing::Place("Faraway"))?;
+    let (session, surroundings) = build.plain().build()?;
+    let (_, actor, _area) = surroundings.unpack()?;
+
+    let action = try_parsing(
+        TeleportActionParser {},
+        &format!("@teleport #{} #{}", actor.gid(), faraway.gid()),
+    )?
+    .unwrap();
+    let reply: SimpleReply = action.perform(session.clone(), &surroundings)?.json_as()?;
+    assert_eq!(reply, SimpleReply::Done);
+    assert_eq!(tools::area_of(&actor)?.key(), faraway.key());
+
+    let action = TeleportAction {
+        who: Item::Named("nobody".to_owned()),
+        area: Item::Gid(faraway.gid()),
+    };
+    let reply: SimpleReply = action.perform(session.clone(), &surroundings)?.json_as()?;
+    assert_eq!(reply, SimpleReply::NotFound);
+
+    build.close()?;
+
+    Ok(())
+}
@@ -25,7 +25,7 @@ impl Plugin for SchedulingPlugin {
     }
 
     fn schema(&self) -> Schema {
-        Schema::empty().action::<actions::ScheduleAction>()
+        Schema::empty().action_requiring::<actions::ScheduleAction>(Role::Admin)
     }
 
     fn key(&self) -> &'static str {
@@ -125,7 +125,160 @@ pub mod parser {
 
 #[cfg(test)]
 mod tests {
-    // use super::model::*;
-    // use super::parser::*;
-    // use crate::library::tests::*;
+    use chrono::Duration;
+    use engine::prelude::{DevNullNotifier, SessionOpener};
+
+    use super::actions::*;
+    use crate::carrying::{actions::HoldAction, CarryingPluginFactory};
+    use crate::library::tests::*;
+
+    #[test]
+    fn it_retries_failed_futures_until_they_are_dead_letters() -> Result<()> {
+        let mut plugins = RegisteredPlugins::default();
+        plugins.register(CarryingPluginFactory::default());
+        let mut build = BuildSurroundings::new_with_plugins(plugins)?;
+        let (_session, surroundings) = build.ground(vec![QuickThing::Object("Rake")]).build()?;
+        build.close()?;
+
+        let (_, person, _) = surroundings.unpack()?;
+        let now = Utc::now();
+
+        let domain = build.domain().unwrap();
+        let session = domain.open_session()?;
+        session.schedule(FutureAction::new(
+            "good".to_owned(),
+            person.key().clone(),
+            FutureSchedule::Utc(now),
+            HoldAction {
+                item: Item::Named("rake".to_owned()),
+            }
+            .to_tagged_json()?,
+        ))?;
+        session.schedule(FutureAction::new(
+            "bad".to_owned(),
+            person.key().clone(),
+            FutureSchedule::Utc(now),
+            TaggedJson::new("missing".to_owned(), serde_json::json!({}).into()),
+        ))?;
+        session.close(&DevNullNotifier {})?;
+
+        domain.tick(now, &DevNullNotifier {})?;
+
+        let person_json = domain
+            .query_entity(&LookupBy::Key(&person.key()))?
+            .unwrap()
+            .to_json_value()?;
+        assert_eq!(
+            person_json["scopes"]["containing"]["holding"]
+                .as_array()
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(domain.query_dead_letters()?.is_empty());
+
+        for hours in 1..5 {
+            domain.tick(now + Duration::hours(hours), &DevNullNotifier {})?;
+        }
+
+        let dead = domain.query_dead_letters()?;
+        assert_eq!(dead.len(), 1);
+        assert_eq!(dead[0].future.key, "bad");
+        assert_eq!(dead[0].future.attempts, 5);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_retries_failed_cron_futures_before_dead_letters() -> Result<()> {
+        let mut build = BuildSurroundings::new()?;
+        let (_session, surroundings) = build.plain().build()?;
+        build.close()?;
+
+        let (_, person, _) = surroundings.unpack()?;
+        let now = Utc::now();
+
+        let domain = build.domain().unwrap();
+        let session = domain.open_session()?;
+        session.schedule(FutureAction::new(
+            "bad".to_owned(),
+            person.key().clone(),
+            FutureSchedule::Cron("0 0 * * * *".to_owned()),
+            TaggedJson::new("missing".to_owned(), serde_json::json!({}).into()),
+        ))?;
+        session.close(&DevNullNotifier {})?;
+
+        for hours in 1..5 {
+            domain.tick(now + Duration::hours(hours), &DevNullNotifier {})?;
+        }
+
+        assert!(domain.query_dead_letters()?.is_empty());
+
+        let later = now + Duration::hours(5);
+        domain.tick(later, &DevNullNotifier {})?;
+
+        let dead = domain.query_dead_letters()?;
+        assert_eq!(dead.len(), 1);
+        assert_eq!(dead[0].future.attempts, 5);
+
+        let session = domain.open_session()?;
+        let futures = session.futures(&person.key())?;
+        session.close(&DevNullNotifier {})?;
+        assert_eq!(futures.len(), 1);
+        assert!(DateTime::parse_from_rfc3339(&futures[0].time)? > later);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_queues_delivered_cron_futures_for_their_following_time() -> Result<()> {
+        let mut plugins = RegisteredPlugins::default();
+        plugins.register(CarryingPluginFactory::default());
+        let mut build = BuildSurroundings::new_with_plugins(plugins)?;
+        let (_session, surroundings) = build
+            .ground(vec![
+                QuickThing::Object("Rake"),
+                QuickThing::Object("Shovel"),
+            ])
+            .build()?;
+        build.close()?;
+
+        let (_, person, _) = surroundings.unpack()?;
+        let now = Utc::now();
+
+        let domain = build.domain().unwrap();
+        let session = domain.open_session()?;
+        session.schedule(FutureAction::new(
+            "good".to_owned(),
+            person.key().clone(),
+            FutureSchedule::Cron("0 0 * * * *".to_owned()),
+            HoldAction {
+                item: Item::Named("rake".to_owned()),
+            }
+            .to_tagged_json()?,
+        ))?;
+        session.schedule(FutureAction::new(
+            "once".to_owned(),
+            person.key().clone(),
+            FutureSchedule::Utc(now),
+            HoldAction {
+                item: Item::Named("shovel".to_owned()),
+            }
+            .to_tagged_json()?,
+        ))?;
+        session.close(&DevNullNotifier {})?;
+
+        let later = now + Duration::hours(1);
+        domain.tick(later, &DevNullNotifier {})?;
+
+        let session = domain.open_session()?;
+        let futures = session.futures(&person.key())?;
+        session.close(&DevNullNotifier {})?;
+        assert_eq!(futures.len(), 1);
+        assert_eq!(futures[0].key, "good");
+        assert!(DateTime::parse_from_rfc3339(&futures[0].time)? > later);
+        assert!(domain.query_dead_letters()?.is_empty());
+
+        Ok(())
+    }
 }
@@ -1,11 +1,14 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
 use crate::library::plugin::*;
+use engine::prelude::HasRoles;
 
 #[derive(Default)]
 pub struct SecurityPluginFactory {}
 
 impl PluginFactory for SecurityPluginFactory {
     fn create_plugin(&self) -> Result<Box<dyn Plugin>> {
-        Ok(Box::new(SecurityPlugin {}))
+        Ok(Box::<SecurityPlugin>::default())
     }
 
     fn stop(&self) -> Result<()> {
@@ -14,7 +17,9 @@ impl PluginFactory for SecurityPluginFactory {
 }
 
 #[derive(Default)]
-pub struct SecurityPlugin {}
+pub struct SecurityPlugin {
+    required: Rc<RefCell<HashMap<String, Role>>>,
+}
 
 impl Plugin for SecurityPlugin {
     fn plugin_key() -> &'static str
@@ -27,19 +32,181 @@ impl Plugin for SecurityPlugin {
     fn key(&self) -> &'static str {
         Self::plugin_key()
     }
+
+    fn schema(&self) -> Schema {
+        Schema::empty()
+            .action::<actions::ChangePasswordAction>()
+            .action_requiring::<actions::GrantAction>(Role::Admin)
+            .action_requiring::<actions::RevokeAction>(Role::Admin)
+            .action::<actions::InviteAction>()
+            .action_requiring::<actions::InvitationsAction>(Role::Admin)
+            .action_requiring::<actions::RevokeInvitationAction>(Role::Admin)
+    }
+
+    fn initialize(&mut self, schema: &SchemaCollection) -> Result<()> {
+        *self.required.borrow_mut() = schema.roles();
+
+        Ok(())
+    }
+
+    fn sources(&self) -> Vec<Box<dyn ActionSource>> {
+        vec![Box::new(ActionSources::default())]
+    }
+
+    fn middleware(&mut self) -> Result<Vec<Rc<dyn Middleware>>> {
+        Ok(vec![Rc::new(RolesMiddleware {
+            required: Rc::clone(&self.required),
+        })])
+    }
 }
 
 impl ParsesActions for SecurityPlugin {
     fn try_parse_action(&self, i: &str) -> EvaluationResult {
         try_parsing(parser::ChangePasswordActionParser {}, i)
+            .or_else(|_| try_parsing(parser::GrantActionParser {}, i))
+            .or_else(|_| try_parsing(parser::RevokeActionParser {}, i))
+            .or_else(|_| try_parsing(parser::InviteActionParser {}, i))
+            .or_else(|_| try_parsing(parser::InvitationsActionParser {}, i))
+            .or_else(|_| try_parsing(parser::RevokeInvitationActionParser {}, i))
+    }
+}
+
+#[derive(Default)]
+pub struct ActionSources {}
+
+impl ActionSource for ActionSources {
+    fn try_deserialize_action(
+        &self,
+        tagged: &TaggedJson,
+    ) -> Result<Option<Box<dyn Action>>, serde_json::Error> {
+        try_deserialize_all!(
+            tagged,
+            actions::GrantAction,
+            actions::RevokeAction,
+            actions::InviteAction,
+            actions::InvitationsAction,
+            actions::RevokeInvitationAction
+        );
+
+        Ok(None)
+    }
+}
+
+/// Prevents actors from performing actions that require a role they haven't
+/// been granted, or actions whose plugin never declared a role for them.
+/// Actions performed without an actor, like those performed by the world, are
+/// always allowed.
+pub struct RolesMiddleware {
+    required: Rc<RefCell<HashMap<String, Role>>>,
+}
+
+impl Middleware for RolesMiddleware {
+    fn handle(&self, value: Perform, next: MiddlewareNext) -> Result<Effect, anyhow::Error> {
+        if let Perform::Surroundings {
+            surroundings: Surroundings::Actor { actor, .. },
+            action,
+        } = &value
+        {
+            let tagged = match action {
+                PerformAction::Instance(action) => action.to_tagged_json()?,
+                PerformAction::TaggedJson(tagged) => tagged.clone(),
+            };
+
+            let required = self.required.borrow().get(tagged.tag()).copied();
+            let Some(role) = required else {
+                warn!(action = tagged.tag(), "prevented, undeclared");
+
+                return Ok(Effect::Prevented);
+            };
+
+            if !actor.has_role(role)? {
+                info!(action = tagged.tag(), %role, "prevented");
+
+                return Ok(Effect::Prevented);
+            }
+        }
+
+        next.handle(value)
     }
 }
 
-pub mod model {}
+pub mod model {
+    use crate::library::model::*;
+
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+    pub struct Invitation {
+        pub code: String,
+        pub inviter: EntityKey,
+        pub created: DateTime<Utc>,
+        pub expires: DateTime<Utc>,
+    }
+
+    impl Invitation {
+        pub fn is_valid(&self, now: DateTime<Utc>) -> bool {
+            now < self.expires
+        }
+    }
+
+    /// Single use codes for registering, kept on the world.
+    #[derive(Debug, Serialize, Deserialize, Default)]
+    pub struct Invitations {
+        invitations: Vec<Invitation>,
+    }
+
+    impl Scope for Invitations {
+        fn scope_key() -> &'static str {
+            "invitations"
+        }
+    }
+
+    impl Invitations {
+        /// Adds the invitation, forgetting about any that have expired.
+        /// Returns false if there's already an invitation with the same code.
+        pub fn mint(&mut self, invitation: Invitation) -> bool {
+            let now = invitation.created;
+            self.invitations.retain(|i| i.is_valid(now));
+            if self
+                .invitations
+                .iter()
+                .any(|i| i.code.eq_ignore_ascii_case(&invitation.code))
+            {
+                return false;
+            }
+            self.invitations.push(invitation);
+            true
+        }
+
+        pub fn pending(&self, now: DateTime<Utc>) -> impl Iterator<Item = &Invitation> {
+            self.invitations.iter().filter(move |i| i.is_valid(now))
+        }
+
+        /// Returns false if there's no such invitation, codes are compared
+        /// ignoring case.
+        pub fn revoke(&mut self, code: &str) -> bool {
+            let before = self.invitations.len();
+            self.invitations
+                .retain(|i| !i.code.eq_ignore_ascii_case(code));
+            self.invitations.len() != before
+        }
+
+        /// Removes and returns the invitation, unless it's expired.
+        pub fn redeem(&mut self, code: &str, now: DateTime<Utc>) -> Option<Invitation> {
+            let index = self
+                .invitations
+                .iter()
+                .position(|i| i.code.eq_ignore_ascii_case(code) && i.is_valid(now))?;
+
+            Some(self.invitations.remove(index))
+        }
+    }
+}
 
 pub mod actions {
+    use chrono::{DateTime, Duration, Utc};
+
+    use super::model::{Invitation, Invitations};
     use crate::library::actions::*;
-    use engine::prelude::Credentials;
+    use engine::prelude::{Credentials, HasRoles, HasUsernames};
 
     #[action]
     pub struct ChangePasswordAction {
@@ -52,7 +219,7 @@ pub mod actions {
         }
 
         fn perform(&self, _session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
-            let (_world, actor, _area) = surroundings.unpack();
+            let (_world, actor, _area) = surroundings.unpack()?;
             let mut credentials = actor.scope_mut::<Credentials>()?;
             credentials.set(self.password.to_owned());
             credentials.save()?;
@@ -60,6 +227,186 @@ pub mod actions {
             Ok(SimpleReply::Done.try_into()?)
         }
     }
+
+    fn find_user(world: &EntityPtr, username: &str) -> Result<Option<EntityPtr>> {
+        let Some(key) = world.find_name_key(username)? else {
+            return Ok(None);
+        };
+
+        Ok(get_my_session()?.entity(&LookupBy::Key(&key))?)
+    }
+
+    #[action]
+    pub struct GrantAction {
+        pub role: Role,
+        pub username: String,
+    }
+
+    impl Action for GrantAction {
+        fn is_read_only(&self) -> bool {
+            false
+        }
+
+        fn perform(&self, _session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
+            let (world, _actor, _area) = surroundings.unpack()?;
+            let Some(user) = find_user(&world, &self.username)? else {
+                return Ok(SimpleReply::NotFound.try_into()?);
+            };
+
+            info!(username = %self.username, role = %self.role, "granting");
+
+            user.grant_role(self.role)?;
+
+            Ok(SimpleReply::Done.try_into()?)
+        }
+    }
+
+    #[action]
+    pub struct RevokeAction {
+        pub role: Role,
+        pub username: String,
+    }
+
+    impl Action for RevokeAction {
+        fn is_read_only(&self) -> bool {
+            false
+        }
+
+        fn perform(&self, _session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
+            let (world, _actor, _area) = surroundings.unpack()?;
+            let Some(user) = find_user(&world, &self.username)? else {
+                return Ok(SimpleReply::NotFound.try_into()?);
+            };
+
+            info!(username = %self.username, role = %self.role, "revoking");
+
+            if !user.revoke_role(self.role)? {
+                return Ok(SimpleReply::Impossible.try_into()?);
+            }
+
+            Ok(SimpleReply::Done.try_into()?)
+        }
+    }
+
+    /// How long an invitation can be used to register.
+    const INVITATION_DAYS: i64 = 7;
+
+    fn observe_invitations<'a>(
+        session: &SessionRef,
+        invitations: impl Iterator<Item = &'a Invitation>,
+    ) -> Result<InvitationsReply, DomainError> {
+        let invitations = invitations
+            .map(|invitation| {
+                let inviter = session
+                    .entity(&LookupBy::Key(&invitation.inviter))?
+                    .map(|e| e.name())
+                    .transpose()?;
+
+                Ok(ObservedInvitation {
+                    code: invitation.code.clone(),
+                    inviter,
+                    created: invitation.created.to_rfc3339(),
+                    expires: invitation.expires.to_rfc3339(),
+                })
+            })
+            .collect::<Result<Vec<_>, DomainError>>()?;
+
+        Ok(InvitationsReply { invitations })
+    }
+
+    /// Codes are easy to type, leaving out letters and digits that are
+    /// easily confused with each other.
+    fn invitation_code() -> String {
+        use rand_core::{OsRng, RngCore};
+
+        const ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+        (0..8)
+            .map(|_| ALPHABET[OsRng.next_u32() as usize % ALPHABET.len()] as char)
+            .collect()
+    }
+
+    /// Mints a single use code for somebody to register with.
+    #[action]
+    pub struct InviteAction {}
+
+    impl Action for InviteAction {
+        fn is_read_only(&self) -> bool {
+            false
+        }
+
+        fn perform(&self, session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
+            let (world, actor, _area) = surroundings.unpack()?;
+
+            info!("inviting");
+
+            let now = Utc::now();
+            let invitation = Invitation {
+                code: invitation_code(),
+                inviter: actor.key(),
+                created: now,
+                expires: now + Duration::days(INVITATION_DAYS),
+            };
+
+            let mut invitations = world.scope_mut::<Invitations>()?;
+            if !invitations.mint(invitation.clone()) {
+                warn!("invitation code collided");
+
+                return Ok(SimpleReply::Impossible.try_into()?);
+            }
+            invitations.save()?;
+
+            Ok(observe_invitations(&session, [invitation].iter())?.try_into()?)
+        }
+    }
+
+    #[action]
+    pub struct InvitationsAction {}
+
+    impl Action for InvitationsAction {
+        fn is_read_only(&self) -> bool {
+            true
+        }
+
+        fn perform(&self, session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
+            let (world, _actor, _area) = surroundings.unpack()?;
+            let Some(invitations) = world.scope::<Invitations>()? else {
+                return Ok(InvitationsReply {
+                    invitations: Vec::new(),
+                }
+                .try_into()?);
+            };
+
+            let now: DateTime<Utc> = Utc::now();
+
+            Ok(observe_invitations(&session, invitations.pending(now))?.try_into()?)
+        }
+    }
+
+    #[action]
+    pub struct RevokeInvitationAction {
+        pub code: String,
+    }
+
+    impl Action for RevokeInvitationAction {
+        fn is_read_only(&self) -> bool {
+            false
+        }
+
+        fn perform(&self, _session: SessionRef, surroundings: &Surroundings) -> ReplyResult {
+            let (world, _actor, _area) = surroundings.unpack()?;
+
+            info!(code = %self.code, "revoking invitation");
+
+            let mut invitations = world.scope_mut::<Invitations>()?;
+            if !invitations.revoke(&self.code) {
+                return Ok(SimpleReply::NotFound.try_into()?);
+            }
+            invitations.save()?;
+
+            Ok(SimpleReply::Done.try_into()?)
+        }
+    }
 }
 
 pub mod parser {
@@ -96,12 +443,98 @@ pub mod parser {
             Ok(Some(action))
         }
     }
+
+    pub struct InviteActionParser {}
+
+    impl ParsesActions for InviteActionParser {
+        fn try_parse_action(&self, i: &str) -> EvaluationResult {
+            let (_, action) = map(tag("invite"), |_| InviteAction {})(i)?;
+
+            Ok(Some(Box::new(action)))
+        }
+    }
+
+    pub struct InvitationsActionParser {}
+
+    impl ParsesActions for InvitationsActionParser {
+        fn try_parse_action(&self, i: &str) -> EvaluationResult {
+            let (_, action) = map(tag("@invites"), |_| InvitationsAction {})(i)?;
+
+            Ok(Some(Box::new(action)))
+        }
+    }
+
+    pub struct RevokeInvitationActionParser {}
+
+    impl ParsesActions for RevokeInvitationActionParser {
+        fn try_parse_action(&self, i: &str) -> EvaluationResult {
+            let (_, action) = map(
+                preceded(pair(tag("@uninvite"), spaces), text_to_end_of_line),
+                |code: &str| RevokeInvitationAction {
+                    code: code.trim().to_owned(),
+                },
+            )(i)?;
+
+            Ok(Some(Box::new(action)))
+        }
+    }
+
+    fn role(i: &str) -> IResult<&str, Role> {
+        map_res(word, |w: &str| w.parse::<Role>())(i)
+    }
+
+    pub struct GrantActionParser {}
+
+    impl ParsesActions for GrantActionParser {
+        fn try_parse_action(&self, i: &str) -> EvaluationResult {
+            let (_, action) = map(
+                tuple((
+                    pair(tag("@grant"), spaces),
+                    role,
+                    tuple((spaces, tag("to"), spaces)),
+                    text_to_end_of_line,
+                )),
+                |(_, role, _, username)| GrantAction {
+                    role,
+                    username: username.trim().to_owned(),
+                },
+            )(i)?;
+
+            Ok(Some(Box::new(action)))
+        }
+    }
+
+    pub struct RevokeActionParser {}
+
+    impl ParsesActions for RevokeActionParser {
+        fn try_parse_action(&self, i: &str) -> EvaluationResult {
+            let (_, action) = map(
+                tuple((
+                    pair(tag("@revoke"), spaces),
+                    role,
+                    tuple((spaces, tag("from"), spaces)),
+                    text_to_end_of_line,
+                )),
+                |(_, role, _, username)| RevokeAction {
+                    role,
+                    username: username.trim().to_owned(),
+                },
+            )(i)?;
+
+            Ok(Some(Box::new(action)))
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use chrono::Duration;
+
+    use super::model::*;
     use super::parser::*;
+    use super::SecurityPluginFactory;
     use crate::library::tests::*;
+    use engine::prelude::{DevNullNotifier, EvaluateAs, HasRoles, HasUsernames, SessionOpener};
 
     #[test]
     fn it_sets_the_users_default_password() -> Result<()> {
@@ -111,7 +544,7 @@ mod tests {
         let action = try_parsing(ChangePasswordActionParser {}, "@password hellohello")?;
         let action = action.unwrap();
         let reply = action.perform(session.clone(), &surroundings)?;
-        let (_, _person, _area) = surroundings.unpack();
+        let (_, _person, _area) = surroundings.unpack()?;
 
         insta::assert_json_snapshot!(reply.to_debug_json()?);
 
@@ -119,4 +552,118 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn it_prevents_granting_roles_without_being_an_admin() -> Result<()> {
+        let mut plugins = RegisteredPlugins::default();
+        plugins.register(SecurityPluginFactory::default());
+        let mut build = BuildSurroundings::new_with_plugins(plugins)?;
+        let carla = build.with(build_entity().living().name("Carla").try_into()?)?;
+        let (_session, surroundings) = build.plain().build()?;
+        let (world, person, _area) = surroundings.unpack()?;
+        world.add_username_to_key("carla", &carla.key())?;
+
+        build.close()?;
+
+        let domain = build.domain().unwrap();
+        let perform = |text: &str| {
+            domain.evaluate_and_perform_as(
+                EvaluateAs::Key(&person.key()),
+                text,
+                &DevNullNotifier {},
+            )
+        };
+        let has_role = |key: &EntityKey, role: Role| -> Result<bool> {
+            let session = domain.open_session()?;
+            let entity = session.entity(&LookupBy::Key(key))?.unwrap();
+            let has = entity.has_role(role)?;
+            session.close(&DevNullNotifier {})?;
+            Ok(has)
+        };
+
+        assert_eq!(perform("@grant builder to carla")?, Some(Effect::Prevented));
+        assert!(!has_role(&carla.key(), Role::Builder)?);
+
+        {
+            let session = domain.open_session()?;
+            let admin = session.entity(&LookupBy::Key(&person.key()))?.unwrap();
+            admin.grant_role(Role::Admin)?;
+            session.close(&DevNullNotifier {})?;
+        }
+
+        let done: Effect = SimpleReply::Done.try_into()?;
+        assert_eq!(perform("@grant builder to carla")?, Some(done.clone()));
+        assert!(has_role(&carla.key(), Role::Builder)?);
+        assert!(!has_role(&carla.key(), Role::Admin)?);
+
+        assert_eq!(perform("@revoke builder from carla")?, Some(done));
+        assert!(!has_role(&carla.key(), Role::Builder)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_redeems_invitations_once_and_until_they_expire() -> Result<()> {
+        let now = Utc::now();
+        let mut invitations = Invitations::default();
+        invitations.mint(Invitation {
+            code: "ABCD2345".to_owned(),
+            inviter: EntityKey::new("inviter"),
+            created: now,
+            expires: now + Duration::days(1),
+        });
+
+        assert!(invitations
+            .redeem("ABCD2345", now + Duration::days(2))
+            .is_none());
+        assert!(invitations.redeem("abcd2345", now).is_some());
+        assert!(invitations.redeem("ABCD2345", now).is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_mints_invitations_that_only_admins_can_see_and_revoke() -> Result<()> {
+        let mut plugins = RegisteredPlugins::default();
+        plugins.register(SecurityPluginFactory::default());
+        let mut build = BuildSurroundings::new_with_plugins(plugins)?;
+        let (_session, surroundings) = build.plain().build()?;
+        let (_world, person, _area) = surroundings.unpack()?;
+
+        build.close()?;
+
+        let domain = build.domain().unwrap();
+        let perform = |text: &str| {
+            domain.evaluate_and_perform_as(
+                EvaluateAs::Key(&person.key()),
+                text,
+                &DevNullNotifier {},
+            )
+        };
+
+        let invited: InvitationsReply = perform("invite")?.unwrap().json_as()?;
+        assert_eq!(invited.invitations.len(), 1);
+        let code = invited.invitations[0].code.clone();
+        assert_eq!(code.len(), 8);
+
+        assert_eq!(perform("@invites")?, Some(Effect::Prevented));
+
+        {
+            let session = domain.open_session()?;
+            let admin = session.entity(&LookupBy::Key(&person.key()))?.unwrap();
+            admin.grant_role(Role::Admin)?;
+            session.close(&DevNullNotifier {})?;
+        }
+
+        let pending: InvitationsReply = perform("@invites")?.unwrap().json_as()?;
+        assert_eq!(pending, invited);
+
+        let done: Effect = SimpleReply::Done.try_into()?;
+        assert_eq!(perform(&format!("@uninvite {}", code))?, Some(done));
+
+        let pending: InvitationsReply = perform("@invites")?.unwrap().json_as()?;
+        assert!(pending.invitations.is_empty());
+
+        Ok(())
+    }
 }
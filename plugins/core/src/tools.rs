@@ -17,15 +17,11 @@ pub fn is_container(item: &EntityPtr) -> Result<bool, DomainError> {
     Ok(item.scope::<Containing>()?.is_some())
 }
 
-pub fn wear_article(
-    from: &EntityPtr,
-    to: &EntityPtr,
-    item: &EntityPtr,
-) -> Result<bool, DomainError> {
+pub fn wear_article(from: &EntityPtr, to: &EntityPtr, item: Found) -> Result<bool, DomainError> {
     change_location(
         from,
         to,
-        item.clone().into(),
+        item,
         |s: &mut Containing, item: Found| s.stop_carrying(item),
         |s: &mut Wearing, item: EntityPtr| {
             s.start_wearing(&item)?;
@@ -34,15 +30,11 @@ pub fn wear_article(
     )
 }
 
-pub fn remove_article(
-    from: &EntityPtr,
-    to: &EntityPtr,
-    item: &EntityPtr,
-) -> Result<bool, DomainError> {
+pub fn remove_article(from: &EntityPtr, to: &EntityPtr, item: Found) -> Result<bool, DomainError> {
     change_location(
         from,
         to,
-        item.clone().into(),
+        item,
         |s: &mut Wearing, item: Found| s.stop_wearing(item),
         |s: &mut Containing, item: EntityPtr| {
             s.start_carrying(&item)?;
@@ -207,6 +199,30 @@ pub fn new_entity_from_template_ptr(template: &EntityPtr) -> Result<EntityPtr, D
     get_my_session()?.add_entity(entity)
 }
 
+pub fn new_entity_from_prototype_ptr(
+    prototype: &EntityPtr,
+    creator: &EntityPtr,
+    quantity: &Quantity,
+) -> Result<EntityPtr, DomainError> {
+    let mut builder = build_entity()
+        .instance_of(&prototype.borrow())
+        .creator(creator.entity_ref());
+
+    // Instances get their own kind, otherwise they would stack with and
+    // change the quantity of the prototype.
+    if prototype.scope::<Carryable>()?.is_some() {
+        builder = builder.default_scope::<Carryable>()?;
+    }
+
+    let instance = get_my_session()?.add_entity(builder.try_into()?)?;
+
+    if instance.scope::<Carryable>()?.is_some() {
+        set_quantity(&instance, quantity)?;
+    }
+
+    Ok(instance)
+}
+
 pub fn quantity(entity: &EntityPtr) -> Result<f32, DomainError> {
     let carryable = entity.scope::<Carryable>()?.unwrap();
     Ok(carryable.quantity())
@@ -249,27 +265,61 @@ pub fn separate<'a, 'b>(
     Ok((entity, separated))
 }
 
-pub fn duplicate(entity: &EntityPtr) -> Result<EntityPtr> {
+pub enum Portion {
+    Whole(EntityPtr),
+    Separated(EntityPtr),
+}
+
+/// Resolves how much of a stack a `Found` refers to, separating that quantity
+/// into a new entity unless the whole stack is involved. Returns `None` when
+/// the quantity is more than is available.
+pub fn portion(found: &Found) -> Result<Option<Portion>, DomainError> {
+    let entity = found.entity()?;
+    let available = match entity.scope::<Carryable>()? {
+        // Items that were initialized with 0 quantities are treated as 1.
+        Some(carryable) => carryable.quantity().max(1.0),
+        None => return Ok(Some(Portion::Whole(entity.clone()))),
+    };
+
+    let quantity = found.quantity();
+    let wanted = quantity.as_f32();
+    if wanted < 1.0 || wanted > available {
+        Ok(None)
+    } else if wanted == available {
+        Ok(Some(Portion::Whole(entity.clone())))
+    } else {
+        let (_original, separated) = separate(entity, &quantity)?;
+
+        Ok(Some(Portion::Separated(separated)))
+    }
+}
+
+pub fn duplicate(found: &Found) -> Result<EntityPtr> {
+    let entity = found.entity()?;
     let mut carryable = entity.scope_mut::<Carryable>()?;
-    carryable.increase_quantity(&1.0.into())?;
+    carryable.increase_quantity(&found.quantity())?;
     carryable.save()?;
 
     Ok(entity.clone())
 }
 
-pub fn obliterate(obliterating: &EntityPtr) -> Result<()> {
+pub fn obliterate(obliterating: Found) -> Result<bool> {
     // NOTE: It's very easy to get confused about which entity is which.
-    let location = obliterating.scope::<Location>()?.unwrap();
+    let location = obliterating.entity()?.scope::<Location>()?.unwrap();
     if let Some(container) = &location.container {
         let container = container.to_entity()?;
         let mut containing = container.scope_mut::<Containing>()?;
 
-        containing.stop_carrying(obliterating.clone().into())?;
-        containing.save()?;
+        match containing.stop_carrying(obliterating)? {
+            Some(removed) => {
+                containing.save()?;
 
-        get_my_session()?.obliterate(obliterating)?;
+                get_my_session()?.obliterate(&removed)?;
 
-        Ok(())
+                Ok(true)
+            }
+            None => Ok(false),
+        }
     } else {
         Err(DomainError::ContainerRequired.into())
     }
@@ -280,6 +280,10 @@ impl From<Audience> for kernel::prelude::Audience {
                 Self::Individuals(keys.into_iter().map(|k| k.into()).collect())
             }
             Audience::Area(area) => Self::Area(area.into()),
+            Audience::Nearby { area, depth } => Self::Nearby {
+                area: area.into(),
+                depth,
+            },
         }
     }
 }
@@ -293,6 +297,10 @@ impl From<kernel::prelude::Audience> for Audience {
                 Audience::Individuals(keys.into_iter().map(|k| k.into()).collect())
             }
             kernel::prelude::Audience::Area(area) => Audience::Area(area.into()),
+            kernel::prelude::Audience::Nearby { area, depth } => Audience::Nearby {
+                area: area.into(),
+                depth,
+            },
         }
     }
 }
@@ -303,6 +311,7 @@ pub enum Audience {
     Everybody,
     Individuals(Vec<EntityKey>),
     Area(EntityKey),
+    Nearby { area: EntityKey, depth: u32 },
 }
 
 #[derive(Debug, Serialize, Deserialize, Encode, Decode, PartialEq, Clone)]
@@ -341,6 +350,13 @@ pub enum Surroundings {
         actor: EntityKey,
         area: EntityKey,
     },
+    World {
+        world: EntityKey,
+    },
+    Area {
+        world: EntityKey,
+        area: EntityKey,
+    },
 }
 
 impl TryFrom<&kernel::prelude::EntityPtr> for Json {
@@ -362,6 +378,13 @@ impl TryFrom<&kernel::prelude::Surroundings> for Surroundings {
                 actor: actor.key().into(),
                 area: area.key().into(),
             }),
+            kernel::prelude::Surroundings::World { world } => Ok(Self::World {
+                world: world.key().into(),
+            }),
+            kernel::prelude::Surroundings::Area { world, area } => Ok(Self::Area {
+                world: world.key().into(),
+                area: area.key().into(),
+            }),
         }
     }
 }
@@ -59,6 +59,10 @@ pub fn have_surroundings(
         kernel::prelude::Surroundings::Actor { world, actor, area } => {
             vec![world.key().clone(), actor.key().clone(), area.key().clone()]
         }
+        kernel::prelude::Surroundings::World { world } => vec![world.key().clone()],
+        kernel::prelude::Surroundings::Area { world, area } => {
+            vec![world.key().clone(), area.key().clone()]
+        }
     };
     let lookups: Vec<_> = keys
         .into_iter()
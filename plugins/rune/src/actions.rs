@@ -166,7 +166,7 @@ impl Action for RegisterAction {
                     let mut runner = RuneRunner::new(&schema, script)?;
                     if let Some(post) = runner.call(Call::Register)? {
                         let rr = RuneReturn::new(vec![post.flush()?])?;
-                        rr.handle(surroundings.actor())?;
+                        rr.handle(surroundings.actor()?)?;
                     }
 
                     Ok(SimpleReply::Done.try_into()?)
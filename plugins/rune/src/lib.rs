@@ -55,7 +55,12 @@ impl Plugin for RunePlugin {
     }
 
     fn schema(&self) -> Schema {
-        Schema::empty().action::<actions::RuneAction>()
+        Schema::empty()
+            .action_requiring::<actions::EditAction>(Role::Builder)
+            .action_requiring::<actions::DiagnosticsAction>(Role::Builder)
+            .action_requiring::<actions::SaveScriptAction>(Role::Builder)
+            .action_requiring::<actions::RegisterAction>(Role::Builder)
+            .action_requiring::<actions::RuneAction>(Role::Admin)
     }
 
     fn key(&self) -> &'static str {
@@ -75,6 +80,15 @@ impl Plugin for RunePlugin {
         Ok(())
     }
 
+    fn verbs(&self) -> Vec<String> {
+        let mut verbs = self.schema().verbs();
+        match self.runners.verbs() {
+            Ok(provided) => verbs.extend(provided),
+            Err(e) => warn!("verbs: {:?}", e),
+        }
+        verbs
+    }
+
     fn sources(&self) -> Vec<Box<dyn ActionSource>> {
         vec![Box::new(ActionSources::default())]
     }
@@ -267,6 +281,7 @@ impl TryFindActor for Surroundings {
                 actor,
                 area: _,
             } => Ok(Some(actor.clone())),
+            Surroundings::World { world: _ } | Surroundings::Area { world: _, area: _ } => Ok(None),
         }
     }
 }
@@ -1,6 +1,6 @@
 use kernel::{
     common::Json,
-    prelude::{DomainError, EntityKey, EntityPtr, IntoEntityPtr, LookupBy},
+    prelude::{DomainError, EntityKey, EntityPtr, EntityPtrResolver, IntoEntityPtr, LookupBy},
     session::get_my_session,
 };
 use rune::runtime::{Object, Protocol};
@@ -54,6 +54,17 @@ impl Bag {
             .map(|r| LocalEntity(r))
     }
 
+    /// World level events have no actor or area, so the world is always
+    /// available even when it's not in the event.
+    fn world(&self) -> Option<LocalEntity> {
+        self.get("world").or_else(|| {
+            get_my_session()
+                .ok()
+                .and_then(|session| session.world().ok().flatten())
+                .map(LocalEntity)
+        })
+    }
+
     fn item(&self) -> Option<LocalEntity> {
         self.get("item")
     }
@@ -127,6 +138,7 @@ pub(super) fn create(schema: &SchemaCollection, owner: Option<Owner>) -> Result<
     module.associated_function(Protocol::STRING_DEBUG, AfterEffect::string_debug)?;
     module.ty::<Bag>()?;
     module.associated_function(Protocol::STRING_DEBUG, Bag::string_debug)?;
+    module.associated_function("world", Bag::world)?;
     module.associated_function("area", Bag::area)?;
     module.associated_function("item", Bag::item)?;
     module.associated_function("actor", Bag::actor)?;
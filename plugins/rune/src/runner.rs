@@ -221,6 +221,13 @@ impl RuneRunner {
         }
     }
 
+    pub fn verbs(&mut self) -> Result<Vec<String>> {
+        Ok(self
+            .commands()?
+            .map(|commands| commands.verbs())
+            .unwrap_or_default())
+    }
+
     pub fn before(&mut self, perform: Perform) -> Result<Option<PostEvaluation<Perform>>> {
         self.invoke("before", (BeforePerform(perform.clone()),))?;
 
@@ -583,6 +590,16 @@ impl SharedRunners {
         Ok(returned)
     }
 
+    pub fn verbs(&self) -> Result<Vec<String>> {
+        let mut runners = self.0.borrow_mut();
+        let mut verbs = Vec::new();
+        for runner in runners.runners.iter_mut() {
+            verbs.extend(runner.verbs()?);
+        }
+
+        Ok(verbs)
+    }
+
     pub fn before(&self, value: Perform) -> Result<Option<Perform>> {
         let mut runners = self.0.borrow_mut();
 
@@ -753,6 +770,13 @@ struct Command {
 }
 
 impl Command {
+    fn verb(&self) -> Option<String> {
+        match self.tongue.first() {
+            Some(English::Literal(v)) => Some(v.to_lowercase()),
+            _ => None,
+        }
+    }
+
     fn try_parse(&self, text: &str) -> Option<ParsedCommand> {
         match english::try_parse(&self.tongue, text) {
             Some(node) => Some(ParsedCommand {
@@ -777,6 +801,10 @@ impl ProvidedCommands {
     fn try_parse(&self, text: &str) -> Option<ParsedCommand> {
         self.commands.iter().flat_map(|c| c.try_parse(text)).next()
     }
+
+    fn verbs(&self) -> Vec<String> {
+        self.commands.iter().flat_map(|c| c.verb()).collect()
+    }
 }
 
 #[derive(Debug)]
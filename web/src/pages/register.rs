@@ -67,6 +67,15 @@ pub fn register_page() -> Html {
             register_info.set(info);
         })
     };
+    let oninput_invite = {
+        let register_info = register_info.clone();
+        Callback::from(move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            let mut info = (*register_info).clone();
+            info.invite = Some(input.value()).filter(|v| !v.is_empty());
+            register_info.set(info);
+        })
+    };
 
     html! {
         <div class="auth-page">
@@ -109,6 +118,15 @@ pub fn register_page() -> Html {
                                         oninput={oninput_password}
                                         />
                                 </fieldset>
+                                <fieldset class="form-group">
+                                    <input
+                                        class="form-control form-control-lg"
+                                        type="text"
+                                        placeholder="Invitation"
+                                        value={register_info.invite.clone().unwrap_or_default()}
+                                        oninput={oninput_invite}
+                                        />
+                                </fieldset>
                                 <fieldset class="form-group">
                                     <button
                                         class="btn btn-lg btn-primary pull-xs-right"
@@ -26,7 +26,8 @@ pub enum WebSocketMessage {
     Evaluate(String),
     Perform(JsonValue),
     Reply(JsonValue),
-    Notify((String, JsonValue)),
+    Notify((String, JsonValue, Option<u64>)),
+    Acknowledge(u64),
     Error(String),
     Ping,
 }
@@ -166,6 +166,111 @@ fn inside_observation(reply: &InsideObservation) -> Html {
     }
 }
 
+fn ambiguous_reply(reply: &AmbiguousReply) -> Html {
+    let candidates = reply
+        .candidates
+        .iter()
+        .enumerate()
+        .map(|(i, e)| html!(<li>{ i + 1 }{ ". " }{ &e.qualified }{ NBSP }{ gid_span(e.gid) }</li>))
+        .collect::<Vec<_>>();
+
+    html! {
+        <div class="entry ambiguous">
+            { "Which do you mean?" }
+            <ol>{ candidates }</ol>
+        </div>
+    }
+}
+
+fn suggestions_reply(reply: &SuggestionsReply) -> Html {
+    let suggestions = reply
+        .suggestions
+        .iter()
+        .map(|s| html!(<li>{ s }</li>))
+        .collect::<Vec<_>>();
+
+    html! {
+        <div class="entry suggestions">
+            { "Did you mean?" }
+            <ul>{ suggestions }</ul>
+        </div>
+    }
+}
+
+fn futures_reply(reply: &FuturesReply) -> Html {
+    let futures = reply
+        .futures
+        .iter()
+        .map(|f| match &f.cron {
+            Some(cron) => {
+                html!(<li>{ &f.key }{ NBSP }{ &f.time }{ NBSP }{ "(" }{ cron }{ ")" }</li>)
+            }
+            None => html!(<li>{ &f.key }{ NBSP }{ &f.time }</li>),
+        })
+        .collect::<Vec<_>>();
+
+    html! {
+        <div class="entry futures">
+            { &reply.entity.qualified }{ NBSP }{ gid_span(reply.entity.gid) }
+            <ul>{ futures }</ul>
+        </div>
+    }
+}
+
+fn history_reply(reply: &HistoryReply) -> Html {
+    let versions = reply
+        .versions
+        .iter()
+        .map(|v| html!(<li>{ v.version }{ NBSP }{ v.differences.join(", ") }</li>))
+        .collect::<Vec<_>>();
+
+    html! {
+        <div class="entry history">
+            { &reply.entity.qualified }{ NBSP }{ gid_span(reply.entity.gid) }
+            <ul>{ versions }</ul>
+        </div>
+    }
+}
+
+fn moderation_reply(reply: &ModerationReply) -> Html {
+    let log = reply
+        .log
+        .iter()
+        .map(|m| {
+            html!(<li>{ &m.time }{ NBSP }{ &m.restriction }{ NBSP }{ &m.change }{ NBSP }{ m.reason.clone().unwrap_or_default() }</li>)
+        })
+        .collect::<Vec<_>>();
+
+    html! {
+        <div class="entry moderation">
+            { &reply.entity.qualified }{ NBSP }{ gid_span(reply.entity.gid) }
+            <ul>{ log }</ul>
+        </div>
+    }
+}
+
+fn invitations_reply(reply: &InvitationsReply) -> Html {
+    let invitations = reply
+        .invitations
+        .iter()
+        .map(|i| {
+            html!(<li>{ &i.code }{ NBSP }{ i.inviter.clone().unwrap_or_default() }{ NBSP }{ &i.expires }</li>)
+        })
+        .collect::<Vec<_>>();
+
+    html! {
+        <div class="entry invitations">
+            <ul>{ invitations }</ul>
+        </div>
+    }
+}
+
+fn denied_reply(reply: &DeniedReply) -> Html {
+    html! {
+        <div class="entry denied">{ format!("Denied: {}", reply.paths.join(", ")) }</div>
+    }
+}
+
 fn simple_reply(reply: &SimpleReply) -> Html {
     html! {
         <div class="entry simple">{ format!("{:?}", reply) }</div>
@@ -191,6 +296,13 @@ impl Render for AllKnownItems {
             Self::InsideObservation(reply) => Some(inside_observation(&reply)),
             Self::SimpleReply(reply) => Some(simple_reply(&reply)),
             Self::EntityObservation(entity) => Some(entity_observation(&entity)),
+            Self::AmbiguousReply(reply) => Some(ambiguous_reply(&reply)),
+            Self::SuggestionsReply(reply) => Some(suggestions_reply(&reply)),
+            Self::FuturesReply(reply) => Some(futures_reply(&reply)),
+            Self::HistoryReply(reply) => Some(history_reply(&reply)),
+            Self::ModerationReply(reply) => Some(moderation_reply(&reply)),
+            Self::InvitationsReply(reply) => Some(invitations_reply(&reply)),
+            Self::DeniedReply(reply) => Some(denied_reply(&reply)),
             Self::MarkdownReply(value) => Some(markdown_reply(&value)),
 
             Self::EditorReply(_) => None,
@@ -198,7 +310,10 @@ impl Render for AllKnownItems {
 
             Self::Carrying(event) => event.render(myself),
             Self::Moving(event) => event.render(myself),
+            Self::Locking(event) => event.render(myself),
+            Self::Consuming(event) => event.render(myself),
             Self::Talking(event) => event.render(myself),
+            Self::Distant(event) => event.render(myself),
 
             Self::Diagnostics(diagnostics) => diagnostics.render(myself),
         }
@@ -262,6 +377,13 @@ impl Render for Carrying {
             } => Some(
                 html! { <div class="entry"> { subject(actor) } { " dropped " } { thing(item) }</div> },
             ),
+            Carrying::Given {
+                actor,
+                item,
+                receiver,
+            } => Some(
+                html! { <div class="entry"> { subject(actor) } { " gave " } { thing(item) } { " to " } { thing(receiver) }</div> },
+            ),
         }
     }
 }
@@ -279,6 +401,53 @@ impl Render for Moving {
     }
 }
 
+fn lockable(item: &ObservedEntity, route: &Option<String>) -> Html {
+    match route {
+        Some(route) => html! { <span>{ route }</span> },
+        None => thing(item),
+    }
+}
+
+impl Render for Locking {
+    fn render(&self, _myself: &Myself) -> Option<Html> {
+        match self {
+            Locking::Locked { actor, item, route } => Some(
+                html! { <div class="entry"> { subject(actor) } { " locked " } { lockable(item, route) }</div> },
+            ),
+            Locking::Unlocked { actor, item, route } => Some(
+                html! { <div class="entry"> { subject(actor) } { " unlocked " } { lockable(item, route) }</div> },
+            ),
+            Locking::Opened { actor, item, route } => Some(
+                html! { <div class="entry"> { subject(actor) } { " opened " } { lockable(item, route) }</div> },
+            ),
+            Locking::Closed { actor, item, route } => Some(
+                html! { <div class="entry"> { subject(actor) } { " closed " } { lockable(item, route) }</div> },
+            ),
+        }
+    }
+}
+
+impl Render for Consuming {
+    fn render(&self, _myself: &Myself) -> Option<Html> {
+        match self {
+            Consuming::Ate {
+                actor,
+                item,
+                area: _,
+            } => Some(
+                html! { <div class="entry"> { subject(actor) } { " ate " } { thing(item) }</div> },
+            ),
+            Consuming::Drank {
+                actor,
+                item,
+                area: _,
+            } => Some(
+                html! { <div class="entry"> { subject(actor) } { " drank " } { thing(item) }</div> },
+            ),
+        }
+    }
+}
+
 impl Render for Talking {
     fn render(&self, _myself: &Myself) -> Option<Html> {
         match self {
@@ -286,6 +455,27 @@ impl Render for Talking {
                 html! { <div class="entry"> <span class="speaker">{ &s.who.name }</span>{ ": " } { &s.message } </div> },
             ),
             Talking::Whispering(_) => todo!(),
+            Talking::Shouting(s) => Some(
+                html! { <div class="entry"> <span class="speaker">{ &s.who.name }</span>{ " shouts: " } { &s.message } </div> },
+            ),
+        }
+    }
+}
+
+impl Render for Distant {
+    fn render(&self, _myself: &Myself) -> Option<Html> {
+        let whereabouts = match &self.direction {
+            Some(direction) => format!(" to the {}", direction.to_lowercase()),
+            None => " in the distance".to_owned(),
+        };
+
+        match serde_json::from_value::<AllKnownItems>(self.event.clone()) {
+            Ok(AllKnownItems::Talking(Talking::Shouting(s))) => Some(
+                html! { <div class="entry">{ "You hear someone shouting \"" } { &s.message } { "\"" } { whereabouts } { "." }</div> },
+            ),
+            _ => Some(
+                html! { <div class="entry">{ "You hear something" } { whereabouts } { "." }</div> },
+            ),
         }
     }
 }
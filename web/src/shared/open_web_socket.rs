@@ -80,10 +80,23 @@ mod manage_connection {
                                                     append.dispatch(value);
                                                 }
                                             }
-                                            WebSocketMessage::Notify((key, value)) => {
+                                            WebSocketMessage::Notify((key, value, sequence)) => {
                                                 log::debug!("notify: key={:?}", key);
 
                                                 append.dispatch(value);
+
+                                                if let Some(sequence) = sequence {
+                                                    match serde_json::to_string(
+                                                        &WebSocketMessage::Acknowledge(sequence),
+                                                    ) {
+                                                        Ok(ack) => {
+                                                            if let Err(e) = c.try_send(Some(ack)) {
+                                                                log::warn!("ack: {:?}", e);
+                                                            }
+                                                        }
+                                                        Err(e) => log::warn!("ack: {:?}", e),
+                                                    }
+                                                }
                                             }
                                             WebSocketMessage::Ping => log::trace!("ping"),
                                             _ => log::warn!("Unknown message: {:?}", item),
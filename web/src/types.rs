@@ -24,6 +24,7 @@ pub struct RegisterInfo {
     pub name: String,
     pub email: String,
     pub password: String,
+    pub invite: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -84,12 +85,22 @@ pub enum AllKnownItems {
     AreaObservation(AreaObservation),
     InsideObservation(InsideObservation),
     EntityObservation(EntityObservation),
+    AmbiguousReply(AmbiguousReply),
+    SuggestionsReply(SuggestionsReply),
+    FuturesReply(FuturesReply),
+    HistoryReply(HistoryReply),
+    ModerationReply(ModerationReply),
+    InvitationsReply(InvitationsReply),
+    DeniedReply(DeniedReply),
     EditorReply(EditorReply),
     MarkdownReply(MarkdownReply),
     JsonReply(JsonReply),
     Carrying(Carrying),
     Moving(Moving),
+    Locking(Locking),
+    Consuming(Consuming),
     Talking(Talking),
+    Distant(Distant),
     Diagnostics(Diagnostics),
 }
 